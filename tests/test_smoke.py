@@ -15,6 +15,8 @@ def test_import_xml_processor():
     """Test that xml_processor can be imported."""
     from polars_dovmed import xml_processor
     assert hasattr(xml_processor, "nxml")
+    assert hasattr(xml_processor, "XmlParseError")
+    assert hasattr(xml_processor, "InputTooLargeError")
     print("✓ xml_processor module loaded")
 
 
@@ -51,8 +53,45 @@ def test_xml_processor_functions():
     
     # Check for nxml submodule functions
     assert hasattr(xml_processor.nxml, "xml_to_polars")
+    assert hasattr(xml_processor.nxml, "xml_to_polars_chunks")
+    assert hasattr(xml_processor.nxml, "validate_xml")
+    assert hasattr(xml_processor.nxml, "ndjson_to_polars")
+    assert hasattr(xml_processor.nxml, "current_schema_version")
+    assert hasattr(xml_processor.nxml, "normalize_identifiers")
+    assert hasattr(xml_processor.nxml, "parse_xml_string")
+    assert hasattr(xml_processor.nxml, "parse_multi_article_xml")
+    assert hasattr(xml_processor.nxml, "build_article_index")
+    assert hasattr(xml_processor.nxml, "read_article_at")
+    assert hasattr(xml_processor.nxml, "parse_article")
+    assert hasattr(xml_processor.nxml, "xml_to_paragraphs")
+    assert hasattr(xml_processor.nxml, "xml_to_sentences")
+    assert hasattr(xml_processor.nxml, "xml_to_sections")
+    assert hasattr(xml_processor.nxml, "xml_to_funding")
+    assert hasattr(xml_processor.nxml, "xml_to_figures")
+    assert hasattr(xml_processor.nxml, "xml_to_supplementary")
+    assert hasattr(xml_processor.nxml, "xml_to_entities")
+    assert hasattr(xml_processor.nxml, "xml_to_xrefs")
+    assert hasattr(xml_processor.nxml, "xml_to_tables")
+    assert hasattr(xml_processor.nxml, "batch_xml_split_output")
+    assert hasattr(xml_processor.nxml, "batch_xml_to_json_array")
+    assert hasattr(xml_processor.nxml, "batch_xml_to_parquet")
+    assert hasattr(xml_processor.nxml, "xml_to_parquet_dataset")
+    assert hasattr(xml_processor.nxml, "batch_xml_to_ipc")
+    assert hasattr(xml_processor.nxml, "references_to_polars")
+    assert hasattr(xml_processor.nxml, "xml_to_references")
     assert hasattr(xml_processor.nxml, "xml_to_ndjson")
+    assert hasattr(xml_processor.nxml, "scan_directory")
     assert hasattr(xml_processor.nxml, "batch_xml_to_ndjson")
+    assert hasattr(xml_processor.nxml, "batch_xml_to_ndjson_chunked")
+    assert hasattr(xml_processor.nxml, "batch_xml_to_ndjson_with_report")
+    assert hasattr(xml_processor.nxml, "batch_xml_to_ndjson_with_summary")
+    assert hasattr(xml_processor.nxml, "batch_xml_to_ndjson_from_archive")
+    assert hasattr(xml_processor.nxml, "XmlNdjsonWriter")
     assert hasattr(xml_processor.nxml, "search_xml_content")
+    assert hasattr(xml_processor.nxml, "search_dataframe")
+    assert hasattr(xml_processor.nxml, "count_xml_matches")
+    assert hasattr(xml_processor.nxml, "word_frequencies")
+    assert hasattr(xml_processor.nxml, "tag_terms")
+    assert hasattr(xml_processor.nxml, "extract_accessions")
     
     print("✓ XML processor functions available")
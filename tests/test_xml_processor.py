@@ -0,0 +1,450 @@
+"""Behaviour tests for the xml_processor extension."""
+
+import json
+import logging
+import time
+
+import polars as pl
+import pytest
+
+from polars_dovmed import xml_processor
+
+nxml = xml_processor.nxml
+
+
+def article_xml(
+    pmid, title="An article", abstract="An abstract.", body="Body text.", year=None
+):
+    """A minimal JATS article with a PMID, title, abstract and body, published
+    in `year` if one is given."""
+    pub_date = f'<pub-date pub-type="epub"><year>{year}</year></pub-date>' if year else ""
+    return f"""<article article-type="research-article">
+<front><article-meta>
+<article-id pub-id-type="pmid">{pmid}</article-id>
+<title-group><article-title>{title}</article-title></title-group>
+{pub_date}
+<abstract><p>{abstract}</p></abstract>
+</article-meta></front>
+<body><p>{body}</p></body>
+</article>"""
+
+
+def write_articles(tmp_path, count, **fields):
+    """Write `count` articles with PMIDs 1..count, returning their paths."""
+    paths = []
+    for pmid in range(1, count + 1):
+        path = tmp_path / f"article_{pmid}.xml"
+        path.write_text(article_xml(pmid, **fields))
+        paths.append(str(path))
+    return paths
+
+
+def read_ndjson(path):
+    """The records of an NDJSON file."""
+    with open(path) as f:
+        return [json.loads(line) for line in f if line.strip()]
+
+
+def test_ndjson_lines_follow_input_order_for_any_thread_count(tmp_path):
+    """Records come out in `xml_paths` order whether parsed on one thread or many."""
+    paths = write_articles(tmp_path, 50)
+    paths.reverse()
+
+    single = tmp_path / "single.ndjson"
+    parallel = tmp_path / "parallel.ndjson"
+    nxml.batch_xml_to_ndjson(paths, str(single), num_threads=1)
+    nxml.batch_xml_to_ndjson(paths, str(parallel), num_threads=8)
+
+    expected = [str(pmid) for pmid in range(50, 0, -1)]
+    assert [record["pmid"] for record in read_ndjson(single)] == expected
+    assert [record["pmid"] for record in read_ndjson(parallel)] == expected
+
+
+def test_progress_is_reported_every_report_every_files(tmp_path):
+    """The callback sees the running file count at each multiple of `report_every`."""
+    paths = write_articles(tmp_path, 7)
+    reported = []
+
+    nxml.batch_xml_to_ndjson(
+        paths, str(tmp_path / "out.ndjson"), progress=reported.append, report_every=3
+    )
+
+    assert reported == [3, 6]
+
+
+def test_an_exception_in_the_progress_callback_aborts_the_run(tmp_path):
+    """An exception raised by the callback stops the run and reaches the caller."""
+    paths = write_articles(tmp_path, 5)
+    output = tmp_path / "out.ndjson"
+
+    def stop_at_two(files_done):
+        if files_done == 2:
+            raise RuntimeError("stopped")
+
+    with pytest.raises(RuntimeError, match="stopped"):
+        nxml.batch_xml_to_ndjson(paths, str(output), progress=stop_at_two, report_every=1)
+
+    assert len(read_ndjson(output)) == 2
+
+
+def test_a_malformed_file_raises_the_parse_error_subclass(tmp_path):
+    """Malformed XML raises XmlParseError, which callers can tell from other ValueErrors."""
+    path = tmp_path / "malformed.xml"
+    path.write_text("<article><front><title>Unclosed</front></article>")
+
+    with pytest.raises(xml_processor.XmlParseError) as excinfo:
+        nxml.parse_article(str(path))
+
+    assert type(excinfo.value) is not ValueError
+    assert isinstance(excinfo.value, ValueError)
+
+
+def test_named_capture_groups_become_columns(tmp_path):
+    """Each named group of a search pattern gets a column of its captures."""
+    path = tmp_path / "geo.xml"
+    path.write_text(article_xml(1, abstract="Data are in GSE12345 and GSE678."))
+
+    result = nxml.search_xml_content([str(path)], [r"(?P<db>GSE)(?P<id>\d+)"])
+
+    assert result["db"].to_list() == ["GSE", "GSE"]
+    assert result["id"].to_list() == ["12345", "678"]
+
+
+def test_whole_word_skips_matches_inside_longer_words(tmp_path):
+    """With `whole_word`, "gene" matches in "the gene is" but not in "genetic"."""
+    path = tmp_path / "gene.xml"
+    path.write_text(article_xml(1, abstract="the gene is genetic"))
+
+    whole = nxml.search_xml_content([str(path)], ["gene"], fields=["abstract"], whole_word=True)
+    partial = nxml.search_xml_content([str(path)], ["gene"], fields=["abstract"])
+
+    assert whole["matched_text"].to_list() == ["gene"]
+    assert whole["match_start"].to_list() == [4]
+    assert partial.height == 2
+
+
+def test_case_sensitive_flags_apply_per_pattern(tmp_path):
+    """A case-sensitive and a case-insensitive pattern can be mixed in one search."""
+    path = tmp_path / "genes.xml"
+    path.write_text(article_xml(1, abstract="BRCA1 brca1 and TP53"))
+
+    result = nxml.search_xml_content(
+        [str(path)],
+        ["BRCA1", "tp53"],
+        fields=["abstract"],
+        case_sensitive_flags=[True, False],
+    )
+
+    assert result["matched_pattern"].to_list() == ["BRCA1", "tp53"]
+    assert result["matched_text"].to_list() == ["BRCA1", "TP53"]
+
+
+def test_dedupe_by_writes_a_repeated_article_once(tmp_path):
+    """The same article given twice is written once with `dedupe_by="pmid"`."""
+    paths = write_articles(tmp_path, 1)
+    output = tmp_path / "out.ndjson"
+
+    written, skipped, _ = nxml.batch_xml_to_ndjson_with_stats(
+        paths * 2, str(output), dedupe_by="pmid"
+    )
+
+    assert (written, skipped) == (1, 1)
+    assert len(read_ndjson(output)) == 1
+
+
+def test_batch_results_keep_one_shape_whatever_the_options(tmp_path):
+    """`batch_xml_to_ndjson` always returns a count and the `_with_stats`
+    variant always a (written, skipped, oversized_paths) tuple."""
+    paths = write_articles(tmp_path, 2)
+    output = str(tmp_path / "out.ndjson")
+
+    assert nxml.batch_xml_to_ndjson(paths, output) == 2
+    assert nxml.batch_xml_to_ndjson(paths, output, dedupe_by="pmid", max_bytes=1) == 0
+    assert nxml.batch_xml_to_ndjson_with_stats(paths, output) == (2, 0, [])
+    assert nxml.batch_xml_to_ndjson_with_stats(paths, output, max_bytes=1) == (0, 0, paths)
+
+
+def test_an_unreadable_path_is_logged_as_a_warning(tmp_path, caplog):
+    """Skipped files are reported through Python's `logging`, not printed."""
+    missing = tmp_path / "missing.xml"
+
+    with caplog.at_level(logging.WARNING):
+        written = nxml.batch_xml_to_ndjson([str(missing)], str(tmp_path / "out.ndjson"))
+
+    assert written == 0
+    warnings = [r for r in caplog.records if r.levelno == logging.WARNING]
+    assert any(str(missing) in r.getMessage() for r in warnings)
+
+
+def test_parquet_output_matches_xml_to_polars(tmp_path):
+    """The Parquet file holds the rows and columns `xml_to_polars` returns."""
+    paths = write_articles(tmp_path, 3)
+    output = tmp_path / "articles.parquet"
+
+    rows = nxml.batch_xml_to_parquet(paths, str(output))
+    expected = nxml.xml_to_polars(paths)
+    written = pl.read_parquet(output)
+
+    assert rows == 3
+    assert written.height == expected.height
+    assert written.columns == expected.columns
+
+
+def test_ipc_output_keeps_the_xml_to_polars_schema(tmp_path):
+    """The IPC file reads back with the schema and rows of `xml_to_polars`."""
+    paths = write_articles(tmp_path, 3)
+    output = tmp_path / "articles.arrow"
+
+    rows = nxml.batch_xml_to_ipc(paths, str(output))
+    expected = nxml.xml_to_polars(paths)
+    written = pl.read_ipc(output)
+
+    assert rows == 3
+    assert written.schema == expected.schema
+    assert written.equals(expected)
+
+
+def test_parquet_dataset_splits_rows_into_parts(tmp_path):
+    """Five articles at `rows_per_file=2` make three parts of 2, 2 and 1 rows."""
+    paths = write_articles(tmp_path, 5)
+
+    parts = nxml.xml_to_parquet_dataset(paths, str(tmp_path / "dataset"), rows_per_file=2)
+
+    assert [pl.read_parquet(part).height for part in parts] == [2, 2, 1]
+    assert [part.rsplit("/", 1)[-1] for part in parts] == [
+        "part-00000.parquet",
+        "part-00001.parquet",
+        "part-00002.parquet",
+    ]
+
+
+def test_chunks_hold_at_most_chunk_size_articles(tmp_path):
+    """Five articles in chunks of two come out as 2, 2 and 1 rows, in order."""
+    paths = write_articles(tmp_path, 5)
+
+    chunks = list(nxml.xml_to_polars_chunks(paths, 2))
+
+    assert [chunk.height for chunk in chunks] == [2, 2, 1]
+    assert pl.concat(chunks)["pmid"].to_list() == ["1", "2", "3", "4", "5"]
+
+
+def test_min_year_leaves_out_older_articles(tmp_path):
+    """A 2018 article is skipped with `min_year=2020`; a 2021 one is written."""
+    old = tmp_path / "old.xml"
+    old.write_text(article_xml(1, year=2018))
+    new = tmp_path / "new.xml"
+    new.write_text(article_xml(2, year=2021))
+    output = tmp_path / "out.ndjson"
+
+    written, skipped, _ = nxml.batch_xml_to_ndjson_with_stats(
+        [str(old), str(new)], str(output), min_year=2020
+    )
+
+    assert (written, skipped) == (1, 1)
+    assert [record["pmid"] for record in read_ndjson(output)] == ["2"]
+
+
+def test_a_field_search_ignores_matches_in_other_fields(tmp_path):
+    """A pattern only in the body isn't found when just `["title"]` is searched."""
+    path = tmp_path / "body.xml"
+    path.write_text(article_xml(1, body="Edited with CRISPR."))
+
+    in_title = nxml.search_xml_content([str(path)], ["CRISPR"], fields=["title"])
+    in_body = nxml.search_xml_content([str(path)], ["CRISPR"], fields=["title", "full_text"])
+
+    assert in_title.height == 0
+    assert in_body["field"].to_list() == ["full_text"]
+
+
+def test_match_offsets_slice_back_to_the_matched_text(tmp_path):
+    """`match_start`/`match_end` are byte offsets into the searched XML."""
+    path = tmp_path / "offsets.xml"
+    path.write_text(article_xml(1, abstract="Müller et al. found BRCA1 and BRCA2."), encoding="utf-8")
+    xml_bytes = path.read_bytes()
+
+    result = nxml.search_xml_content([str(path)], [r"BRCA\d"])
+
+    assert result["matched_text"].to_list() == ["BRCA1", "BRCA2"]
+    for row in result.iter_rows(named=True):
+        matched = xml_bytes[row["match_start"] : row["match_end"]].decode("utf-8")
+        assert matched == row["matched_text"]
+
+
+def test_ndjson_reads_back_into_the_xml_to_polars_frame(tmp_path):
+    """XML -> NDJSON -> DataFrame gives the frame `xml_to_polars` builds directly."""
+    paths = write_articles(tmp_path, 3)
+    output = tmp_path / "articles.ndjson"
+
+    nxml.batch_xml_to_ndjson(paths, str(output))
+    round_tripped = nxml.ndjson_to_polars(str(output))
+    expected = nxml.xml_to_polars(paths)
+
+    assert round_tripped.schema == expected.schema
+    assert round_tripped.equals(expected)
+
+
+def test_accessions_are_found_in_the_body_text(tmp_path):
+    """GEO and GenBank ids are found; years and amounts aren't taken for PDB ids."""
+    path = tmp_path / "accessions.xml"
+    path.write_text(
+        article_xml(
+            1,
+            body="Reads are in GSE12345 and the sequence is AB123456. "
+            "Structure 1ABC was solved in 2019 with 20mg of protein.",
+        )
+    )
+
+    result = nxml.extract_accessions([str(path)])
+
+    assert list(zip(result["accession_type"], result["accession_id"])) == [
+        ("geo", "GSE12345"),
+        ("genbank", "AB123456"),
+        ("pdb", "1ABC"),
+    ]
+
+
+def test_a_cleaned_context_has_no_tags(tmp_path):
+    """With `clean_context`, even a window that cuts through tags comes out tag-free."""
+    path = tmp_path / "tagged.xml"
+    path.write_text(article_xml(1, abstract="The <italic>BRCA1</italic> gene in <bold>breast</bold> cancer."))
+
+    result = nxml.search_xml_content(
+        [str(path)], ["BRCA1"], context_chars=25, clean_context=True
+    )
+
+    assert result.height == 1
+    context = result["match_context"][0]
+    assert "BRCA1" in context
+    assert "<" not in context and ">" not in context
+
+
+def test_the_ndjson_writer_writes_a_line_per_file(tmp_path):
+    """Opening, writing two files and closing leaves two records."""
+    paths = write_articles(tmp_path, 2)
+    output = tmp_path / "streamed.ndjson"
+
+    writer = nxml.XmlNdjsonWriter.open(str(output))
+    assert all(writer.write_file(path) for path in paths)
+    assert not writer.write_file(str(tmp_path / "missing.xml"))
+    written = writer.close()
+
+    assert written == 2
+    assert [record["pmid"] for record in read_ndjson(output)] == ["1", "2"]
+
+
+def test_search_dataframe_reports_row_and_column_of_each_match():
+    """Matches in a two-row frame carry their row index and column; nulls are skipped."""
+    df = pl.DataFrame(
+        {
+            "title": ["On TP53", "Nothing here"],
+            "abstract_text": ["TP53 and tp53.", None],
+        }
+    )
+
+    result = nxml.search_dataframe(df, ["title", "abstract_text"], ["tp53"])
+
+    assert result["row_index"].to_list() == [0, 0, 0]
+    assert result["column"].to_list() == ["title", "abstract_text", "abstract_text"]
+    assert result["matched_text"].to_list() == ["TP53", "TP53", "tp53"]
+
+
+def test_parse_xml_string_reads_a_literal_string():
+    """XML already in memory parses into one row with its title."""
+    df = nxml.parse_xml_string(article_xml(7, title="From a string"), "inline.xml")
+
+    assert df.height == 1
+    assert df["title"].to_list() == ["From a string"]
+    assert df["pmid"].to_list() == ["7"]
+
+
+def test_paragraphs_come_out_one_row_each_with_their_index(tmp_path):
+    """A three-paragraph body gives three rows indexed 0, 1 and 2."""
+    path = tmp_path / "paragraphs.xml"
+    path.write_text(
+        article_xml(1).replace(
+            "<p>Body text.</p>", "<p>First.</p><p>Second.</p><p>Third.</p>"
+        )
+    )
+
+    df = nxml.xml_to_paragraphs([str(path)])
+
+    assert df["paragraph_index"].to_list() == [0, 1, 2]
+    assert df["paragraph_text"].to_list() == ["First.", "Second.", "Third."]
+
+
+def test_match_counts_per_file_and_pattern(tmp_path):
+    """Each file gets a row and each pattern a column of its match counts."""
+    without = tmp_path / "without.xml"
+    without.write_text(article_xml(1))
+    with_genes = tmp_path / "with_genes.xml"
+    with_genes.write_text(article_xml(2, body="BRCA1 and BRCA2, but not TP53."))
+
+    df = nxml.count_xml_matches([str(without), str(with_genes)], [r"BRCA\d", "TP53"])
+
+    assert df.columns == ["file_path", r"BRCA\d", "TP53"]
+    assert df[r"BRCA\d"].to_list() == [0, 2]
+    assert df["TP53"].to_list() == [0, 1]
+
+
+def test_parse_article_exposes_fields_as_attributes(tmp_path):
+    """Fields are attributes of the returned object; absent ones are None."""
+    path = tmp_path / "article.xml"
+    path.write_text(article_xml(42, title="Attribute access"))
+
+    article = nxml.parse_article(str(path))
+
+    assert article.pmid == "42"
+    assert article.title == "Attribute access"
+    assert article.abstract_text == "An abstract."
+    assert article.doi is None
+    assert article.journal is None
+
+
+def test_scan_directory_finds_nested_files_in_sorted_order(tmp_path):
+    """XML files at any depth are listed, sorted; other files are left out."""
+    nested = tmp_path / "b" / "deeper"
+    nested.mkdir(parents=True)
+    for path in [tmp_path / "c.xml", tmp_path / "b" / "a.xml", nested / "d.xml"]:
+        path.write_text(article_xml(1))
+    (tmp_path / "notes.txt").write_text("not an article")
+
+    found = nxml.scan_directory(str(tmp_path))
+    top_level = nxml.scan_directory(str(tmp_path), recursive=False)
+
+    assert found == [
+        str(tmp_path / "b" / "a.xml"),
+        str(nested / "d.xml"),
+        str(tmp_path / "c.xml"),
+    ]
+    assert top_level == [str(tmp_path / "c.xml")]
+
+
+def test_chunked_conversion_skips_files_over_max_bytes(tmp_path):
+    """Files larger than `max_bytes` are left out of the chunked output."""
+    small, large = tmp_path / "small.xml", tmp_path / "large.xml"
+    small.write_text(article_xml(1))
+    large.write_text(article_xml(2, body="Body text. " * 1000))
+    output = tmp_path / "out.ndjson"
+
+    counts = nxml.batch_xml_to_ndjson_chunked(
+        [str(small), str(large)],
+        str(output),
+        chunk_files=2,
+        max_bytes=small.stat().st_size,
+    )
+
+    assert counts == [1]
+    assert [record["pmid"] for record in read_ndjson(output)] == ["1"]
+
+
+def test_the_ndjson_writer_retries_a_failing_read(tmp_path):
+    """A path that fails with an I/O error is tried `retries` more times,
+    `retry_delay_ms` apart, before the writer gives up on it."""
+    writer = nxml.XmlNdjsonWriter.open(
+        str(tmp_path / "out.ndjson"), retries=2, retry_delay_ms=50
+    )
+
+    started = time.monotonic()
+    assert not writer.write_file(str(tmp_path / "missing.xml"))
+    assert time.monotonic() - started >= 0.1
+    assert writer.close() == 0
@@ -1,59 +1,1428 @@
 """Stub file for xml_processor module providing type annotations."""
 
-from typing import List, Optional
+from typing import Callable, Dict, Iterator, List, Optional, Tuple
 from polars import DataFrame
 
+class XmlIoError(OSError):
+    """An XML file couldn't be opened or read."""
+
+class XmlParseError(ValueError):
+    """A document is not well-formed XML."""
+
+class EmptyDocumentError(XmlParseError):
+    """A document has no root element."""
+
+class UnsupportedFormatError(ValueError):
+    """A file is not UTF-8 XML text (e.g. a zip or bzip2 archive)."""
+
+class SerializationError(ValueError):
+    """An extracted record couldn't be serialized to JSON."""
+
+class InputTooLargeError(XmlIoError):
+    """A file (or article) is larger than the max_bytes limit, so it wasn't read."""
+
 class nxml:
-    """NXML processing submodule for PMC XML files."""
+    """
+    NXML processing submodule for PMC XML files.
+    
+    Every function taking XML paths also accepts gzip-compressed files
+    (`.xml.gz`, or any file starting with the gzip magic number).
+    
+    Besides PMC/JATS articles, native PubMed XML (a <PubmedArticleSet>,
+    <PubmedArticle> or <MedlineCitation> root) is recognised and mapped to
+    the same fields: PMID, ArticleTitle, AbstractText, authors, journal
+    title, PubDate, keywords, MeSH headings and article ids.
+    
+    Files that can't be read or parsed are skipped with a warning on the
+    "xml_processor" logger (or a child of it) of Python's logging module.
+    """
+    
+    class Award:
+        """A grant from an article's <funding-group> (read-only)."""
+        funder: str
+        funder_id: Optional[str]
+        award_id: Optional[str]
+    
+    class ArticleMetadata:
+        """
+        One article's extracted fields, as returned by parse_article.
+        Attributes are read-only; fields absent from the article are None
+        (or an empty list).
+        """
+        schema_version: int
+        pmid: Optional[str]
+        pmc_id: Optional[str]
+        article_version: Optional[str]
+        title: Optional[str]
+        subtitle: Optional[str]
+        trans_titles: List[Tuple[str, str]]
+        article_type: Optional[str]
+        language: Optional[str]
+        publication_types: List[str]
+        abstract_text: Optional[str]
+        abstract_sections: List[Tuple[str, str]]
+        abstracts_by_language: List[Tuple[str, str]]
+        abstract_xml: Optional[str]
+        graphical_abstract_href: Optional[str]
+        graphical_abstract_caption: Optional[str]
+        authors: List[str]
+        author_surnames: List[Optional[str]]
+        author_given_names: List[Optional[str]]
+        author_is_collab: List[bool]
+        affiliations: List[List[str]]
+        orcids: List[Optional[str]]
+        author_roles: List[List[str]]
+        corresponding_author: Optional[str]
+        corresponding_author_index: Optional[int]
+        corresponding_email: Optional[str]
+        editors: List[str]
+        keywords: List[str]
+        mesh_terms: List[str]
+        journal: Optional[str]
+        issn_print: Optional[str]
+        issn_electronic: Optional[str]
+        journal_abbrev: Optional[str]
+        publisher_name: Optional[str]
+        volume: Optional[str]
+        issue: Optional[str]
+        first_page: Optional[str]
+        last_page: Optional[str]
+        elocation_id: Optional[str]
+        publication_date: Optional[str]
+        date_received: Optional[str]
+        date_accepted: Optional[str]
+        date_published: Optional[str]
+        doi: Optional[str]
+        doi_source: Optional[str]
+        full_text: Optional[str]
+        body_paragraphs: List[str]
+        full_text_sections: List[Tuple[str, str, str]]
+        funding: List["nxml.Award"]
+        is_open_access: Optional[bool]
+        license_type: Optional[str]
+        license_url: Optional[str]
+        license_text: Optional[str]
+        copyright_statement: Optional[str]
+        acknowledgements: Optional[str]
+        contributions_text: Optional[str]
+        conflict_of_interest: Optional[str]
+        has_competing_interests: Optional[bool]
+        data_availability: Optional[str]
+        trial_numbers: List[str]
+        is_empty: bool
+        source_mtime: Optional[int]
+        file_path: str
+    
+    @staticmethod
+    def scan_directory(
+        root_dir: str,
+        pattern: str = "*.xml",
+        recursive: bool = True,
+    ) -> List[str]:
+        """
+        List the files under a directory whose names match a glob pattern,
+        ready to pass to the batch functions.
+        
+        Args:
+            root_dir: Directory to walk
+            pattern: Glob matched against file names, e.g. "*.xml", "*.nxml"
+                or "*.xml.gz"
+            recursive: Descend into subdirectories (symlinks are followed;
+                symlink loops are reported and skipped)
+            
+        Returns:
+            Matching file paths, sorted
+            
+        Raises:
+            IOError: If root_dir is not a directory
+            ValueError: If the pattern is not a valid glob
+        """
+        ...
     
     @staticmethod
-    def xml_to_ndjson(xml_path: str, output_path: str) -> None:
+    def current_schema_version() -> int:
+        """
+        Version of the NDJSON record layout written by this module. Every
+        record carries it as its first key, schema_version (0 in records read
+        back from NDJSON written before versioning); it is bumped whenever a
+        field is added, removed or changes meaning.
+        """
+        ...
+    
+    @staticmethod
+    def normalize_identifiers(
+        pmid: Optional[str] = None,
+        doi: Optional[str] = None,
+        pmc_id: Optional[str] = None,
+    ) -> Tuple[Optional[str], Optional[str], Optional[str]]:
+        """
+        Normalize identifiers the way extraction does, so records from other
+        sources join against extracted ones.
+        
+        Args:
+            pmid: PMID, e.g. " PMID: 12345" -> "12345"
+            doi: DOI or doi.org link, e.g. "https://doi.org/10.1000/ABC" -> "10.1000/abc"
+            pmc_id: PMC ID with or without prefix, e.g. "12345" -> "PMC12345"
+            
+        Returns:
+            (pmid, doi, pmc_id); each None when missing or malformed (a PMID
+            or PMC ID that isn't a number, a DOI not starting with "10.")
+        """
+        ...
+    
+    @staticmethod
+    def xml_to_ndjson(
+        xml_path: str,
+        output_path: str,
+        lenient_xml: bool = False,
+        strip_xref: bool = False,
+        normalize_whitespace: bool = False,
+        keep_raw_abstract: bool = False,
+        append: bool = False,
+        sections_filter: Optional[List[str]] = None,
+        lowercase: bool = False,
+        unicode_superscripts: bool = False,
+    ) -> None:
         """
         Convert a single XML file to NDJSON format.
         
         Args:
             xml_path: Path to the input XML file
             output_path: Path where the output NDJSON file will be written
+            lenient_xml: Escape bare ampersands and drop illegal control
+                characters before parsing (off by default since it alters input)
+            strip_xref: Leave the text of <xref> markers (citation numbers,
+                "Fig. 2", ...) out of the body text (full_text, paragraphs,
+                sections)
+            normalize_whitespace: Collapse runs of whitespace (spaces, tabs,
+                newlines) in title, abstract and full_text to single spaces
+                and trim them (off by default)
+            keep_raw_abstract: Also keep the abstract's inner XML, markup
+                included, in abstract_xml
+            append: Add the record as a new line of an existing output file
+                instead of overwriting it
+            sections_filter: Build full_text only from the body <sec>s (with
+                their subsections) whose sec-type or title matches one of these,
+                e.g. ["methods", "materials-and-methods"]. Compared
+                case-insensitively, with spaces and punctuation as "-", so
+                "Materials and Methods" matches "materials-and-methods"
+            lowercase: Lowercase title, abstract, full_text and journal for
+                search indexing (after normalize_whitespace). Destructive: the
+                original casing is not kept. Identifiers such as pmid and doi
+                are left untouched. Off by default
+            unicode_superscripts: Write digits (and + - = parentheses) in
+                <sup>/<sub> of the title and abstract as Unicode super- and
+                subscripts, e.g. "CO<sub>2</sub>" -> "CO₂" instead of "CO2"
             
         Raises:
-            IOError: If the XML file cannot be read or output file cannot be written
-            ValueError: If the XML content cannot be parsed or serialized
+            XmlIoError: If the XML file cannot be read
+            UnsupportedFormatError: If the file is not UTF-8 XML text
+            XmlParseError: If the XML is not well-formed (EmptyDocumentError if
+                it has no root element)
+            SerializationError: If the record cannot be serialized
+            IOError: If the output file cannot be written
         """
         ...
     
     @staticmethod
-    def batch_xml_to_ndjson(xml_paths: List[str], output_path: str) -> int:
+    def batch_xml_to_ndjson(
+        xml_paths: List[str],
+        output_path: str,
+        chunk_files: Optional[int] = None,
+        rotate_output: bool = False,
+        lenient_xml: bool = False,
+        num_threads: Optional[int] = None,
+        progress: Optional[Callable[[int], None]] = None,
+        report_every: int = 1000,
+        strip_xref: bool = False,
+        normalize_whitespace: bool = False,
+        dedupe_by: Optional[str] = None,
+        min_year: Optional[int] = None,
+        max_year: Optional[int] = None,
+        keep_undated: bool = True,
+        keep_raw_abstract: bool = False,
+        max_bytes: Optional[int] = None,
+        append: bool = False,
+        sections_filter: Optional[List[str]] = None,
+        retries: int = 0,
+        retry_delay_ms: int = 100,
+        lowercase: bool = False,
+        unicode_superscripts: bool = False,
+        max_open_files: Optional[int] = None,
+    ) -> int:
         """
         Convert multiple XML files to a single NDJSON file.
         
+        Files are read and parsed in parallel; output lines are always written
+        in the same order as `xml_paths`.
+        
         Args:
             xml_paths: List of paths to XML files to process
             output_path: Path where the output NDJSON file will be written
+            chunk_files: Flush the output every N input files (default: only at the end)
+            rotate_output: Write each chunk to its own numbered file
+                (`out.ndjson` -> `out.00000.ndjson`, `out.00001.ndjson`, ...)
+            lenient_xml: Repair common XML issues before parsing (see xml_to_ndjson)
+            strip_xref: Leave <xref> marker text out of the body text (see xml_to_ndjson)
+            normalize_whitespace: Collapse whitespace runs in title, abstract and
+                full_text (see xml_to_ndjson)
+            num_threads: Number of parsing threads (default: all cores)
+            progress: Called with the number of files processed so far every
+                `report_every` files; an exception raised by it aborts the run
+            report_every: How often (in files) to call `progress`
+            dedupe_by: "doi", "pmid" or "pmc_id"; a record whose identifier was
+                already written is skipped (the first in input order is kept).
+                Records without that identifier are always written
+            min_year: Skip records published before this year (going by the
+                year of publication_date)
+            max_year: Skip records published after this year
+            keep_undated: With min_year/max_year, whether records without a
+                publication year are still written
+            keep_raw_abstract: Keep the abstract's inner XML in abstract_xml
+                (see xml_to_ndjson)
+            max_bytes: Skip files larger than this many bytes on disk without
+                reading them, to bound memory use on pathological inputs
+            append: Add the records after the lines already in the output
+                file(s) instead of overwriting them; counts are of new records
+            sections_filter: Only build full_text from the matching body
+                sections (see xml_to_ndjson)
+            retries: Read a file up to this many more times when it fails with
+                an I/O error (e.g. on a network filesystem) before reporting it;
+                parse errors are not retried
+            retry_delay_ms: Pause between those attempts, in milliseconds
+            lowercase: Lowercase the text fields (see xml_to_ndjson)
+            unicode_superscripts: Unicode super/subscripts in the title and
+                abstract (see xml_to_ndjson)
+            max_open_files: Most files read at once across all threads
+                (default: 256); parsing still runs on every thread. Lower it
+                under a low open-file limit or on storage that thrashes with
+                many concurrent reads, at the cost of threads waiting on I/O
             
         Returns:
-            Number of files successfully processed
+            Number of records written; see batch_xml_to_ndjson_with_stats
+            for the records and files left out
             
         Raises:
             IOError: If the output file cannot be created
+            ValueError: If dedupe_by is not a known identifier
         """
         ...
     
     @staticmethod
-    def xml_to_polars(xml_paths: List[str]) -> DataFrame:
+    def batch_xml_to_ndjson_with_stats(
+        xml_paths: List[str],
+        output_path: str,
+        chunk_files: Optional[int] = None,
+        rotate_output: bool = False,
+        lenient_xml: bool = False,
+        num_threads: Optional[int] = None,
+        progress: Optional[Callable[[int], None]] = None,
+        report_every: int = 1000,
+        strip_xref: bool = False,
+        normalize_whitespace: bool = False,
+        dedupe_by: Optional[str] = None,
+        min_year: Optional[int] = None,
+        max_year: Optional[int] = None,
+        keep_undated: bool = True,
+        keep_raw_abstract: bool = False,
+        max_bytes: Optional[int] = None,
+        append: bool = False,
+        sections_filter: Optional[List[str]] = None,
+        retries: int = 0,
+        retry_delay_ms: int = 100,
+        lowercase: bool = False,
+        unicode_superscripts: bool = False,
+        max_open_files: Optional[int] = None,
+    ) -> Tuple[int, int, List[str]]:
+        """
+        Convert multiple XML files to a single NDJSON file like
+        batch_xml_to_ndjson, which takes the same arguments, and also return
+        what was left out.
+        
+        Returns:
+            (written, skipped, oversized_paths) tuple: records written, records
+            skipped as duplicates (dedupe_by) or outside the year range
+            (min_year/max_year), and the files left unread for exceeding
+            max_bytes
+            
+        Raises:
+            IOError: If the output file cannot be created
+            ValueError: If dedupe_by is not a known identifier
+        """
+        ...
+    
+    @staticmethod
+    def batch_xml_to_ndjson_chunked(
+        xml_paths: List[str],
+        output_path: str,
+        chunk_files: int,
+        rotate_output: bool = False,
+        lenient_xml: bool = False,
+        num_threads: Optional[int] = None,
+        strip_xref: bool = False,
+        normalize_whitespace: bool = False,
+        max_bytes: Optional[int] = None,
+        retries: int = 0,
+        retry_delay_ms: int = 100,
+    ) -> List[int]:
+        """
+        Convert multiple XML files to NDJSON, flushing every `chunk_files` files.
+        
+        Args:
+            xml_paths: List of paths to XML files to process
+            output_path: Path where the output NDJSON file will be written
+            chunk_files: Number of input files per chunk
+            rotate_output: Write each chunk to its own numbered file
+                (`out.ndjson` -> `out.00000.ndjson`, `out.00001.ndjson`, ...)
+            lenient_xml: Repair common XML issues before parsing (see xml_to_ndjson)
+            strip_xref: Leave <xref> marker text out of the body text (see xml_to_ndjson)
+            normalize_whitespace: Collapse whitespace runs in title, abstract and
+                full_text (see xml_to_ndjson)
+            num_threads: Number of parsing threads (default: all cores)
+            max_bytes: Skip files larger than this many bytes on disk (see
+                batch_xml_to_ndjson)
+            retries: Retry reads failing with an I/O error (see
+                batch_xml_to_ndjson)
+            retry_delay_ms: Pause between retries, in milliseconds
+            
+        Returns:
+            Number of records written for each chunk, in order
+            
+        Raises:
+            IOError: If an output file cannot be created or written
+        """
+        ...
+    
+    @staticmethod
+    def batch_xml_to_ndjson_with_report(
+        xml_paths: List[str],
+        output_path: str,
+        lenient_xml: bool = False,
+        strip_xref: bool = False,
+        normalize_whitespace: bool = False,
+        max_bytes: Optional[int] = None,
+        retries: int = 0,
+        retry_delay_ms: int = 100,
+    ) -> DataFrame:
+        """
+        Convert multiple XML files to a single NDJSON file and report what
+        happened to each input file.
+        
+        Args:
+            xml_paths: List of paths to XML files to process
+            output_path: Path where the output NDJSON file will be written
+            lenient_xml: Repair common XML issues before parsing (see xml_to_ndjson)
+            strip_xref: Leave <xref> marker text out of the body text (see xml_to_ndjson)
+            normalize_whitespace: Collapse whitespace runs in title, abstract and
+                full_text (see xml_to_ndjson)
+            max_bytes: Skip files larger than this many bytes on disk (see
+                batch_xml_to_ndjson)
+            retries: Retry reads failing with an I/O error (see
+                batch_xml_to_ndjson)
+            retry_delay_ms: Pause between retries, in milliseconds
+            
+        Returns:
+            Polars DataFrame with one row per input path:
+            - file_path: str - Input path
+            - status: str - "ok", "empty", "read_error", "parse_error" or
+              "too_large"; "empty" records (no title, abstract or body text)
+              are still written
+            - error_message: Optional[str] - Error details for failed files
+            
+        Raises:
+            IOError: If the output file cannot be created or written
+        """
+        ...
+    
+    @staticmethod
+    def batch_xml_to_ndjson_with_summary(
+        xml_paths: List[str],
+        output_path: str,
+        lenient_xml: bool = False,
+        strip_xref: bool = False,
+        normalize_whitespace: bool = False,
+        max_bytes: Optional[int] = None,
+        retries: int = 0,
+        retry_delay_ms: int = 100,
+    ) -> Dict[str, int]:
+        """
+        Convert multiple XML files to a single NDJSON file and return the
+        tallies of the run, for a one-object run report. Arguments are those
+        of batch_xml_to_ndjson_with_report.
+        
+        Returns:
+            Dict with the keys:
+            - total_files: Input paths processed
+            - succeeded: Records written
+            - read_failures: Files that couldn't be read, aren't XML text or
+              exceed max_bytes
+            - parse_failures: Files that aren't well-formed XML
+            - empty_documents: Files without a root element, or without title,
+              abstract and body text (these are still written)
+            - bytes_read: On-disk size of the files that were read
+              (compressed size for .gz)
+            
+        Raises:
+            XmlIoError: If the output file cannot be created or written
+        """
+        ...
+    
+    @staticmethod
+    def batch_xml_to_ndjson_from_archive(
+        archive_path: str,
+        output_path: str,
+        lenient_xml: bool = False,
+        strip_xref: bool = False,
+        normalize_whitespace: bool = False,
+        max_bytes: Optional[int] = None,
+        retries: int = 0,
+        retry_delay_ms: int = 100,
+    ) -> int:
+        """
+        Convert the .xml/.nxml members of a .tar.gz archive (e.g. a PMC OA
+        bundle) to a single NDJSON file, streaming the archive rather than
+        extracting it to disk.
+        
+        Each record's file_path is the member's path inside the archive.
+        Members that can't be parsed, or exceed max_bytes, are skipped with a
+        warning.
+        
+        Args:
+            archive_path: Path to the .tar.gz archive
+            output_path: Path where the output NDJSON file will be written
+            lenient_xml: Repair common XML issues before parsing (see xml_to_ndjson)
+            strip_xref: Leave <xref> marker text out of the body text (see xml_to_ndjson)
+            normalize_whitespace: Collapse whitespace runs in title, abstract and
+                full_text (see xml_to_ndjson)
+            max_bytes: Skip members larger than this many bytes uncompressed;
+                members over 256 MiB are always skipped
+            retries: Try opening the archive up to this many more times when
+                it fails with an I/O error; an error partway through the
+                stream is not retried
+            retry_delay_ms: Pause between retries, in milliseconds
+            
+        Returns:
+            Number of articles written
+            
+        Raises:
+            XmlIoError: If the archive cannot be read or is truncated
+            UnsupportedFormatError: If the archive is not a gzip-compressed tar
+            IOError: If the output file cannot be created or written
+        """
+        ...
+    
+    class XmlNdjsonWriter:
+        """
+        NDJSON writer fed one XML path at a time, for pipelines that discover
+        files lazily. Each write_file call appends and flushes one line.
+        """
+        @staticmethod
+        def open(
+            output_path: str,
+            lenient_xml: bool = False,
+            strip_xref: bool = False,
+            normalize_whitespace: bool = False,
+            retries: int = 0,
+            retry_delay_ms: int = 100,
+        ) -> "nxml.XmlNdjsonWriter":
+            """
+            Create (or truncate) output_path and return a writer for it; the
+            options are those of xml_to_ndjson, and retries/retry_delay_ms
+            those of batch_xml_to_ndjson.
+            
+            Raises:
+                IOError: If the output file cannot be created
+            """
+            ...
+        def write_file(self, xml_path: str) -> bool:
+            """
+            Extract xml_path and append its record. Returns False (with a
+            warning logged) if the file can't be read or parsed.
+            
+            Raises:
+                ValueError: If the writer is closed
+                IOError: If the output file cannot be written
+            """
+            ...
+        def close(self) -> int:
+            """Flush and close the file; returns the number of records written."""
+            ...
+    
+    @staticmethod
+    def xml_to_polars(
+        xml_paths: List[str],
+        lenient_xml: bool = False,
+        strip_xref: bool = False,
+        normalize_whitespace: bool = False,
+        keep_raw_abstract: bool = False,
+        sections_filter: Optional[List[str]] = None,
+        lowercase: bool = False,
+        unicode_superscripts: bool = False,
+        columns: Optional[List[str]] = None,
+        max_open_files: Optional[int] = None,
+    ) -> DataFrame:
         """
         Read XML files directly into a Polars DataFrame.
         
         Args:
             xml_paths: List of paths to XML files to process
+            lenient_xml: Repair common XML issues before parsing (see xml_to_ndjson)
+            strip_xref: Leave <xref> marker text out of the body text (see xml_to_ndjson)
+            normalize_whitespace: Collapse whitespace runs in title, abstract and
+                full_text (see xml_to_ndjson)
+            keep_raw_abstract: Keep the abstract's inner XML in abstract_xml
+                (see xml_to_ndjson)
+            sections_filter: Only build full_text from the matching body
+                sections (see xml_to_ndjson)
+            lowercase: Lowercase the text fields (see xml_to_ndjson)
+            unicode_superscripts: Unicode super/subscripts in the title and
+                abstract (see xml_to_ndjson)
+            columns: Build only these of the columns below, in this order. The
+                body text isn't collected at all unless "full_text",
+                "full_text_word_count" or "trial_numbers" is among them, which
+                saves memory and time on large corpora
+            max_open_files: Most files read at once (see batch_xml_to_ndjson)
             
         Returns:
             Polars DataFrame with columns:
             - pmid: Optional[str] - PubMed ID
             - pmc_id: Optional[str] - PMC ID  
+            - article_version: Optional[str] - <article-version>, else the
+              suffix of a versioned PMC id (PMC12345.2 gives "2")
             - title: Optional[str] - Article title
-            - abstract: Optional[str] - Abstract text
+            - subtitle: Optional[str] - <subtitle> of the article's <title-group>
+            - trans_titles: Optional[List[Struct{lang, title}]] - Translated
+              titles (<trans-title>) with their xml:lang ("" if not given)
+            - article_type: Optional[str] - article-type of the root <article>
+              ("research-article", "editorial", "correction", ...), or the first
+              PubMed <PublicationType>
+            - language: Optional[str] - xml:lang of the root <article>, or
+              else (in builds with the langdetect feature) a guess from the
+              title and abstract among en, es, fr, de, pt and it
+            - publication_types: Optional[List[str]] - All PubMed
+              <PublicationType> values (empty for JATS)
+            - abstract: Optional[str] - Abstract text, from front matter or
+              else the first <abstract> elsewhere in the document
+            - abstract_xml: Optional[str] - Inner XML of that <abstract>, with
+              keep_raw_abstract; null otherwise
+            - abstract_word_count: Optional[int] - Whitespace-separated words in
+              the abstract; null when the article has no abstract
+            - abstract_section_labels: Optional[List[str]] - Section titles of a
+              structured abstract ("Background", "Methods", ...); "" for an
+              unstructured abstract
+            - abstract_section_texts: Optional[List[str]] - Text of each abstract
+              section, aligned with abstract_section_labels
+            - abstracts_by_language: Optional[List[Struct{lang: str, text:
+              str}]] - Every abstract with its xml:lang ("" when it has none),
+              in document order; abstract holds the first one, or the first
+              English one when the first is in another language
+            - graphical_abstract_href: Optional[str] - xlink:href of the
+              <graphic> in an <abstract abstract-type="graphical">, which is
+              never used as the abstract text
+            - graphical_abstract_caption: Optional[str] - Caption text of that
+              graphical abstract
             - journal: Optional[str] - Journal name
+            - issn_print: Optional[str] - Print ISSN (<issn pub-type="ppub">)
+            - issn_electronic: Optional[str] - Electronic ISSN
+              (<issn pub-type="epub">)
+            - journal_abbrev: Optional[str] - NLM title abbreviation
+              (journal-id-type="nlm-ta"), else <abbrev-journal-title>
+            - publisher_name: Optional[str] - <publisher-name>
+            - volume: Optional[str] - Journal volume
+            - issue: Optional[str] - Journal issue
+            - first_page: Optional[str] - First page (<fpage>)
+            - last_page: Optional[str] - Last page (<lpage>)
+            - elocation_id: Optional[str] - Article number used instead of
+              pages (<elocation-id>, e.g. "e1234")
+            - date_received: Optional[str] - <history> received date
+              (YYYY[-MM[-DD]])
+            - date_accepted: Optional[str] - <history> accepted date
+            - date_published: Optional[str] - <history> publication date, or
+              else the electronic (epub) publication date
+            - authors: Optional[List[Struct{surname, given_names}]] - Authors in
+              order; either name part is null when absent from the contrib
+            - first_author: Optional[str] - First author as "Surname, Given",
+              null when no authors were found
+            - last_author: Optional[str] - Last author; the same as
+              first_author for single-author articles
+            - author_count: Optional[int] - Number of authors
+            - affiliations: Optional[List[List[str]]] - Affiliations of each
+              author (resolved from <xref ref-type="aff">), in author order
+            - orcids: Optional[List[Optional[str]]] - Bare ORCID of each author
+              (e.g. "0000-0002-1825-0097"), null for authors without one
+            - author_roles: Optional[List[List[str]]] - <role>s of each author
+              (CRediT terms such as "Conceptualization" where used), in author
+              order
+            - authors_detailed: Optional[List[Struct{name, orcid, affiliations,
+              email, roles, is_collab}]] - One struct per author combining
+              authors, orcids, affiliations and author_roles; orcid is null for
+              authors without one, email is set only on the corresponding
+              author, and is_collab marks group authors (<collab>), whose name
+              is the group name
+            - corresponding_author: Optional[str] - Author marked
+              corresp="yes" or linked to a <corresp> note, as "Surname, Given"
+            - corresponding_email: Optional[str] - That author's email (from
+              the contrib or its <corresp> note), else the first email in
+              <author-notes>
+            - editors: Optional[List[str]] - <contrib contrib-type="editor">
+              names as "Surname, Given", kept out of authors
+            - keywords: Optional[List[str]] - Deduplicated <kwd> terms from all
+              front-matter <kwd-group>s, in document order
+            - mesh_terms: Optional[List[str]] - MeSH headings from PubMed
+              <MeshHeadingList>, as "Descriptor" or "Descriptor/Qualifier",
+              in document order
             - full_text: Optional[str] - Full article text
+            - full_text_word_count: Optional[int] - Words in full_text; null
+              when there is no body
+            - is_open_access: Optional[bool] - Open-access flag from an
+              <open-access> element, OA custom-meta, or a Creative Commons
+              license; None when the XML doesn't say
+            - license_type: Optional[str] - license-type of the <license> in
+              <permissions>, or the Creative Commons type implied by its URL
+              ("cc-by", "cc-by-nc-nd", "cc0", ...)
+            - license_url: Optional[str] - License URL (xlink:href,
+              <ali:license_ref>, or the first link in <license-p>)
+            - license_text: Optional[str] - Text of the <license-p> paragraphs
+            - copyright_statement: Optional[str] - <copyright-statement>, or
+              "© year holder" from <copyright-year>/<copyright-holder>
+            - acknowledgements: Optional[str] - Text of the <ack> blocks,
+              without their titles
+            - contributions_text: Optional[str] - Author-contributions
+              statement (<fn fn-type="con"> or an author-contributions
+              section), for articles without per-author roles
+            - conflict_of_interest: Optional[str] - Competing-interest statement
+            - has_competing_interests: Optional[bool] - False when the statement
+              matches a "no competing interests" phrase, True for any other
+              statement, None when there is no statement. Phrase heuristic only:
+              mixed statements ("A consults for B; the others declare none")
+              read as False
+            - data_availability: Optional[str] - Text of the data-availability
+              <sec>/<notes> (body or back matter), without its title
+            - trial_numbers: Optional[List[str]] - Clinical trial registration
+              numbers from <related-object> registry links, plus NCT/ISRCTN
+              numbers mentioned in the abstract or body; deduplicated
+            - is_empty: Optional[bool] - No title, abstract or body text
+            - source_mtime: Optional[int] - Modification time of the source
+              file in Unix seconds, for provenance
+            
+        Raises:
+            ValueError: If a name in columns is unknown or DataFrame creation fails
+        """
+        ...
+    
+    class ArticleChunks(Iterator[DataFrame]):
+        """Iterator of DataFrames returned by xml_to_polars_chunks."""
+        def __iter__(self) -> "nxml.ArticleChunks": ...
+        def __next__(self) -> DataFrame: ...
+    
+    @staticmethod
+    def xml_to_polars_chunks(
+        xml_paths: List[str],
+        chunk_size: int,
+        lenient_xml: bool = False,
+        strip_xref: bool = False,
+        normalize_whitespace: bool = False,
+    ) -> "nxml.ArticleChunks":
+        """
+        Read XML files into DataFrames of at most chunk_size rows, one chunk
+        at a time, so memory use doesn't grow with the number of files.
+        
+        Files are only read as the iterator is advanced. Each chunk has the
+        columns and dtypes of xml_to_polars, with one row per path (null rows
+        for files that can't be read), in input order.
+        
+        Args:
+            xml_paths: List of paths to XML files to process
+            chunk_size: Maximum rows per DataFrame
+            lenient_xml: See xml_to_polars
+            strip_xref: See xml_to_polars
+            normalize_whitespace: See xml_to_polars
+            
+        Returns:
+            Iterator of Polars DataFrames
+            
+        Raises:
+            ValueError: If chunk_size is 0
+        """
+        ...
+    
+    @staticmethod
+    def validate_xml(
+        xml_paths: List[str],
+        required_fields: List[str],
+        lenient_xml: bool = False,
+    ) -> DataFrame:
+        """
+        Check which required fields each XML file provides, without writing
+        any output. A field counts as present when it was extracted with a
+        non-blank value (list fields: at least one entry).
+        
+        Args:
+            xml_paths: List of paths to XML files to process
+            required_fields: Fields to check, from "pmid", "pmc_id", "doi",
+                "title", "abstract", "authors", "affiliations", "keywords",
+                "mesh_terms", "journal", "volume", "issue", "publication_date",
+                "article_type", "language", "full_text", "funding", "license",
+                "data_availability"
+            lenient_xml: Repair common XML issues before parsing (see xml_to_ndjson)
+            
+        Returns:
+            Polars DataFrame with one row per path and columns:
+            - file_path: str - Path of the source file
+            - has_<field>: bool - One per required field, in the given order
+            - is_complete: bool - All required fields present (False for files
+              that can't be read or parsed)
+            
+        Raises:
+            ValueError: If a field name is unknown or DataFrame creation fails
+        """
+        ...
+    
+    @staticmethod
+    def ndjson_to_polars(ndjson_path: str) -> DataFrame:
+        """
+        Read NDJSON written by xml_to_ndjson / batch_xml_to_ndjson back into a
+        DataFrame, without reparsing the XML.
+        
+        Args:
+            ndjson_path: Path to the NDJSON file
+            
+        Returns:
+            Polars DataFrame with the same columns as xml_to_polars, one row per
+            record. Blank lines are ignored; malformed lines are skipped and
+            their count is logged as a warning
+            
+        Raises:
+            IOError: If the file cannot be read
+        """
+        ...
+    
+    @staticmethod
+    def parse_xml_string(
+        xml_content: str,
+        file_path_label: str = "",
+        lenient_xml: bool = False,
+        strip_xref: bool = False,
+        normalize_whitespace: bool = False,
+        keep_raw_abstract: bool = False,
+        sections_filter: Optional[List[str]] = None,
+        lowercase: bool = False,
+        unicode_superscripts: bool = False,
+    ) -> DataFrame:
+        """
+        Parse XML content that is already in memory (e.g. fetched from an API)
+        without writing it to a file first.
+        
+        Args:
+            xml_content: The XML document as a string
+            file_path_label: Stored in the file_path field for provenance
+            lenient_xml: Repair common XML issues before parsing (see xml_to_ndjson)
+            strip_xref: Leave <xref> marker text out of the body text (see xml_to_ndjson)
+            normalize_whitespace: Collapse whitespace runs in title, abstract and
+                full_text (see xml_to_ndjson)
+            keep_raw_abstract: Keep the abstract's inner XML in abstract_xml
+                (see xml_to_ndjson)
+            sections_filter: Only build full_text from the matching body
+                sections (see xml_to_ndjson)
+            lowercase: Lowercase the text fields (see xml_to_ndjson)
+            unicode_superscripts: Unicode super/subscripts in the title and
+                abstract (see xml_to_ndjson)
+            
+        Returns:
+            Single-row Polars DataFrame with the same columns as xml_to_polars
+            
+        Raises:
+            XmlParseError: If the XML is not well-formed (EmptyDocumentError if
+                it has no root element)
+        """
+        ...
+    
+    @staticmethod
+    def parse_article(
+        xml_path: str,
+        lenient_xml: bool = False,
+        strip_xref: bool = False,
+        normalize_whitespace: bool = False,
+        keep_raw_abstract: bool = False,
+        sections_filter: Optional[List[str]] = None,
+        lowercase: bool = False,
+        unicode_superscripts: bool = False,
+    ) -> "nxml.ArticleMetadata":
+        """
+        Read a single XML file into an ArticleMetadata object, for attribute
+        access to one article's fields (article.title, article.authors, ...).
+        
+        Args:
+            xml_path: Path to the XML file
+            lenient_xml: Repair common XML issues before parsing (see xml_to_ndjson)
+            strip_xref: Leave <xref> marker text out of the body text (see xml_to_ndjson)
+            normalize_whitespace: Collapse whitespace runs in title, abstract and
+                full_text (see xml_to_ndjson)
+            keep_raw_abstract: Keep the abstract's inner XML in abstract_xml
+                (see xml_to_ndjson)
+            sections_filter: Only build full_text from the matching body
+                sections (see xml_to_ndjson)
+            lowercase: Lowercase the text fields (see xml_to_ndjson)
+            unicode_superscripts: Unicode super/subscripts in the title and
+                abstract (see xml_to_ndjson)
+            
+        Returns:
+            ArticleMetadata; optional fields missing from the article are None
+            
+        Raises:
+            XmlIoError: If the XML file cannot be read
+            UnsupportedFormatError: If the file is not UTF-8 XML text
+            XmlParseError: If the XML is not well-formed (EmptyDocumentError if
+                it has no root element)
+        """
+        ...
+    
+    @staticmethod
+    def parse_multi_article_xml(
+        xml_path: str,
+        strip_xref: bool = False,
+        normalize_whitespace: bool = False,
+        max_bytes: Optional[int] = None,
+    ) -> DataFrame:
+        """
+        Read a file holding many articles, such as a PubMed baseline
+        <PubmedArticleSet> or a JATS <pmc-articleset>, with one row per
+        <PubmedArticle>/<article>. The file (optionally .gz) is streamed, so
+        only one article's XML is in memory at a time; an uncompressed file is
+        memory-mapped rather than read, and must not be modified while this
+        runs. Articles that fail to parse are logged as warnings and skipped.
+        
+        Args:
+            xml_path: Path to the multi-article XML file
+            strip_xref: Leave <xref> marker text out of the body text (see xml_to_ndjson)
+            normalize_whitespace: Collapse whitespace runs in title, abstract and
+                full_text (see xml_to_ndjson)
+            max_bytes: Skip (with a warning) any article whose XML is longer
+                than this many bytes, instead of holding it in memory
+            
+        Returns:
+            Polars DataFrame with the same columns as xml_to_polars
+            
+        Raises:
+            XmlIoError: If the file cannot be read
+            UnsupportedFormatError: If the file is not UTF-8 XML text
+            XmlParseError: If the file is not well-formed XML
+        """
+        ...
+    
+    @staticmethod
+    def build_article_index(xml_path: str, index_path: str) -> int:
+        """
+        Index a multi-article file (see parse_multi_article_xml) for random
+        access: the byte offset at which each <PubmedArticle>/<article> starts,
+        and its PMID, are written to index_path as tab-separated "offset" and
+        "pmid" columns (pmid empty when the article has none), readable with
+        polars.read_csv(index_path, separator="\t"). Offsets are into the
+        uncompressed XML.
+        
+        Args:
+            xml_path: Path to the multi-article XML file (optionally .gz)
+            index_path: Path where the index file will be written
+            
+        Returns:
+            Number of articles indexed
+            
+        Raises:
+            XmlIoError: If the file cannot be read or the index cannot be written
+            UnsupportedFormatError: If the file is not UTF-8 XML text
+            XmlParseError: If the file is not well-formed XML
+        """
+        ...
+    
+    @staticmethod
+    def read_article_at(
+        xml_path: str,
+        offset: int,
+        strip_xref: bool = False,
+        normalize_whitespace: bool = False,
+    ) -> "nxml.ArticleMetadata":
+        """
+        Read the one article starting at byte offset of a multi-article file,
+        as recorded by build_article_index, without scanning the articles
+        before it (a .gz file still has to be decompressed up to the offset).
+        
+        Args:
+            xml_path: Path to the multi-article XML file
+            offset: Start of the article, from the index
+            strip_xref: Leave <xref> marker text out of the body text (see xml_to_ndjson)
+            normalize_whitespace: Collapse whitespace runs in title, abstract and
+                full_text (see xml_to_ndjson)
+            
+        Returns:
+            ArticleMetadata of that article
+            
+        Raises:
+            XmlIoError: If the file cannot be read
+            UnsupportedFormatError: If no article starts at the offset
+            XmlParseError: If the article is not well-formed XML
+        """
+        ...
+    
+    @staticmethod
+    def batch_xml_split_output(
+        xml_paths: List[str],
+        abstract_output: str,
+        fulltext_output: str,
+        lenient_xml: bool = False,
+        strip_xref: bool = False,
+        normalize_whitespace: bool = False,
+    ) -> Tuple[int, int]:
+        """
+        Convert multiple XML files to two NDJSON files, so abstracts can be
+        read without the (much larger) body text: abstract_output gets every
+        record of batch_xml_to_ndjson minus full_text, body_paragraphs and
+        full_text_sections; fulltext_output gets a {"pmid", "file_path",
+        "full_text"} record for each article with non-empty body text. Files
+        that fail are logged and left out of both.
+        
+        Args:
+            xml_paths: List of paths to XML files to process
+            abstract_output: Path where the metadata/abstract NDJSON will be written
+            fulltext_output: Path where the full-text NDJSON will be written
+            lenient_xml: Repair common XML issues before parsing (see xml_to_ndjson)
+            strip_xref: Leave <xref> marker text out of the body text (see xml_to_ndjson)
+            normalize_whitespace: Collapse whitespace runs in title, abstract and
+                full_text (see xml_to_ndjson)
+            
+        Returns:
+            (lines in abstract_output, lines in fulltext_output)
+            
+        Raises:
+            IOError: If an output file cannot be created or written
+        """
+        ...
+    
+    @staticmethod
+    def batch_xml_to_json_array(
+        xml_paths: List[str],
+        output_path: str,
+        lenient_xml: bool = False,
+        strip_xref: bool = False,
+        normalize_whitespace: bool = False,
+    ) -> int:
+        """
+        Convert multiple XML files to a single JSON array of article objects
+        (the records of batch_xml_to_ndjson), for consumers that need one valid
+        JSON document. Each article is written as soon as it is extracted;
+        files that fail are logged and left out, and no articles give "[]".
+        
+        Args:
+            xml_paths: List of paths to XML files to process
+            output_path: Path where the JSON file will be written
+            lenient_xml: Repair common XML issues before parsing (see xml_to_ndjson)
+            strip_xref: Leave <xref> marker text out of the body text (see xml_to_ndjson)
+            normalize_whitespace: Collapse whitespace runs in title, abstract and
+                full_text (see xml_to_ndjson)
+            
+        Returns:
+            Number of articles written
+            
+        Raises:
+            IOError: If the output file cannot be created or written
+        """
+        ...
+    
+    @staticmethod
+    def batch_xml_to_parquet(
+        xml_paths: List[str],
+        output_path: str,
+        compression: Optional[str] = None,
+        lenient_xml: bool = False,
+        strip_xref: bool = False,
+        normalize_whitespace: bool = False,
+        max_bytes: Optional[int] = None,
+        retries: int = 0,
+        retry_delay_ms: int = 100,
+    ) -> int:
+        """
+        Convert multiple XML files directly to a Parquet file, skipping the
+        NDJSON round-trip. Columns are the same as xml_to_polars.
+        
+        Args:
+            xml_paths: List of paths to XML files to process
+            output_path: Path where the Parquet file will be written
+            compression: "snappy", "zstd", "lz4", "gzip" or "uncompressed"
+                (default: the Polars default, zstd)
+            lenient_xml: Repair common XML issues before parsing (see xml_to_ndjson)
+            strip_xref: Leave <xref> marker text out of the body text (see xml_to_ndjson)
+            normalize_whitespace: Collapse whitespace runs in title, abstract and
+                full_text (see xml_to_ndjson)
+            max_bytes: Don't read files larger than this many bytes on disk;
+                their rows are null, like those of unreadable files
+            retries: Retry reads failing with an I/O error (see
+                batch_xml_to_ndjson)
+            retry_delay_ms: Pause between retries, in milliseconds
+            
+        Returns:
+            Number of rows written
+            
+        Raises:
+            ValueError: If the compression name is unknown or DataFrame creation fails
+            IOError: If the Parquet file cannot be written
+        """
+        ...
+    
+    @staticmethod
+    def batch_xml_to_ipc(
+        xml_paths: List[str],
+        output_path: str,
+        compression: Optional[str] = None,
+        lenient_xml: bool = False,
+        strip_xref: bool = False,
+        normalize_whitespace: bool = False,
+        max_bytes: Optional[int] = None,
+        retries: int = 0,
+        retry_delay_ms: int = 100,
+    ) -> int:
+        """
+        Convert multiple XML files to an Arrow IPC (Feather v2) file, readable
+        with pl.read_ipc / pl.scan_ipc. Columns are the same as xml_to_polars.
+        
+        Args:
+            xml_paths: List of paths to XML files to process
+            output_path: Path where the IPC file will be written
+            compression: "lz4", "zstd" or "uncompressed" (default: uncompressed)
+            lenient_xml: Repair common XML issues before parsing (see xml_to_ndjson)
+            strip_xref: Leave <xref> marker text out of the body text (see xml_to_ndjson)
+            normalize_whitespace: Collapse whitespace runs in title, abstract and
+                full_text (see xml_to_ndjson)
+            max_bytes: Don't read files larger than this many bytes on disk;
+                their rows are null, like those of unreadable files
+            retries: Retry reads failing with an I/O error (see
+                batch_xml_to_ndjson)
+            retry_delay_ms: Pause between retries, in milliseconds
+            
+        Returns:
+            Number of rows written
+            
+        Raises:
+            ValueError: If the compression name is unknown or DataFrame creation fails
+            IOError: If the IPC file cannot be written
+        """
+        ...
+    
+    @staticmethod
+    def xml_to_parquet_dataset(
+        xml_paths: List[str],
+        output_dir: str,
+        rows_per_file: int = 10000,
+        compression: Optional[str] = None,
+        lenient_xml: bool = False,
+        strip_xref: bool = False,
+        normalize_whitespace: bool = False,
+    ) -> List[str]:
+        """
+        Convert XML files to a directory of Parquet part files
+        (part-00000.parquet, part-00001.parquet, ...) with the same columns as
+        xml_to_polars. Only one part is held in memory at a time, so very
+        large corpora can be converted and then read with
+        pl.scan_parquet(output_dir).
+        
+        Args:
+            xml_paths: List of paths to XML files to process
+            output_dir: Directory for the part files (created if missing)
+            rows_per_file: Articles per part file (one row per input path)
+            compression: Parquet codec (see batch_xml_to_parquet)
+            lenient_xml: Repair common XML issues before parsing (see xml_to_ndjson)
+            strip_xref: Leave <xref> marker text out of the body text (see xml_to_ndjson)
+            normalize_whitespace: Collapse whitespace runs in title, abstract and
+                full_text (see xml_to_ndjson)
+            
+        Returns:
+            Paths of the written part files, in order
+            
+        Raises:
+            ValueError: If rows_per_file is 0, the compression name is unknown
+                or DataFrame creation fails
+            IOError: If the directory or a part file cannot be written
+        """
+        ...
+    
+    @staticmethod
+    def xml_to_paragraphs(
+        xml_paths: List[str],
+        lenient_xml: bool = False,
+        strip_xref: bool = False,
+    ) -> DataFrame:
+        """
+        Read XML files into a Polars DataFrame with one row per <p> in <body>.
+        Inline markup (<italic>, <xref>, ...) stays in its enclosing paragraph.
+        
+        Args:
+            xml_paths: List of paths to XML files to process
+            lenient_xml: Repair common XML issues before parsing (see xml_to_ndjson)
+            strip_xref: Leave <xref> marker text out of the body text (see xml_to_ndjson)
+            
+        Returns:
+            Polars DataFrame with columns:
+            - file_path: str - Path of the source file
+            - pmid: Optional[str] - PubMed ID
+            - paragraph_index: int - 0-based position of the paragraph in the body
+            - paragraph_text: str - Paragraph text
+            
+        Raises:
+            ValueError: If DataFrame creation fails
+        """
+        ...
+    
+    @staticmethod
+    def xml_to_sentences(
+        xml_paths: List[str],
+        source: str = "abstract",
+        lenient_xml: bool = False,
+        strip_xref: bool = False,
+    ) -> DataFrame:
+        """
+        Read XML files into a Polars DataFrame with one row per sentence of the
+        abstract or full text. The whitespace-normalized text is split at
+        ".", "!" or "?" followed by a capitalized word, a number, a bracket or
+        a quote; abbreviations such as "Fig.", "et al.", "e.g." and "i.e.",
+        single-letter initials and decimal points don't end a sentence.
+        
+        Args:
+            xml_paths: List of paths to XML files to process
+            source: "abstract" or "full_text"
+            lenient_xml: Repair common XML issues before parsing (see xml_to_ndjson)
+            strip_xref: Leave <xref> marker text out of the body text (see xml_to_ndjson)
+            
+        Returns:
+            Polars DataFrame with columns:
+            - file_path: str - Path of the source file
+            - pmid: Optional[str] - PubMed ID
+            - sentence_index: int - 0-based position of the sentence in the text
+            - sentence_text: str - Sentence text, ending punctuation included
+            
+        Raises:
+            ValueError: If source is not "abstract" or "full_text", or
+                DataFrame creation fails
+        """
+        ...
+    
+    @staticmethod
+    def xml_to_sections(
+        xml_paths: List[str],
+        lenient_xml: bool = False,
+        strip_xref: bool = False,
+    ) -> DataFrame:
+        """
+        Read XML files into a Polars DataFrame with one row per <sec> in <body>,
+        in document order. Text of nested subsections is attributed to the
+        innermost enclosing section, which gets its own row.
+        
+        Args:
+            xml_paths: List of paths to XML files to process
+            lenient_xml: Repair common XML issues before parsing (see xml_to_ndjson)
+            strip_xref: Leave <xref> marker text out of the body text (see xml_to_ndjson)
+            
+        Returns:
+            Polars DataFrame with columns:
+            - file_path: str - Path of the source file
+            - section_type: Optional[str] - sec-type attribute (e.g. "methods")
+            - section_title: Optional[str] - Section <title>
+            - section_text: str - Text directly inside the section
+            
+        Raises:
+            ValueError: If DataFrame creation fails
+        """
+        ...
+    
+    @staticmethod
+    def xml_to_funding(xml_paths: List[str], lenient_xml: bool = False) -> DataFrame:
+        """
+        Read the <funding-group> of XML files into a Polars DataFrame with one
+        row per (funder, award id) pair. An <award-group> without an
+        <award-id> still yields a row for each of its funders.
+        
+        Args:
+            xml_paths: List of paths to XML files to process
+            lenient_xml: Repair common XML issues before parsing (see xml_to_ndjson)
+            
+        Returns:
+            Polars DataFrame with columns:
+            - file_path: str - Path of the source file
+            - pmid: Optional[str] - PubMed ID of the article
+            - funder: str - <funding-source> name
+            - funder_id: Optional[str] - Funder identifier (e.g. FundRef DOI)
+            - award_id: Optional[str] - Grant number
+            
+        Raises:
+            ValueError: If DataFrame creation fails
+        """
+        ...
+    
+    @staticmethod
+    def xml_to_figures(xml_paths: List[str], lenient_xml: bool = False) -> DataFrame:
+        """
+        Read the figures of XML files into a Polars DataFrame with one row per
+        <fig>, wherever it appears (body or floats group).
+        
+        Args:
+            xml_paths: List of paths to XML files to process
+            lenient_xml: Repair common XML issues before parsing (see xml_to_ndjson)
+            
+        Returns:
+            Polars DataFrame with columns:
+            - file_path: str - Path of the source file
+            - fig_id: Optional[str] - id attribute of the <fig>
+            - label: Optional[str] - Figure label (e.g. "Figure 1")
+            - caption_text: str - Flattened <caption> text, "" if none
+            - graphic_href: Optional[str] - xlink:href of the first <graphic>
+            
+        Raises:
+            ValueError: If DataFrame creation fails
+        """
+        ...
+    
+    @staticmethod
+    def xml_to_supplementary(xml_paths: List[str], lenient_xml: bool = False) -> DataFrame:
+        """
+        Read the supplementary files of XML files into a Polars DataFrame with
+        one row per <media> of a <supplementary-material>, wherever it appears
+        (body or back matter). A block without <media> gives one row for its
+        own xlink:href.
+        
+        Args:
+            xml_paths: List of paths to XML files to process
+            lenient_xml: Repair common XML issues before parsing (see xml_to_ndjson)
+            
+        Returns:
+            Polars DataFrame with columns:
+            - file_path: str - Path of the source file
+            - pmid: Optional[str] - PubMed ID of the article
+            - supp_label: Optional[str] - <label> of the block, shared by its media
+            - supp_href: Optional[str] - xlink:href of the file
+            - supp_mimetype: Optional[str] - "mimetype/mime-subtype", e.g. "application/pdf"
+            
+        Raises:
+            ValueError: If DataFrame creation fails
+        """
+        ...
+    
+    @staticmethod
+    def xml_to_entities(xml_paths: List[str], lenient_xml: bool = False) -> DataFrame:
+        """
+        Read the entities publishers tag inline with <named-content
+        content-type="..."> (species, gene, chemical, ...) into a Polars
+        DataFrame with one row per distinct type and text in each file. Markup
+        inside the tag only contributes its text; untyped tags are left out.
+        
+        Args:
+            xml_paths: List of paths to XML files to process
+            lenient_xml: Repair common XML issues before parsing (see xml_to_ndjson)
+            
+        Returns:
+            Polars DataFrame with columns:
+            - file_path: str - Path of the source file
+            - entity_type: str - content-type attribute, e.g. "gene"
+            - entity_text: str - Text of the tagged span
+            - count: int - Occurrences of this type and text in the file
+            
+        Raises:
+            ValueError: If DataFrame creation fails
+        """
+        ...
+    
+    @staticmethod
+    def xml_to_xrefs(xml_paths: List[str], lenient_xml: bool = False) -> DataFrame:
+        """
+        Read the inline cross-references (<xref>) of the body into a Polars
+        DataFrame, one row per target, to see which figures, tables and
+        references each paragraph cites without loading the full text. An
+        xref with several space-separated rids gives one row per rid; xrefs
+        without a rid are left out.
+        
+        Args:
+            xml_paths: List of paths to XML files to process
+            lenient_xml: Repair common XML issues before parsing (see xml_to_ndjson)
+            
+        Returns:
+            Polars DataFrame with columns:
+            - file_path: str - Path of the source file
+            - source_context: str - Up to 60 characters of paragraph text on
+              each side of the xref, including its own text
+            - ref_type: Optional[str] - ref-type attribute, e.g. "bibr", "fig"
+              or "table"
+            - rid: str - id of the referenced element
+            
+        Raises:
+            ValueError: If DataFrame creation fails
+        """
+        ...
+    
+    @staticmethod
+    def xml_to_tables(xml_paths: List[str], lenient_xml: bool = False) -> DataFrame:
+        """
+        Read the tables of XML files into a Polars DataFrame with one row per
+        <table-wrap>. Cells spanning several columns or rows (colspan/rowspan)
+        are repeated in every position they cover, so all rows have the same
+        width.
+        
+        Args:
+            xml_paths: List of paths to XML files to process
+            lenient_xml: Repair common XML issues before parsing (see xml_to_ndjson)
+            
+        Returns:
+            Polars DataFrame with columns:
+            - file_path: str - Path of the source file
+            - table_id: Optional[str] - id attribute of the <table-wrap>
+            - label: Optional[str] - Table label (e.g. "Table 1")
+            - caption_text: str - Flattened <caption> text, "" if none
+            - table_json: str - Cell matrix as a JSON list of rows (header
+              rows first), e.g. '[["Gene","Count"],["abc","3"]]'
+            
+        Raises:
+            ValueError: If DataFrame creation fails
+        """
+        ...
+    
+    @staticmethod
+    def references_to_polars(xml_paths: List[str], lenient_xml: bool = False) -> DataFrame:
+        """
+        Read the reference lists of XML files into a single Polars DataFrame,
+        one row per cited reference (a citation edge list).
+        
+        Args:
+            xml_paths: List of paths to XML files to process
+            lenient_xml: Repair common XML issues before parsing (see xml_to_ndjson)
+            
+        Returns:
+            Polars DataFrame with columns:
+            - citing_file_path: str - Path of the citing article
+            - citing_pmid: Optional[str] - PubMed ID of the citing article
+            - ref_label: Optional[str] - Reference label (e.g. "1")
+            - ref_text: Optional[str] - Flattened citation text
+            - ref_doi: Optional[str] - DOI from <pub-id pub-id-type="doi">
+            - ref_pmid: Optional[str] - PubMed ID from <pub-id pub-id-type="pmid">
+            
+        Raises:
+            ValueError: If DataFrame creation fails
+        """
+        ...
+    
+    @staticmethod
+    def xml_to_references(xml_paths: List[str], lenient_xml: bool = False) -> DataFrame:
+        """
+        Read the <ref-list> of XML files into a Polars DataFrame with one row
+        per <ref>, keeping the identifiers of each cited work.
+        
+        Args:
+            xml_paths: List of paths to XML files to process
+            lenient_xml: Repair common XML issues before parsing (see xml_to_ndjson)
+            
+        Returns:
+            Polars DataFrame with columns:
+            - file_path: str - Path of the citing article
+            - citing_pmid: Optional[str] - PubMed ID of the citing article
+            - ref_id: Optional[str] - id attribute of the <ref> (e.g. "B12")
+            - ref_pmid: Optional[str] - PubMed ID from <pub-id pub-id-type="pmid">
+            - ref_doi: Optional[str] - DOI from <pub-id pub-id-type="doi">
+            - ref_title: Optional[str] - <article-title> of the cited work
+            - ref_year: Optional[str] - Publication year of the cited work
             
         Raises:
             ValueError: If DataFrame creation fails
@@ -64,23 +1433,233 @@ class nxml:
     def search_xml_content(
         xml_paths: List[str],
         patterns: List[str], 
-        case_sensitive: Optional[bool] = None
+        case_sensitive: Optional[bool] = None,
+        fields: Optional[List[str]] = None,
+        context_chars: int = 100,
+        num_threads: Optional[int] = None,
+        whole_word: bool = False,
+        clean_context: bool = False,
+        case_sensitive_flags: Optional[List[bool]] = None,
+        highlight: Optional[Tuple[str, str]] = None,
+        fuzzy: Optional[int] = None,
     ) -> DataFrame:
         """
         Search for patterns in XML content and return matching articles.
+        Files are searched in parallel; rows are ordered by input file, then
+        field, pattern and match position.
+        
+        Named capture groups in the patterns, e.g. (?P<db>GSE)(?P<id>\\d+),
+        each add a string column named after the group holding the captured
+        text; it is null in rows of patterns without that group.
         
         Args:
             xml_paths: List of paths to XML files to search
             patterns: List of regex patterns to search for
             case_sensitive: Whether search should be case sensitive (default: False)
+            fields: Search only these extracted fields instead of the raw XML
+                (tags included). Any of "title", "abstract", "full_text",
+                "keywords", "mesh_terms", "data_availability"; list fields are
+                joined with "; "
+            context_chars: Characters of context kept on each side of a match
+            num_threads: Worker threads to use (default: all cores)
+            whole_word: Only match whole words, by wrapping each pattern in
+                \\b(?:...)\\b ("gene" then no longer matches "genetic"). Meant for
+                literal-ish patterns; one that starts or ends with a non-word
+                character (e.g. "\\(") can no longer match next to a space
+            clean_context: Strip XML tags (including ones cut off at the window
+                edges) from match_context and collapse whitespace, for
+                readable snippets from raw-XML searches
+            case_sensitive_flags: Case sensitivity of each pattern, aligned with
+                patterns (e.g. a case-sensitive gene symbol next to a
+                case-insensitive keyword); overrides case_sensitive
+            highlight: (open, close) markers, e.g. ("<b>", "</b>"), inserted
+                around every match of the row's pattern that lies within
+                match_context (not only the row's own match). With
+                clean_context the markers are kept even if they look like tags
+            fuzzy: Maximum edit distance (Levenshtein, in characters) for
+                approximate matching of spelling or OCR variants ("tumor"
+                matches "tumour" with fuzzy=1). The patterns are then literals
+                rather than regexes, compared against runs of as many whole
+                words as they have; whole_word has no effect
             
         Returns:
             Polars DataFrame with columns:
             - file_path: Optional[str] - Path to file containing match
             - matched_pattern: Optional[str] - Pattern that matched
-            - match_context: Optional[str] - Context around the match (±100 chars)
+            - field: Optional[str] - Field the match is in; null for raw XML
+            - match_start: int - Byte offset of the match in the searched text
+              (the raw XML or the field text)
+            - match_end: int - Byte offset just past the match
+            - matched_text: str - The matched substring
+            - match_context: Optional[str] - Context around the match
+              (±context_chars characters)
+            - distance: int - Edits between the pattern and matched_text
+              (only with fuzzy)
+            
+        Raises:
+            ValueError: If regex patterns are invalid, a field is unknown, a
+                capture group is named like one of the columns above or
+                case_sensitive_flags doesn't match patterns in length
+        """
+        ...
+    
+    @staticmethod
+    def search_dataframe(
+        df: DataFrame,
+        columns: List[str],
+        patterns: List[str],
+        case_sensitive: Optional[bool] = None,
+        context_chars: int = 100,
+    ) -> DataFrame:
+        """
+        Search the text columns of an already extracted DataFrame (e.g. from
+        xml_to_polars or ndjson_to_polars), so a corpus can be extracted once
+        and queried many times without re-reading the XML.
+        
+        Args:
+            df: DataFrame to search
+            columns: Names of the string columns to search (e.g. "abstract",
+                "full_text"); null cells are skipped
+            patterns: List of regex patterns to search for
+            case_sensitive: Whether search should be case sensitive (default: False)
+            context_chars: Characters of context kept on each side of a match
+            
+        Returns:
+            Polars DataFrame with one row per match, ordered by row, column,
+            pattern and position, with columns:
+            - row_index: int - Row of df the match is in
+            - column: str - Column the match is in
+            - matched_pattern: str - Pattern that matched
+            - match_start: int - Byte offset of the match in the cell text
+            - match_end: int - Byte offset just past the match
+            - matched_text: str - The matched substring
+            - match_context: str - Context around the match
+              (±context_chars characters)
+            
+        Raises:
+            ValueError: If regex patterns are invalid, or a column is missing
+                or not a string column
+        """
+        ...
+    
+    @staticmethod
+    def count_xml_matches(
+        xml_paths: List[str],
+        patterns: List[str],
+        case_sensitive: Optional[bool] = None,
+        num_threads: Optional[int] = None,
+    ) -> DataFrame:
+        """
+        Count pattern matches in the raw XML of each file, giving a compact
+        file x pattern matrix instead of one row per match.
+        
+        Args:
+            xml_paths: List of paths to XML files to search
+            patterns: List of regex patterns; each becomes a column
+            case_sensitive: Whether search should be case sensitive (default: False)
+            num_threads: Worker threads to use (default: all cores)
+            
+        Returns:
+            Polars DataFrame with one row per input path, in order:
+            - file_path: str - Path of the file
+            - <pattern>: Optional[int] - Number of matches of that pattern;
+              null when the file could not be read
+            
+        Raises:
+            ValueError: If regex patterns are invalid, repeated, or equal to
+                "file_path"
+        """
+        ...
+    
+    @staticmethod
+    def word_frequencies(
+        xml_paths: List[str],
+        field: str = "abstract",
+        top_n: Optional[int] = 100,
+        min_length: int = 3,
+        num_threads: Optional[int] = None,
+    ) -> DataFrame:
+        """
+        Count the words of one extracted text field across a corpus.
+        
+        Args:
+            xml_paths: List of paths to XML files
+            field: Field to count: "title", "abstract", "full_text",
+                "keywords", "mesh_terms" or "data_availability"
+            top_n: Keep only the N most frequent words (None for all)
+            min_length: Leave out words shorter than this many characters
+            num_threads: Worker threads to use (default: all cores)
+            
+        Returns:
+            Polars DataFrame, most frequent first (ties alphabetically):
+            - word: str - Lowercased word, split on Unicode word boundaries
+            - count: int - Occurrences across all files
             
         Raises:
-            ValueError: If regex patterns are invalid
+            ValueError: If field is not one of the fields above
+        """
+        ...    
+    @staticmethod
+    def tag_terms(
+        xml_paths: List[str],
+        term_dict: Dict[str, List[str]],
+        field: str = "abstract",
+        num_threads: Optional[int] = None,
+    ) -> DataFrame:
         """
-        ...
\ No newline at end of file
+        Tag articles with controlled vocabularies, e.g.
+        {"disease": ["asthma", "COPD"], "gene": ["BRCA1"]}. All terms are
+        compiled into one regex and matched as whole words, ignoring case;
+        where terms overlap the longest wins ("breast cancer" over "cancer").
+        Files are scanned in parallel; unreadable files are logged and skipped.
+        
+        Args:
+            xml_paths: List of paths to XML files
+            term_dict: Terms to look for, by category name
+            field: Field to tag: "title", "abstract", "full_text",
+                "keywords", "mesh_terms" or "data_availability"
+            num_threads: Worker threads to use (default: all cores)
+            
+        Returns:
+            Polars DataFrame with one row per term found in a file, ordered by
+            input file, category name and term order:
+            - file_path: str - Path of the source file
+            - category: str - Category of the term in term_dict
+            - term: str - The term as given in term_dict
+            - count: int - Occurrences in the field
+            
+        Raises:
+            ValueError: If field is not one of the fields above
+        """
+        ...    
+    @staticmethod
+    def extract_accessions(
+        xml_paths: List[str],
+        patterns: Optional[Dict[str, str]] = None,
+        context_chars: int = 100,
+    ) -> DataFrame:
+        """
+        Find database accession numbers in the extracted full text (not the
+        raw XML) of each file.
+        
+        Built-in types: "genbank" (e.g. AB123456, U12345.1), "geo" (GSE/GSM/
+        GPL/GDS), "sra" (SRR/ERX/DRP...) and "pdb" (e.g. 4HHB; matches need an
+        uppercase letter and no lowercase ones, to skip years and units).
+        
+        Args:
+            xml_paths: List of paths to XML files
+            patterns: Accession type -> regex; replaces the built-in regex of
+                that type or adds a new type
+            context_chars: Characters of text kept on each side of a match
+            
+        Returns:
+            Polars DataFrame with one row per occurrence, in document order:
+            - file_path: str - Path of the file
+            - accession_type: str - Type the matching regex belongs to
+            - accession_id: str - Matched accession
+            - context: str - The accession with surrounding text
+            
+        Raises:
+            ValueError: If a regex pattern is invalid
+        """
+        ...
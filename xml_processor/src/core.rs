@@ -0,0 +1,5027 @@
+//! Article extraction without any Python dependency: the metadata types,
+//! the JATS/PubMed parsers and the article DataFrame builder. The `nxml`
+//! module wraps these as Python functions.
+
+// The parser keeps one match arm per tag with the state checks inside it,
+// which reads better than folding every check into a match guard.
+#![allow(clippy::collapsible_match)]
+
+use crate::error::XmlProcessorError;
+use flate2::read::MultiGzDecoder;
+use polars::prelude::*;
+use quick_xml::escape::resolve_predefined_entity;
+use quick_xml::events::{BytesRef, BytesText, Event};
+use quick_xml::Reader;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{hash_map::Entry, HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::sync::{Condvar, LazyLock, Mutex};
+use unicode_segmentation::UnicodeSegmentation;
+
+pub(crate) type Result<T, E = XmlProcessorError> = std::result::Result<T, E>;
+
+/// Version of the `ArticleMetadata` record layout, written as the first key of
+/// every NDJSON record; bumped whenever a field is added, removed or changes
+/// meaning
+pub const SCHEMA_VERSION: u32 = 9;
+
+/// Metadata for an article; also the Python object returned by `parse_article`,
+/// with a read-only attribute per field. Serialized fields keep their
+/// declaration order, so new fields go at the end or next to related ones but
+/// never reorder existing ones.
+#[cfg_attr(
+    feature = "python",
+    pyo3::pyclass(module = "xml_processor.nxml", get_all)
+)]
+#[derive(Serialize, Deserialize, Default)]
+// Fields missing from NDJSON written by older versions read back as empty
+#[serde(default)]
+pub struct ArticleMetadata {
+    /// [`SCHEMA_VERSION`] of the code that extracted the record; 0 for records
+    /// read back from NDJSON written before versioning
+    pub schema_version: u32,
+    pub pmid: Option<String>,
+    pub pmc_id: Option<String>,
+    /// `<article-version>` (e.g. "1.3" for a revised preprint), else the
+    /// version suffix of a versioned PMC id such as `PMC12345.2`
+    pub article_version: Option<String>,
+    pub title: Option<String>,
+    /// `<subtitle>` of the article's `<title-group>`
+    pub subtitle: Option<String>,
+    /// (`xml:lang`, text) of each `<trans-title>`; the language is empty when
+    /// neither the title nor its `<trans-title-group>` gives one
+    pub trans_titles: Vec<(String, String)>,
+    /// `article-type` of the root `<article>` (JATS), or the first
+    /// `<PublicationType>` (PubMed)
+    pub article_type: Option<String>,
+    /// `xml:lang` of the root `<article>`; with the `langdetect` feature,
+    /// otherwise a guess from the title and abstract (ISO 639-1 code)
+    pub language: Option<String>,
+    /// Every `<PublicationType>` of a PubMed record
+    pub publication_types: Vec<String>,
+    /// The first abstract, or the first English one when the first has
+    /// another `xml:lang`; see `abstracts_by_language` for all of them
+    pub abstract_text: Option<String>,
+    /// (label, text) per `<sec>` of a structured abstract; an unstructured
+    /// abstract yields a single entry with an empty label
+    pub abstract_sections: Vec<(String, String)>,
+    /// (`xml:lang`, text) of every non-empty `<abstract>`, in document order;
+    /// the language is empty when the abstract doesn't give one
+    pub abstracts_by_language: Vec<(String, String)>,
+    /// Inner XML of the `<abstract>`, markup included, with
+    /// [`ExtractOptions::keep_raw_abstract`]
+    pub abstract_xml: Option<String>,
+    /// `xlink:href` of the `<graphic>` in an `<abstract
+    /// abstract-type="graphical">`, and the text of its `<caption>`; a
+    /// graphical abstract never feeds `abstract_text`
+    pub graphical_abstract_href: Option<String>,
+    pub graphical_abstract_caption: Option<String>,
+    pub authors: Vec<String>,
+    /// Name parts of each author, aligned with `authors`; `None` where the
+    /// contrib has no such element (e.g. a given-name-only or group author)
+    pub author_surnames: Vec<Option<String>>,
+    pub author_given_names: Vec<Option<String>>,
+    /// Whether each author is a group (`<collab>`, PubMed `<CollectiveName>`)
+    /// named as a whole rather than a person, aligned with `authors`
+    pub author_is_collab: Vec<bool>,
+    /// Affiliation texts of each author, aligned with `authors`
+    pub affiliations: Vec<Vec<String>>,
+    /// Bare ORCID (`0000-0002-1825-0097`) of each author, aligned with `authors`
+    pub orcids: Vec<Option<String>>,
+    /// `<role>` texts of each author (CRediT terms such as "Conceptualization"
+    /// where the publisher uses them), aligned with `authors`
+    pub author_roles: Vec<Vec<String>>,
+    /// Name (as in `authors`) of the contrib marked `corresp="yes"` or
+    /// pointing to a `<corresp>` note
+    pub corresponding_author: Option<String>,
+    /// Position of `corresponding_author` in `authors`, which tells
+    /// namesakes apart
+    pub corresponding_author_index: Option<usize>,
+    /// Email of the corresponding author, from the contrib itself or its
+    /// `<corresp>` note; otherwise the first email in `<author-notes>`
+    pub corresponding_email: Option<String>,
+    /// `<contrib contrib-type="editor">` names, formatted like `authors`
+    pub editors: Vec<String>,
+    pub keywords: Vec<String>,
+    pub mesh_terms: Vec<String>,
+    pub journal: Option<String>,
+    /// ISSNs from `<journal-meta>`, told apart by `pub-type` ("ppub"/"epub")
+    /// or `publication-format` ("print"/"electronic")
+    pub issn_print: Option<String>,
+    pub issn_electronic: Option<String>,
+    /// NLM title abbreviation (`<journal-id journal-id-type="nlm-ta">`), else
+    /// the `<abbrev-journal-title>`
+    pub journal_abbrev: Option<String>,
+    pub publisher_name: Option<String>,
+    /// Citation details from `<article-meta>` (never from reference lists)
+    pub volume: Option<String>,
+    pub issue: Option<String>,
+    pub first_page: Option<String>,
+    pub last_page: Option<String>,
+    /// Article number used instead of pages, e.g. `e1234`
+    pub elocation_id: Option<String>,
+    pub publication_date: Option<String>,
+    /// Dates from `<history>` (`YYYY[-MM[-DD]]`); `date_published` falls back
+    /// to the electronic publication date
+    pub date_received: Option<String>,
+    pub date_accepted: Option<String>,
+    pub date_published: Option<String>,
+    /// Lowercased, without a `https://doi.org/` prefix
+    pub doi: Option<String>,
+    /// Element the DOI was taken from: `article-id`, `elocation-id`,
+    /// `self-uri` or `ext-link`
+    pub doi_source: Option<String>,
+    pub full_text: Option<String>,
+    /// Text of each `<p>` in `<body>`; not serialized since `full_text` has it
+    #[serde(skip)]
+    pub body_paragraphs: Vec<String>,
+    /// (sec_type, title, text) for every `<sec>` in `<body>`, in document
+    /// order; text belongs to the innermost enclosing section
+    #[serde(skip)]
+    pub full_text_sections: Vec<(String, String, String)>,
+    /// One entry per (funder, award id) of each `<award-group>`
+    pub funding: Vec<Award>,
+    pub is_open_access: Option<bool>,
+    /// `license-type` of the front-matter `<license>`, or the type implied by
+    /// a Creative Commons URL (`cc-by`, `cc-by-nc-nd`, `cc0`, ...)
+    pub license_type: Option<String>,
+    /// `xlink:href` of the `<license>`, else its `<ali:license_ref>` or the
+    /// first link in its `<license-p>`
+    pub license_url: Option<String>,
+    /// Text of the `<license-p>` paragraphs
+    pub license_text: Option<String>,
+    /// `<copyright-statement>`, else "© year holder" built from
+    /// `<copyright-year>`/`<copyright-holder>`
+    pub copyright_statement: Option<String>,
+    /// Text of the `<ack>` blocks, without their titles
+    pub acknowledgements: Option<String>,
+    /// Author-contributions statement (`<fn fn-type="con">` or an
+    /// "author-contributions" `<sec>`/`<notes>`), without its title; the
+    /// fallback for articles without per-author `<role>`s
+    pub contributions_text: Option<String>,
+    pub conflict_of_interest: Option<String>,
+    pub has_competing_interests: Option<bool>,
+    /// Text of the `data-availability` `<sec>`/`<notes>`/`<fn>` blocks (in
+    /// body or back matter), without their titles
+    pub data_availability: Option<String>,
+    /// Clinical trial registration numbers (NCT, ISRCTN, EudraCT, ...) from
+    /// `<related-object>` registry links, then NCT/ISRCTN numbers mentioned in
+    /// the abstract or body; deduplicated, in that order
+    pub trial_numbers: Vec<String>,
+    /// Well-formed but without title, abstract or body text, e.g. a truncated
+    /// stub; reported as `empty` rather than as a successful record
+    pub is_empty: bool,
+    /// Modification time of the source file (Unix seconds) when it was
+    /// read; `None` for records extracted from a string or archive member
+    pub source_mtime: Option<i64>,
+    pub file_path: String,
+}
+
+/// Options that change how an article is read and its text extracted
+#[derive(Clone, Default)]
+pub struct ExtractOptions {
+    /// Repair common XML issues before parsing (see [`repair_xml`])
+    pub lenient_xml: bool,
+    /// Leave the text of `<xref>` markers (citation numbers, "Fig. 2", ...)
+    /// out of the body text
+    pub strip_xref: bool,
+    /// Collapse whitespace runs in title, abstract and full text to single
+    /// spaces (see [`normalize_whitespace`])
+    pub normalize_whitespace: bool,
+    /// Also keep the abstract's inner XML verbatim, in `abstract_xml`
+    pub keep_raw_abstract: bool,
+    /// Refuse files larger than this many bytes on disk (and, in multi-article
+    /// files, articles larger than this) instead of reading them into memory
+    pub max_bytes: Option<u64>,
+    /// Build `full_text` only from the body `<sec>`s (and their subsections)
+    /// whose `sec-type` or title matches one of these, e.g. `["methods",
+    /// "materials-and-methods"]`; see [`section_key`] for the comparison
+    pub sections_filter: Option<Vec<String>>,
+    /// How many more times to try reading a file after an I/O error (e.g. on
+    /// a flaky network filesystem), waiting `retry_delay_ms` in between; parse
+    /// errors are never retried
+    pub retries: u32,
+    pub retry_delay_ms: u64,
+    /// Lowercase title, abstract, full text and journal (after whitespace
+    /// normalization) for search indexing; identifiers are left as they are.
+    /// The original casing is lost.
+    pub lowercase: bool,
+    /// Write digits (and `+`, `-`, `=`, parentheses) in `<sup>`/`<sub>` of the
+    /// title and abstract as Unicode super/subscripts (`CO<sub>2</sub>` ->
+    /// `CO₂`) instead of plain characters (`CO2`)
+    pub unicode_superscripts: bool,
+    /// Don't collect the body text (`full_text`, `body_paragraphs`,
+    /// `full_text_sections`), for callers that only want the front matter;
+    /// `trial_numbers` then only has those linked or mentioned in the abstract
+    pub skip_body_text: bool,
+    /// Most files read at once across all threads (default
+    /// [`DEFAULT_MAX_OPEN_FILES`]); only reading waits for a turn, parsing
+    /// still runs on every thread. Lower it on filesystems that struggle with
+    /// many concurrent reads or under a low descriptor limit, at the cost of
+    /// threads idling while they wait. The count is shared by the whole
+    /// process, so concurrent calls with different limits throttle each
+    /// other: each waits until fewer than its own limit are open in total.
+    pub max_open_files: Option<usize>,
+}
+
+/// Default of [`ExtractOptions::max_open_files`]
+pub const DEFAULT_MAX_OPEN_FILES: usize = 256;
+
+/// Form in which section types and titles are compared against
+/// [`ExtractOptions::sections_filter`]: lowercased, with every run of other
+/// characters than letters and digits turned into a single `-`
+/// ("Materials and Methods" and "materials_and_methods" both give
+/// "materials-and-methods")
+pub fn section_key(name: &str) -> String {
+    name.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// A single entry of an article's reference list
+#[derive(Serialize, Deserialize, Default)]
+pub struct Reference {
+    pub id: Option<String>,
+    pub label: Option<String>,
+    pub text: Option<String>,
+    pub doi: Option<String>,
+    pub pmid: Option<String>,
+    /// `<article-title>` of the cited work
+    pub title: Option<String>,
+    pub year: Option<String>,
+}
+
+/// A grant from an article's `<funding-group>`
+#[cfg_attr(
+    feature = "python",
+    pyo3::pyclass(module = "xml_processor.nxml", get_all)
+)]
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct Award {
+    pub funder: String,
+    /// Funder identifier (e.g. a FundRef DOI) from `<institution-id>` or the
+    /// `<funding-source>` link, if given
+    pub funder_id: Option<String>,
+    pub award_id: Option<String>,
+}
+
+/// A `<fig>` with its caption and the image it points to
+#[derive(Serialize, Deserialize, Default)]
+pub struct Figure {
+    pub id: Option<String>,
+    pub label: Option<String>,
+    /// Flattened `<caption>` text (title and paragraphs); empty if none
+    pub caption: String,
+    /// `xlink:href` of the first `<graphic>`
+    pub graphic_href: Option<String>,
+}
+
+/// One file of a `<supplementary-material>` block: a `<media>` in it, or the
+/// block itself when it links its file directly
+#[derive(Serialize, Deserialize, Default)]
+pub struct SupplementaryFile {
+    /// `<label>` of the block, shared by all its media
+    pub label: Option<String>,
+    pub href: Option<String>,
+    /// `mimetype`/`mime-subtype`, e.g. `application/pdf`
+    pub mimetype: Option<String>,
+}
+
+/// Entity tagged by the publisher with `<named-content content-type=...>`
+/// (species, gene, chemical, ...), with how often it occurs in the article
+#[derive(Serialize, Deserialize)]
+pub struct NamedEntity {
+    pub entity_type: String,
+    pub text: String,
+    pub count: u32,
+}
+
+/// One target of an inline `<xref>` in the body; an xref pointing at several
+/// targets (`rid="fig1 fig2"`) gives one entry per target
+#[derive(Serialize, Deserialize)]
+pub struct CrossReference {
+    /// `ref-type` of the xref: "bibr", "fig", "table", ...
+    pub ref_type: Option<String>,
+    pub rid: String,
+    /// Whitespace-normalized text around the xref within its paragraph,
+    /// including the xref's own text
+    pub context: String,
+}
+
+/// A `<table-wrap>` with its caption and cell matrix
+#[derive(Serialize, Deserialize, Default)]
+pub struct Table {
+    pub id: Option<String>,
+    pub label: Option<String>,
+    pub caption: String,
+    /// One entry per `<tr>` (header rows first); spanned cells are repeated
+    /// so every row covers the full width
+    pub rows: Vec<Vec<String>>,
+}
+
+/// Builds table rows, expanding `colspan`/`rowspan` by repeating cell values
+#[derive(Default)]
+struct TableGrid {
+    rows: Vec<Vec<String>>,
+    current_row: Vec<String>,
+    /// Per column: rows still covered by a rowspan from above, and its text
+    carried: Vec<Option<(usize, String)>>,
+}
+
+impl TableGrid {
+    /// Fill columns at the current position still covered by a rowspan
+    fn fill_carried(&mut self) {
+        while let Some(Some((remaining, text))) = self.carried.get_mut(self.current_row.len()) {
+            self.current_row.push(text.clone());
+            *remaining -= 1;
+            if *remaining == 0 {
+                self.carried[self.current_row.len() - 1] = None;
+            }
+        }
+    }
+
+    fn push_cell(&mut self, text: String, colspan: usize, rowspan: usize) {
+        self.fill_carried();
+        for _ in 0..colspan.max(1) {
+            let column = self.current_row.len();
+            if rowspan > 1 {
+                if self.carried.len() <= column {
+                    self.carried.resize(column + 1, None);
+                }
+                self.carried[column] = Some((rowspan - 1, text.clone()));
+            }
+            self.current_row.push(text.clone());
+        }
+    }
+
+    fn end_row(&mut self) {
+        self.fill_carried();
+        self.rows.push(std::mem::take(&mut self.current_row));
+    }
+}
+
+/// Numeric span attribute (`colspan`/`rowspan`), 1 when absent or malformed
+fn span_attribute(e: &quick_xml::events::BytesStart, key: &[u8]) -> usize {
+    attribute_value(e, key)
+        .and_then(|value| value.trim().parse().ok())
+        .unwrap_or(1)
+}
+
+/// Phrases that mark a competing-interest statement as a declaration of *no*
+/// competing interests. Matched case-insensitively as substrings.
+const NO_COMPETING_INTERESTS_PHRASES: &[&str] = &[
+    "no competing",
+    "no conflict",
+    "no potential conflict",
+    "no known conflict",
+    "no known competing",
+    "no financial conflict",
+    "no relevant financial",
+    "no financial or non-financial",
+    "not have any competing",
+    "not have any conflict",
+    "nothing to disclose",
+    "nothing to declare",
+    "no disclosures",
+    "none declared",
+    "declare none",
+    "declares none",
+];
+
+/// Classify a competing-interest statement: `Some(false)` when it matches one
+/// of [`NO_COMPETING_INTERESTS_PHRASES`] (or is just "None"), `Some(true)` for
+/// any other non-empty statement, `None` when there is no statement.
+///
+/// This is a phrase heuristic, not NLP: a statement such as "X received fees
+/// from Y; the other authors declare no competing interests" is classified as
+/// `false`, and unusual wording of a negative statement is classified as `true`.
+pub fn classify_competing_interests(statement: Option<&str>) -> Option<bool> {
+    let statement = statement?.trim();
+    if statement.is_empty() {
+        return None;
+    }
+
+    let lower = statement.to_lowercase();
+    let bare = lower.trim_end_matches('.');
+    if bare == "none" || bare == "n/a" {
+        return Some(false);
+    }
+
+    Some(
+        !NO_COMPETING_INTERESTS_PHRASES
+            .iter()
+            .any(|phrase| lower.contains(phrase)),
+    )
+}
+
+/// Whether an element's type attribute marks it as a competing-interest statement
+fn is_coi_element(e: &quick_xml::events::BytesStart) -> bool {
+    e.attributes().flatten().any(|attr| {
+        matches!(attr.key.as_ref(), b"fn-type" | b"sec-type" | b"notes-type") && {
+            let value = String::from_utf8_lossy(&attr.value).to_lowercase();
+            value.contains("conflict") || value.contains("competing") || value.starts_with("coi")
+        }
+    })
+}
+
+/// Which `ArticleMetadata` field a `<journal-meta>` element fills: "nlm_ta" and
+/// "abbrev_title" (the candidates for `journal_abbrev`), "issn_print",
+/// "issn_electronic" or "publisher_name"; `None` for other journal ids and
+/// ISSNs of unknown type
+fn journal_meta_field_of(e: &quick_xml::events::BytesStart) -> Option<&'static str> {
+    match e.local_name().as_ref() {
+        b"journal-id" => (attribute_value(e, b"journal-id-type").as_deref() == Some("nlm-ta"))
+            .then_some("nlm_ta"),
+        b"abbrev-journal-title" => Some("abbrev_title"),
+        b"issn" => {
+            let issn_type = attribute_value(e, b"pub-type")
+                .or_else(|| attribute_value(e, b"publication-format"))?;
+            match issn_type.to_lowercase().as_str() {
+                "ppub" | "print" => Some("issn_print"),
+                "epub" | "electronic" => Some("issn_electronic"),
+                _ => None,
+            }
+        }
+        b"publisher-name" => Some("publisher_name"),
+        _ => None,
+    }
+}
+
+/// Whether an element is an author-contributions statement (`fn-type="con"`,
+/// or a `sec-type`/`notes-type`/`fn-type` mentioning "contribution")
+fn is_contributions_element(e: &quick_xml::events::BytesStart) -> bool {
+    e.attributes().flatten().any(|attr| {
+        matches!(attr.key.as_ref(), b"fn-type" | b"sec-type" | b"notes-type") && {
+            let value = String::from_utf8_lossy(&attr.value).to_lowercase();
+            value == "con" || value.contains("contribution")
+        }
+    })
+}
+
+/// Whether an element is a data-availability statement (`sec-type`,
+/// `notes-type` or `fn-type` of "data-availability", "data_availability", ...)
+fn is_data_availability_element(e: &quick_xml::events::BytesStart) -> bool {
+    e.attributes().flatten().any(|attr| {
+        matches!(attr.key.as_ref(), b"fn-type" | b"sec-type" | b"notes-type")
+            && String::from_utf8_lossy(&attr.value)
+                .to_lowercase()
+                .replace(['_', ' '], "-")
+                .contains("data-availability")
+    })
+}
+
+/// Named entities outside XML's predefined five that turn up in article text
+/// (declared by the JATS DTD), with the characters they stand for
+const NAMED_ENTITIES: &[(&str, &str)] = &[
+    ("nbsp", "\u{a0}"),
+    ("ndash", "\u{2013}"),
+    ("mdash", "\u{2014}"),
+    ("lsquo", "\u{2018}"),
+    ("rsquo", "\u{2019}"),
+    ("ldquo", "\u{201c}"),
+    ("rdquo", "\u{201d}"),
+    ("hellip", "\u{2026}"),
+    ("deg", "\u{b0}"),
+    ("plusmn", "\u{b1}"),
+    ("micro", "\u{b5}"),
+    ("middot", "\u{b7}"),
+    ("times", "\u{d7}"),
+    ("divide", "\u{f7}"),
+    ("minus", "\u{2212}"),
+    ("le", "\u{2264}"),
+    ("ge", "\u{2265}"),
+    ("ne", "\u{2260}"),
+    ("asymp", "\u{2248}"),
+    ("infin", "\u{221e}"),
+    ("rarr", "\u{2192}"),
+    ("larr", "\u{2190}"),
+    ("prime", "\u{2032}"),
+    ("alpha", "\u{3b1}"),
+    ("beta", "\u{3b2}"),
+    ("gamma", "\u{3b3}"),
+    ("delta", "\u{3b4}"),
+    ("epsilon", "\u{3b5}"),
+    ("zeta", "\u{3b6}"),
+    ("eta", "\u{3b7}"),
+    ("theta", "\u{3b8}"),
+    ("iota", "\u{3b9}"),
+    ("kappa", "\u{3ba}"),
+    ("lambda", "\u{3bb}"),
+    ("mu", "\u{3bc}"),
+    ("nu", "\u{3bd}"),
+    ("xi", "\u{3be}"),
+    ("pi", "\u{3c0}"),
+    ("rho", "\u{3c1}"),
+    ("sigma", "\u{3c3}"),
+    ("tau", "\u{3c4}"),
+    ("phi", "\u{3c6}"),
+    ("chi", "\u{3c7}"),
+    ("psi", "\u{3c8}"),
+    ("omega", "\u{3c9}"),
+    ("Gamma", "\u{393}"),
+    ("Delta", "\u{394}"),
+    ("Theta", "\u{398}"),
+    ("Lambda", "\u{39b}"),
+    ("Sigma", "\u{3a3}"),
+    ("Phi", "\u{3a6}"),
+    ("Psi", "\u{3a8}"),
+    ("Omega", "\u{3a9}"),
+];
+
+/// Decoded text of an entity or character reference (`&#x3b2;`, `&beta;`,
+/// `&amp;`); an entity that is neither predefined nor in `NAMED_ENTITIES` is
+/// kept as written
+fn reference_text(e: &BytesRef) -> String {
+    if let Ok(Some(c)) = e.resolve_char_ref() {
+        return c.to_string();
+    }
+    let name = e.decode().unwrap_or_default();
+    resolve_predefined_entity(&name)
+        .or_else(|| {
+            NAMED_ENTITIES
+                .iter()
+                .find(|(entity, _)| *entity == name)
+                .map(|(_, text)| *text)
+        })
+        .map_or_else(|| format!("&{name};"), str::to_string)
+}
+
+/// Next event of `reader`, with each run of text and entity or character
+/// references merged into one text event holding the decoded characters, so
+/// `&#x3b2;-catenin` reaches the handlers as a single piece. The reader must
+/// trim only leading whitespace (see `text_reader`); trailing whitespace is
+/// trimmed here, once the whole run is read, so `IL-6 &amp; TNF` keeps its
+/// spaces.
+fn next_event<'b>(
+    reader: &mut Reader<&[u8]>,
+    buf: &'b mut Vec<u8>,
+) -> quick_xml::Result<Event<'b>> {
+    // The reader has consumed the `<` ending a text event, but neither the
+    // `&` ending one nor anything after a reference
+    let ref_follows = |reader: &Reader<&[u8]>| reader.get_ref().first() == Some(&b'&');
+    let text_follows = |reader: &Reader<&[u8]>| {
+        reader
+            .get_ref()
+            .iter()
+            .find(|b| !b.is_ascii_whitespace())
+            .is_some_and(|&b| b != b'<')
+    };
+
+    let mut text = match reader.read_event_into(buf)? {
+        Event::Text(mut e) if !ref_follows(reader) => {
+            e.inplace_trim_end();
+            return Ok(Event::Text(e));
+        }
+        Event::Text(e) => String::from_utf8_lossy(&e).into_owned(),
+        Event::GeneralRef(e) => reference_text(&e),
+        event => return Ok(event),
+    };
+
+    let mut run_buf = Vec::new();
+    // Each pass starts at a reference or right after one
+    loop {
+        if !text_follows(reader) {
+            break;
+        }
+        // Whitespace after a reference would be lost to the leading trim of
+        // the text that follows
+        if reader.get_ref()[0].is_ascii_whitespace() {
+            text.push(' ');
+        }
+        // Whitespace-only text between two references is skipped by the
+        // reader, which then returns the second reference
+        match reader.read_event_into(&mut run_buf)? {
+            Event::Text(e) => {
+                text.push_str(&String::from_utf8_lossy(&e));
+                if !ref_follows(reader) {
+                    break;
+                }
+            }
+            Event::GeneralRef(e) => text.push_str(&reference_text(&e)),
+            _ => break,
+        }
+        run_buf.clear();
+    }
+    text.truncate(text.trim_end().len());
+    Ok(Event::Text(BytesText::from_escaped(text)))
+}
+
+/// Reader over `xml_content` set up for `next_event`
+fn text_reader(xml_content: &str) -> Reader<&[u8]> {
+    let mut reader = Reader::from_str(xml_content);
+    let config = reader.config_mut();
+    config.trim_text_start = true;
+    config.trim_text_end = false;
+    reader
+}
+
+/// Value of attribute `key` on an element, if present
+fn attribute_value(e: &quick_xml::events::BytesStart, key: &[u8]) -> Option<String> {
+    e.attributes()
+        .flatten()
+        .find(|attr| attr.key.as_ref() == key)
+        .map(|attr| String::from_utf8_lossy(&attr.value).into_owned())
+}
+
+/// Reduce an ORCID, usually given as `https://orcid.org/0000-...`, to its bare
+/// 16-character form; `None` if what remains doesn't look like an ORCID
+fn normalize_orcid(raw: &str) -> Option<String> {
+    let bare = raw.trim().trim_end_matches('/');
+    let bare = bare.rsplit('/').next().unwrap_or(bare).trim();
+    let digits: Vec<char> = bare.chars().filter(|c| *c != '-').collect();
+    let well_formed = digits.len() == 16
+        && digits[..15].iter().all(char::is_ascii_digit)
+        && (digits[15].is_ascii_digit() || digits[15] == 'X' || digits[15] == 'x');
+    well_formed.then(|| bare.to_uppercase())
+}
+
+/// Normalize a PMID such as `"PMID: 12345"` to its bare digits; `None` if
+/// what remains isn't a number
+pub fn normalize_pmid(raw: &str) -> Option<String> {
+    let pmid = raw.trim();
+    let pmid = match pmid.get(..4) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("pmid") => {
+            pmid[4..].trim_start().trim_start_matches(':').trim_start()
+        }
+        _ => pmid,
+    };
+    (!pmid.is_empty() && pmid.bytes().all(|b| b.is_ascii_digit())).then(|| pmid.to_string())
+}
+
+/// Normalize a PMC ID to the `PMC12345` form, adding the prefix to a bare
+/// number; `None` if what remains isn't a number
+pub fn normalize_pmc_id(raw: &str) -> Option<String> {
+    let pmc_id = raw.trim();
+    let digits = match pmc_id.get(..3) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("pmc") => &pmc_id[3..],
+        _ => pmc_id,
+    };
+    (!digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()))
+        .then(|| format!("PMC{digits}"))
+}
+
+/// Normalize a DOI, possibly given as a `https://doi.org/...` link, to its
+/// lowercased bare form; `None` if what remains doesn't look like a DOI
+pub fn normalize_doi(raw: &str) -> Option<String> {
+    let doi = raw.trim().to_lowercase();
+    let doi = [
+        "https://doi.org/",
+        "http://doi.org/",
+        "https://dx.doi.org/",
+        "http://dx.doi.org/",
+        "doi:",
+    ]
+    .iter()
+    .find_map(|prefix| doi.strip_prefix(prefix))
+    .unwrap_or(&doi)
+    .trim();
+    doi.starts_with("10.").then(|| doi.to_string())
+}
+
+/// Source name for a `<self-uri>` or `<ext-link>` that may carry the article's
+/// DOI: a self-uri pointing at doi.org, or an ext-link of type `doi`
+fn doi_link_source(e: &quick_xml::events::BytesStart) -> Option<&'static str> {
+    match e.local_name().as_ref() {
+        b"self-uri" => Some("self-uri"),
+        b"ext-link" if attribute_value(e, b"ext-link-type").as_deref() == Some("doi") => {
+            Some("ext-link")
+        }
+        _ => None,
+    }
+}
+
+/// Target ids of an `<xref ref-type="aff">`; empty for any other element
+fn aff_rids(e: &quick_xml::events::BytesStart) -> Vec<String> {
+    if attribute_value(e, b"ref-type").as_deref() != Some("aff") {
+        return Vec::new();
+    }
+    attribute_value(e, b"rid")
+        .map(|rids| rids.split_whitespace().map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+/// `rid`s of an `<xref ref-type="corresp">`, which points to a `<corresp>` note
+fn corresp_rids(e: &quick_xml::events::BytesStart) -> Vec<String> {
+    if attribute_value(e, b"ref-type").as_deref() != Some("corresp") {
+        return Vec::new();
+    }
+    attribute_value(e, b"rid")
+        .map(|rids| rids.split_whitespace().map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+/// Whether a license `license-type` or href marks the article as open access
+fn is_open_license(license_type: &str, href: &str) -> bool {
+    license_type.eq_ignore_ascii_case("open-access")
+        || license_type.to_lowercase().starts_with("cc")
+        || href.contains("creativecommons.org")
+}
+
+/// License type of a Creative Commons URL, e.g. `cc-by-nc` for
+/// `https://creativecommons.org/licenses/by-nc/4.0/` or `cc0` for the public
+/// domain dedication
+fn cc_license_type(url: &str) -> Option<String> {
+    let url = url.to_ascii_lowercase();
+    let (_, path) = url.split_once("creativecommons.org/")?;
+    let mut segments = path.split('/');
+    match (segments.next()?, segments.next()) {
+        ("licenses", Some(kind)) if !kind.is_empty() => Some(format!("cc-{kind}")),
+        ("publicdomain", Some("zero")) => Some("cc0".to_string()),
+        _ => None,
+    }
+}
+
+/// Whether an `<abstract>` is a graphical abstract (`abstract-type="graphical"`)
+fn is_graphical_abstract(e: &quick_xml::events::BytesStart) -> bool {
+    attribute_value(e, b"abstract-type").is_some_and(|t| t.eq_ignore_ascii_case("graphical"))
+}
+
+/// ClinicalTrials.gov and ISRCTN numbers as they appear in running text
+static TRIAL_NUMBER_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b(?:NCT|ISRCTN)\d{8}\b").unwrap());
+
+/// `document-id` of a `<related-object>` that links to a clinical trial
+/// registry (`source-type="clinical-trials-registry"`,
+/// `document-id-type="clinical-trial-number"`, ...)
+fn trial_registration_number(e: &quick_xml::events::BytesStart) -> Option<String> {
+    let mut is_trial = false;
+    let mut document_id = None;
+    for attr in e.attributes().flatten() {
+        let value = String::from_utf8_lossy(&attr.value);
+        match attr.key.as_ref() {
+            b"document-id" => document_id = Some(value.trim().to_string()),
+            _ => is_trial |= value.to_lowercase().contains("clinical-trial"),
+        }
+    }
+    document_id.filter(|id| is_trial && !id.is_empty())
+}
+
+/// Open-access status from a `<license>` element's attributes, if it says anything
+fn license_open_access(e: &quick_xml::events::BytesStart) -> Option<bool> {
+    let mut license_type = String::new();
+    let mut href = String::new();
+    for attr in e.attributes().flatten() {
+        match attr.key.as_ref() {
+            b"license-type" => license_type = String::from_utf8_lossy(&attr.value).into_owned(),
+            b"xlink:href" => href = String::from_utf8_lossy(&attr.value).into_owned(),
+            _ => {}
+        }
+    }
+    is_open_license(&license_type, &href).then_some(true)
+}
+
+/// Repair common XML defects that make quick-xml reject a document: bare `&`
+/// that don't start a valid entity reference (e.g. `AT&T`) are escaped to
+/// `&amp;`, and control characters that are illegal in XML 1.0 are dropped.
+/// Returns the repaired content and the number of fixes applied.
+pub fn repair_xml(xml_content: &str) -> (String, usize) {
+    let mut repaired = String::with_capacity(xml_content.len());
+    let mut fixes = 0;
+
+    for (idx, ch) in xml_content.char_indices() {
+        match ch {
+            '&' if !starts_with_entity(&xml_content[idx + 1..]) => {
+                repaired.push_str("&amp;");
+                fixes += 1;
+            }
+            '\t' | '\n' | '\r' => repaired.push(ch),
+            c if (c as u32) < 0x20 => fixes += 1,
+            c => repaired.push(c),
+        }
+    }
+
+    (repaired, fixes)
+}
+
+/// Longest entity reference name [`starts_with_entity`] looks through for its `;`
+const MAX_ENTITY_NAME_LEN: usize = 32;
+
+/// Whether `rest` (the text right after an `&`) is a well-formed entity reference
+fn starts_with_entity(rest: &str) -> bool {
+    // Stop at the first byte that can't be part of a name, so a bare `&` far
+    // from any `;` doesn't scan the rest of the document
+    let Some(end) = rest
+        .bytes()
+        .take(MAX_ENTITY_NAME_LEN + 1)
+        .position(|b| !(b.is_ascii_alphanumeric() || matches!(b, b'#' | b'_' | b'-' | b'.')))
+    else {
+        return false;
+    };
+    if rest.as_bytes()[end] != b';' {
+        return false;
+    }
+    let name = &rest[..end];
+    if let Some(hex) = name.strip_prefix("#x").or_else(|| name.strip_prefix("#X")) {
+        !hex.is_empty() && hex.chars().all(|c| c.is_ascii_hexdigit())
+    } else if let Some(dec) = name.strip_prefix('#') {
+        !dec.is_empty() && dec.chars().all(|c| c.is_ascii_digit())
+    } else {
+        name.chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+    }
+}
+
+/// Whether a file is gzip-compressed, judged by its `.gz` extension or the
+/// gzip magic number
+fn is_gzip(xml_path: &str, bytes: &[u8]) -> bool {
+    xml_path.ends_with(".gz") || bytes.starts_with(&[0x1f, 0x8b])
+}
+
+/// Reject archive formats other than gzip, recognised by their magic number,
+/// before they are parsed as garbled XML
+fn check_supported_format(bytes: &[u8]) -> Result<()> {
+    let format = if bytes.starts_with(b"PK\x03\x04") {
+        "zip archive"
+    } else if bytes.starts_with(b"BZh") {
+        "bzip2-compressed file"
+    } else if bytes.starts_with(b"\xfd7zXZ\x00") {
+        "xz-compressed file"
+    } else if bytes.starts_with(b"\x28\xb5\x2f\xfd") {
+        "zstd-compressed file"
+    } else {
+        return Ok(());
+    };
+    Err(XmlProcessorError::UnsupportedFormat(format.to_string()))
+}
+
+/// Read an XML file as UTF-8 text, transparently decompressing `.xml.gz` files
+pub(crate) fn read_xml_text(xml_path: &str) -> Result<String> {
+    let bytes = std::fs::read(xml_path)?;
+    check_supported_format(&bytes)?;
+    if !is_gzip(xml_path, &bytes) {
+        return String::from_utf8(bytes)
+            .map_err(|e| XmlProcessorError::UnsupportedFormat(format!("not UTF-8 text ({e})")));
+    }
+
+    let mut xml_content = String::new();
+    MultiGzDecoder::new(bytes.as_slice())
+        .read_to_string(&mut xml_content)
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::InvalidData => {
+                XmlProcessorError::UnsupportedFormat(format!("not UTF-8 text ({e})"))
+            }
+            _ => e.into(),
+        })?;
+    Ok(xml_content)
+}
+
+/// Fail with [`XmlProcessorError::TooLarge`] if the file at `xml_path` is
+/// larger than `limit` bytes, without reading it
+pub(crate) fn check_file_size(xml_path: &str, limit: u64) -> Result<()> {
+    let size = std::fs::metadata(xml_path)?.len();
+    if size > limit {
+        return Err(XmlProcessorError::TooLarge { size, limit });
+    }
+    Ok(())
+}
+
+/// Read an XML file, optionally repairing it with [`repair_xml`] first
+#[cfg(feature = "python")]
+pub(crate) fn load_xml(xml_path: &str, lenient_xml: bool) -> Result<String> {
+    let xml_content = read_xml_text(xml_path)?;
+    Ok(repair_if_lenient(xml_content, xml_path, lenient_xml))
+}
+
+/// `xml_content` repaired with [`repair_xml`] when `lenient_xml` is set
+fn repair_if_lenient(xml_content: String, xml_path: &str, lenient_xml: bool) -> String {
+    if !lenient_xml {
+        return xml_content;
+    }
+
+    let (repaired, fixes) = repair_xml(&xml_content);
+    if fixes > 0 {
+        log::info!("Applied {fixes} XML fixes to {xml_path}");
+    }
+    repaired
+}
+
+/// Open an XML file for streaming, transparently decompressing `.xml.gz` files
+fn open_xml_stream(xml_path: &str) -> Result<Box<dyn BufRead>> {
+    let mut file = BufReader::new(File::open(xml_path)?);
+    check_supported_format(file.fill_buf()?)?;
+    if is_gzip(xml_path, file.fill_buf()?) {
+        Ok(Box::new(BufReader::new(MultiGzDecoder::new(file))))
+    } else {
+        Ok(Box::new(file))
+    }
+}
+
+/// Elements that hold one article in a multi-article file
+fn is_article_element(name: &[u8]) -> bool {
+    matches!(name, b"PubmedArticle" | b"article")
+}
+
+/// Memory-map an uncompressed XML file, or `None` when it is gzip-compressed
+/// or can't be mapped (e.g. on some network filesystems), in which case it
+/// should be streamed instead.
+///
+/// The map is read-only, but its bytes can still change under us: a file
+/// truncated or rewritten by another process while it is mapped is undefined
+/// behaviour (typically a SIGBUS on access past the new end). Input files are
+/// treated as immutable while they are being read, as they already are by the
+/// parallel batch functions.
+fn map_xml_file(xml_path: &str) -> Result<Option<memmap2::Mmap>> {
+    let file = File::open(xml_path)?;
+    // SAFETY: see above; the map never outlives this read of the file
+    let mmap = match unsafe { memmap2::Mmap::map(&file) } {
+        Ok(mmap) => mmap,
+        Err(e) => {
+            log::debug!("Reading {xml_path} without mmap: {e}");
+            return Ok(None);
+        }
+    };
+    check_supported_format(&mmap)?;
+    Ok((!is_gzip(xml_path, &mmap)).then_some(mmap))
+}
+
+/// Stream a multi-article file (a PubMed `<PubmedArticleSet>` or a JATS
+/// `<pmc-articleset>`), calling `on_article` with the extraction result of each
+/// `<PubmedArticle>`/`<article>` in turn. Only one article's XML is held in
+/// memory at a time; with `options.max_bytes`, an article longer than that is
+/// dropped as soon as it exceeds it and reported as
+/// [`XmlProcessorError::TooLarge`].
+///
+/// Uncompressed files are memory-mapped rather than copied onto the heap (see
+/// [`for_each_article_buffered`] for the fallback).
+pub fn for_each_article(
+    xml_path: &str,
+    options: &ExtractOptions,
+    on_article: impl FnMut(Result<ArticleMetadata>),
+) -> Result<()> {
+    match map_xml_file(xml_path)? {
+        Some(mmap) => articles_from_reader(
+            Reader::from_reader(&mmap[..]),
+            xml_path,
+            options,
+            on_article,
+        ),
+        None => for_each_article_buffered(xml_path, options, on_article),
+    }
+}
+
+/// [`for_each_article`] reading the file through a buffer (decompressing
+/// `.xml.gz` files) instead of memory-mapping it
+pub fn for_each_article_buffered(
+    xml_path: &str,
+    options: &ExtractOptions,
+    on_article: impl FnMut(Result<ArticleMetadata>),
+) -> Result<()> {
+    let reader = Reader::from_reader(open_xml_stream(xml_path)?);
+    articles_from_reader(reader, xml_path, options, on_article)
+}
+
+/// The article loop of [`for_each_article`] over any reader
+fn articles_from_reader<R: BufRead>(
+    mut reader: Reader<R>,
+    xml_path: &str,
+    options: &ExtractOptions,
+    mut on_article: impl FnMut(Result<ArticleMetadata>),
+) -> Result<()> {
+    let mut buf = Vec::new();
+    // Serializes the events of the current article back to XML text
+    let mut article: Option<quick_xml::Writer<Vec<u8>>> = None;
+    let mut in_article = false;
+    let mut article_start = 0;
+    let mut depth = 0usize;
+
+    loop {
+        let event = reader.read_event_into(&mut buf)?;
+
+        match &event {
+            Event::Eof => break,
+            Event::Start(e) if !in_article && is_article_element(e.local_name().as_ref()) => {
+                article = Some(quick_xml::Writer::new(Vec::new()));
+                in_article = true;
+                article_start = reader.buffer_position() - e.len() as u64 - 2;
+                depth = 0;
+            }
+            _ => {}
+        }
+
+        if in_article {
+            match &event {
+                Event::Start(_) => depth += 1,
+                Event::End(_) => depth -= 1,
+                _ => {}
+            }
+            // An oversized article is still read to its end, but not kept
+            if let Some(writer) = article.as_mut() {
+                writer.write_event(event)?;
+            }
+            let size = reader.buffer_position() - article_start;
+            if options.max_bytes.is_some_and(|limit| size > limit) {
+                article = None;
+            }
+
+            if depth == 0 {
+                in_article = false;
+                let Some(writer) = article.take() else {
+                    let limit = options.max_bytes.unwrap_or_default();
+                    on_article(Err(XmlProcessorError::TooLarge { size, limit }));
+                    buf.clear();
+                    continue;
+                };
+                let xml_content = String::from_utf8(writer.into_inner()).map_err(|e| {
+                    XmlProcessorError::UnsupportedFormat(format!("not UTF-8 text ({e})"))
+                })?;
+                on_article(extract_article_metadata_with_options(
+                    &xml_content,
+                    xml_path,
+                    options,
+                ));
+            }
+        }
+        buf.clear();
+    }
+
+    Ok(())
+}
+
+/// Byte offset (in the uncompressed XML) at which each
+/// `<PubmedArticle>`/`<article>` of a multi-article file starts, with its
+/// PMID when it has one, for random access with [`read_article_at`]
+pub fn index_articles(xml_path: &str) -> Result<Vec<(u64, Option<String>)>> {
+    match map_xml_file(xml_path)? {
+        Some(mmap) => index_from_reader(Reader::from_reader(&mmap[..])),
+        None => index_from_reader(Reader::from_reader(open_xml_stream(xml_path)?)),
+    }
+}
+
+/// The scan of [`index_articles`] over any reader
+fn index_from_reader<R: BufRead>(mut reader: Reader<R>) -> Result<Vec<(u64, Option<String>)>> {
+    let mut buf = Vec::new();
+    let mut index: Vec<(u64, Option<String>)> = Vec::new();
+    let mut depth = 0usize;
+    // Text of the article's first <PMID> (PubMed) or pmid <article-id> (JATS)
+    let mut in_pmid = false;
+    let mut pmid_text = String::new();
+
+    loop {
+        match reader.read_event_into(&mut buf)? {
+            Event::Eof => break,
+            Event::Start(e) => {
+                if depth == 0 && is_article_element(e.local_name().as_ref()) {
+                    let offset = reader.buffer_position() - e.len() as u64 - 2;
+                    index.push((offset, None));
+                    depth = 1;
+                } else if depth > 0 {
+                    depth += 1;
+                    let is_pmid = match e.local_name().as_ref() {
+                        b"PMID" => true,
+                        b"article-id" => {
+                            attribute_value(&e, b"pub-id-type").as_deref() == Some("pmid")
+                        }
+                        _ => false,
+                    };
+                    if is_pmid && index.last().is_some_and(|(_, pmid)| pmid.is_none()) {
+                        in_pmid = true;
+                        pmid_text.clear();
+                    }
+                }
+            }
+            Event::Text(e) if in_pmid => {
+                pmid_text.push_str(&String::from_utf8_lossy(&e));
+            }
+            Event::End(_) if depth > 0 => {
+                depth -= 1;
+                if in_pmid {
+                    in_pmid = false;
+                    if let Some((_, pmid)) = index.last_mut() {
+                        *pmid = normalize_pmid(&pmid_text);
+                    }
+                }
+            }
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(index)
+}
+
+/// Write the [`index_articles`] index of `xml_path` to `index_path` as a
+/// tab-separated file with an `offset`/`pmid` header (the PMID left empty
+/// when there is none), and return the number of articles indexed
+pub fn build_article_index(xml_path: &str, index_path: &str) -> Result<usize> {
+    let index = index_articles(xml_path)?;
+
+    let mut output = std::io::BufWriter::new(File::create(index_path)?);
+    writeln!(output, "offset\tpmid")?;
+    for (offset, pmid) in &index {
+        writeln!(output, "{offset}\t{}", pmid.as_deref().unwrap_or_default())?;
+    }
+    output.flush()?;
+
+    Ok(index.len())
+}
+
+/// Extract the one article starting at byte `offset` of a multi-article file,
+/// as recorded by [`index_articles`]. Uncompressed files are read from the
+/// offset on; gzipped ones have to be decompressed up to it.
+pub fn read_article_at(
+    xml_path: &str,
+    offset: u64,
+    options: &ExtractOptions,
+) -> Result<ArticleMetadata> {
+    match map_xml_file(xml_path)? {
+        Some(mmap) => {
+            let rest = usize::try_from(offset)
+                .ok()
+                .and_then(|offset| mmap.get(offset..))
+                .unwrap_or_default();
+            article_from_reader(Reader::from_reader(rest), xml_path, offset, options)
+        }
+        None => {
+            let mut stream = open_xml_stream(xml_path)?;
+            std::io::copy(&mut (&mut stream).take(offset), &mut std::io::sink())?;
+            article_from_reader(Reader::from_reader(stream), xml_path, offset, options)
+        }
+    }
+}
+
+/// The article element `reader` starts with, extracted
+fn article_from_reader<R: BufRead>(
+    mut reader: Reader<R>,
+    xml_path: &str,
+    offset: u64,
+    options: &ExtractOptions,
+) -> Result<ArticleMetadata> {
+    let mut buf = Vec::new();
+    let mut writer = quick_xml::Writer::new(Vec::new());
+    let mut depth = 0usize;
+
+    loop {
+        let event = reader.read_event_into(&mut buf)?;
+        match &event {
+            Event::Start(e) if depth == 0 && !is_article_element(e.local_name().as_ref()) => break,
+            Event::Start(_) => depth += 1,
+            Event::End(_) if depth > 0 => depth -= 1,
+            Event::Eof => break,
+            _ if depth == 0 => break,
+            _ => {}
+        }
+        writer.write_event(event)?;
+        if depth == 0 {
+            let xml_content = String::from_utf8(writer.into_inner()).map_err(|e| {
+                XmlProcessorError::UnsupportedFormat(format!("not UTF-8 text ({e})"))
+            })?;
+            return extract_article_metadata_with_options(&xml_content, xml_path, options);
+        }
+        buf.clear();
+    }
+
+    Err(XmlProcessorError::UnsupportedFormat(format!(
+        "no <PubmedArticle> or <article> starts at byte {offset}"
+    )))
+}
+
+/// Largest archive member read into memory, whatever `max_bytes` allows; a
+/// member whose header claims more is reported as too large without reading it
+const MAX_ARCHIVE_MEMBER_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Stream a gzip-compressed tar archive (e.g. a PMC OA bundle), calling
+/// `on_article` with the archive-internal path and extraction result of every
+/// regular member whose name ends in `.xml` or `.nxml`. Nothing is extracted
+/// to disk and only one member is held in memory at a time. A member larger
+/// than `options.max_bytes` (or [`MAX_ARCHIVE_MEMBER_BYTES`]) is reported as
+/// [`XmlProcessorError::TooLarge`] and skipped unread. Opening the archive is
+/// retried as in [`read_and_extract`]; the stream can't be rewound, so an I/O
+/// error partway through ends the run.
+pub fn for_each_archive_article(
+    archive_path: &str,
+    options: &ExtractOptions,
+    mut on_article: impl FnMut(&str, Result<ArticleMetadata>),
+) -> Result<()> {
+    let mut attempt = 0;
+    let file = loop {
+        match File::open(archive_path) {
+            Err(e) if attempt < options.retries => {
+                attempt += 1;
+                log::info!(
+                    "Retrying {archive_path} ({attempt}/{}) after: {e}",
+                    options.retries
+                );
+                std::thread::sleep(std::time::Duration::from_millis(options.retry_delay_ms));
+            }
+            result => break result?,
+        }
+    };
+    let mut file = BufReader::new(file);
+    if !file.fill_buf()?.starts_with(&[0x1f, 0x8b]) {
+        return Err(XmlProcessorError::UnsupportedFormat(
+            "not a gzip-compressed tar archive".to_string(),
+        ));
+    }
+    let mut archive = tar::Archive::new(MultiGzDecoder::new(file));
+    let limit = options
+        .max_bytes
+        .map_or(MAX_ARCHIVE_MEMBER_BYTES, |max_bytes| {
+            max_bytes.min(MAX_ARCHIVE_MEMBER_BYTES)
+        });
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let name = entry.path()?.to_string_lossy().into_owned();
+        if !(name.ends_with(".xml") || name.ends_with(".nxml")) {
+            continue;
+        }
+
+        // The size comes from the header, so check it before allocating
+        let size = entry.size();
+        if size > limit {
+            on_article(&name, Err(XmlProcessorError::TooLarge { size, limit }));
+            continue;
+        }
+        let mut data = Vec::with_capacity(size as usize);
+        (&mut entry).take(limit).read_to_end(&mut data)?;
+
+        let metadata = String::from_utf8(data)
+            .map_err(|e| XmlProcessorError::UnsupportedFormat(format!("not UTF-8 text ({e})")))
+            .and_then(|xml_content| {
+                let xml_content = repair_if_lenient(xml_content, &name, options.lenient_xml);
+                extract_article_metadata_with_options(&xml_content, &name, options)
+            });
+        on_article(&name, metadata);
+    }
+
+    Ok(())
+}
+
+/// Build a `YYYY[-MM[-DD]]` date from its parts, converting month names to
+/// numbers; `None` without a year
+fn format_date(year: &str, month: &str, day: &str) -> Option<String> {
+    let (year, month, day) = (year.trim(), month.trim(), day.trim());
+    if year.is_empty() {
+        return None;
+    }
+
+    let mut date_parts = vec![year.to_string()];
+    if !month.is_empty() {
+        // Convert month name to number if needed
+        let month_num = match month.to_lowercase().as_str() {
+            "january" | "jan" => "01",
+            "february" | "feb" => "02",
+            "march" | "mar" => "03",
+            "april" | "apr" => "04",
+            "may" => "05",
+            "june" | "jun" => "06",
+            "july" | "jul" => "07",
+            "august" | "aug" => "08",
+            "september" | "sep" => "09",
+            "october" | "oct" => "10",
+            "november" | "nov" => "11",
+            "december" | "dec" => "12",
+            _ => month, // Assume it's already a number
+        };
+        date_parts.push(zero_pad(month_num));
+
+        if !day.is_empty() {
+            date_parts.push(zero_pad(day));
+        }
+    }
+
+    Some(date_parts.join("-"))
+}
+
+/// A one-digit date part padded to two digits (`3` -> `03`); anything else as is
+fn zero_pad(part: &str) -> String {
+    if part.len() == 1 && part.as_bytes()[0].is_ascii_digit() {
+        format!("0{part}")
+    } else {
+        part.to_string()
+    }
+}
+
+/// Local name of the document's root element, without a namespace prefix;
+/// `None` if it has none (empty, or only a declaration and comments)
+fn root_element(xml_content: &str) -> Result<Option<Vec<u8>>> {
+    let mut reader = Reader::from_str(xml_content);
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) | Event::Empty(e) => return Ok(Some(e.local_name().as_ref().to_vec())),
+            Event::Eof => return Ok(None),
+            _ => {}
+        }
+    }
+}
+
+/// Whether a root element is native PubMed XML (`<PubmedArticleSet>`,
+/// `<PubmedArticle>` or `<MedlineCitation>`) rather than JATS
+fn is_pubmed_root(root: &[u8]) -> bool {
+    matches!(
+        root,
+        b"PubmedArticleSet" | b"PubmedArticle" | b"MedlineCitation"
+    )
+}
+
+/// Extract key metadata and text from article XML content, either PMC/JATS or
+/// native PubMed (MEDLINE) XML, detected from the root element
+pub fn extract_article_metadata(xml_content: &str, file_path: &str) -> Result<ArticleMetadata> {
+    extract_article_metadata_with_options(xml_content, file_path, &ExtractOptions::default())
+}
+
+/// [`extract_article_metadata`] with non-default [`ExtractOptions`]. The XML is
+/// taken as is; `lenient_xml` only applies when reading files.
+pub fn extract_article_metadata_with_options(
+    xml_content: &str,
+    file_path: &str,
+    options: &ExtractOptions,
+) -> Result<ArticleMetadata> {
+    let root = root_element(xml_content)?.ok_or(XmlProcessorError::EmptyDocument)?;
+    let mut metadata = if is_pubmed_root(&root) {
+        extract_pubmed_metadata(xml_content, file_path)?
+    } else {
+        extract_jats_metadata(xml_content, file_path, options)?
+    };
+
+    if options.normalize_whitespace {
+        for text in [
+            &mut metadata.title,
+            &mut metadata.abstract_text,
+            &mut metadata.full_text,
+        ]
+        .into_iter()
+        .flatten()
+        .chain(
+            metadata
+                .abstracts_by_language
+                .iter_mut()
+                .map(|(_, text)| text),
+        ) {
+            *text = normalize_whitespace(text);
+        }
+    }
+    if options.lowercase {
+        for text in [
+            &mut metadata.title,
+            &mut metadata.abstract_text,
+            &mut metadata.full_text,
+            &mut metadata.journal,
+        ]
+        .into_iter()
+        .flatten()
+        .chain(
+            metadata
+                .abstracts_by_language
+                .iter_mut()
+                .map(|(_, text)| text),
+        ) {
+            *text = text.to_lowercase();
+        }
+    }
+
+    Ok(metadata)
+}
+
+/// `text` in Unicode subscript (or superscript) characters, e.g. `2` -> `₂`
+/// (`²`); `None` unless every character has such a form
+fn to_unicode_script(text: &str, subscript: bool) -> Option<String> {
+    const PLAIN: &str = "0123456789+-\u{2212}=()";
+    const SUPERSCRIPT: &[char] = &[
+        '⁰', '¹', '²', '³', '⁴', '⁵', '⁶', '⁷', '⁸', '⁹', '⁺', '⁻', '⁻', '⁼', '⁽', '⁾',
+    ];
+    const SUBSCRIPT: &[char] = &[
+        '₀', '₁', '₂', '₃', '₄', '₅', '₆', '₇', '₈', '₉', '₊', '₋', '₋', '₌', '₍', '₎',
+    ];
+    let script = if subscript { SUBSCRIPT } else { SUPERSCRIPT };
+    if text.is_empty() {
+        return None;
+    }
+    text.chars()
+        .map(|c| PLAIN.chars().position(|p| p == c).map(|i| script[i]))
+        .collect()
+}
+
+/// Collapse each run of Unicode whitespace (spaces, tabs, newlines, ...) to a
+/// single space and trim both ends
+pub fn normalize_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Abbreviations (lowercased, without their last period) whose period doesn't
+/// end a sentence even before a capitalized word ("Fig. 2", "et al. (2019)",
+/// "e.g. PCR")
+const NON_TERMINAL_ABBREVIATIONS: &[&str] = &[
+    "al", "approx", "ca", "cf", "dr", "e.g", "eq", "eqs", "fig", "figs", "i.e", "no", "nos",
+    "prof", "ref", "refs", "resp", "sp", "spp", "suppl", "tab", "var", "viz", "vol", "vs",
+];
+
+/// Split text into sentences, after whitespace normalization. A sentence ends
+/// at `.`, `!` or `?` followed by a space and an uppercase letter, digit,
+/// bracket or quote, except after one of `NON_TERMINAL_ABBREVIATIONS` or a
+/// single-letter initial; decimal points never qualify since no space follows.
+pub fn split_sentences(text: &str) -> Vec<String> {
+    let text = normalize_whitespace(text);
+    let mut sentences = Vec::new();
+    let mut start = 0;
+
+    for (i, c) in text.char_indices() {
+        if !matches!(c, '.' | '!' | '?') {
+            continue;
+        }
+        let end = i + c.len_utf8();
+        let next = text[end..]
+            .strip_prefix(' ')
+            .and_then(|rest| rest.chars().next());
+        let Some(next) = next else {
+            continue;
+        };
+        if !(next.is_uppercase() || next.is_ascii_digit() || "([\"'\u{201c}".contains(next)) {
+            continue;
+        }
+        if c == '.' {
+            let word = text[start..i].rsplit(' ').next().unwrap_or_default();
+            let word = word.trim_start_matches(['(', '[']).to_lowercase();
+            let is_initial = word.chars().count() == 1 && word.chars().all(char::is_alphabetic);
+            if is_initial || NON_TERMINAL_ABBREVIATIONS.contains(&word.as_str()) {
+                continue;
+            }
+        }
+        sentences.push(text[start..end].trim().to_string());
+        start = end;
+    }
+
+    let rest = text[start..].trim();
+    if !rest.is_empty() {
+        sentences.push(rest.to_string());
+    }
+    sentences
+}
+
+/// `text` with `open` and `close` inserted around each of the byte `ranges`,
+/// which must be sorted, non-overlapping and on char boundaries
+pub fn highlight_ranges(text: &str, ranges: &[(usize, usize)], open: &str, close: &str) -> String {
+    let mut highlighted =
+        String::with_capacity(text.len() + ranges.len() * (open.len() + close.len()));
+    let mut copied = 0;
+    for &(start, end) in ranges {
+        highlighted.push_str(&text[copied..start]);
+        highlighted.push_str(open);
+        highlighted.push_str(&text[start..end]);
+        highlighted.push_str(close);
+        copied = end;
+    }
+    highlighted.push_str(&text[copied..]);
+    highlighted
+}
+
+/// Add the words of `text` to `counts`: split on Unicode word boundaries
+/// (UAX #29, so punctuation is dropped and "IL-6" gives "il" and "6"),
+/// lowercased, leaving out words of fewer than `min_length` characters
+pub fn count_words(text: &str, min_length: usize, counts: &mut HashMap<String, u64>) {
+    for word in text.unicode_words() {
+        if word.chars().count() >= min_length {
+            *counts.entry(word.to_lowercase()).or_default() += 1;
+        }
+    }
+}
+
+/// Levenshtein distance between `a` and `b`, counted in characters
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, a_char) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, &b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+/// Approximate matches of the literal `pattern` in `text`, as (start, end,
+/// distance) byte ranges: runs of as many words as the pattern has (split as
+/// in [`count_words`]), joined by single spaces, within `max_distance` edits
+/// of it. Matches don't overlap; the scan resumes after each one.
+pub fn fuzzy_matches(
+    text: &str,
+    pattern: &str,
+    max_distance: usize,
+    case_sensitive: bool,
+) -> Vec<(usize, usize, usize)> {
+    let fold = |s: &str| {
+        if case_sensitive {
+            s.to_string()
+        } else {
+            s.to_lowercase()
+        }
+    };
+    let pattern = fold(&pattern.unicode_words().collect::<Vec<_>>().join(" "));
+    let pattern_words = pattern.unicode_words().count();
+    let pattern_chars = pattern.chars().count();
+    if pattern_words == 0 {
+        return Vec::new();
+    }
+
+    let words: Vec<(usize, &str)> = text.unicode_word_indices().collect();
+    let mut matches = Vec::new();
+    let mut i = 0;
+    while i + pattern_words <= words.len() {
+        let window = &words[i..i + pattern_words];
+        let candidate = fold(
+            &window
+                .iter()
+                .map(|&(_, word)| word)
+                .collect::<Vec<_>>()
+                .join(" "),
+        );
+        // Cheap rejection before the quadratic distance
+        if candidate.chars().count().abs_diff(pattern_chars) <= max_distance {
+            let distance = edit_distance(&candidate, &pattern);
+            if distance <= max_distance {
+                let (last_start, last_word) = window[pattern_words - 1];
+                matches.push((window[0].0, last_start + last_word.len(), distance));
+                i += pattern_words;
+                continue;
+            }
+        }
+        i += 1;
+    }
+    matches
+}
+
+/// Controlled vocabulary compiled into one whole-word, case-insensitive
+/// alternation, counting the occurrences of each (category, term) in a text
+pub struct TermTagger {
+    regex: Regex,
+    /// (category, term) in vocabulary order
+    entries: Vec<(String, String)>,
+    /// Indices into `entries` by lowercased term; a term may be listed under
+    /// several categories
+    entries_by_term: HashMap<String, Vec<usize>>,
+}
+
+impl TermTagger {
+    /// Compile `vocabulary`, (category, terms) pairs; blank terms and repeats
+    /// within a category (ignoring case) are ignored. Longer terms are tried
+    /// first, so "breast cancer" wins over "cancer".
+    pub fn new(vocabulary: &[(String, Vec<String>)]) -> std::result::Result<Self, regex::Error> {
+        let mut entries: Vec<(String, String)> = Vec::new();
+        let mut entries_by_term: HashMap<String, Vec<usize>> = HashMap::new();
+        for (category, terms) in vocabulary {
+            for term in terms
+                .iter()
+                .map(|term| term.trim())
+                .filter(|t| !t.is_empty())
+            {
+                let indices = entries_by_term.entry(term.to_lowercase()).or_default();
+                if indices.iter().any(|&i| entries[i].0 == *category) {
+                    continue;
+                }
+                indices.push(entries.len());
+                entries.push((category.clone(), term.to_string()));
+            }
+        }
+
+        let mut alternatives: Vec<&String> = entries_by_term.keys().collect();
+        alternatives.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        let alternation = alternatives
+            .iter()
+            .map(|term| regex::escape(term))
+            .collect::<Vec<_>>()
+            .join("|");
+        // An empty alternation would match everywhere
+        let pattern = if alternation.is_empty() {
+            r"[^\s\S]".to_string()
+        } else {
+            format!(r"(?i)\b(?:{alternation})\b")
+        };
+
+        Ok(Self {
+            regex: Regex::new(&pattern)?,
+            entries,
+            entries_by_term,
+        })
+    }
+
+    /// (category, term, count) of every term found in `text`, in vocabulary
+    /// order
+    pub fn tag<'a>(&'a self, text: &str) -> Vec<(&'a str, &'a str, u32)> {
+        let mut counts = vec![0u32; self.entries.len()];
+        for mat in self.regex.find_iter(text) {
+            if let Some(indices) = self.entries_by_term.get(&mat.as_str().to_lowercase()) {
+                for &i in indices {
+                    counts[i] += 1;
+                }
+            }
+        }
+        self.entries
+            .iter()
+            .zip(counts)
+            .filter(|&(_, count)| count > 0)
+            .map(|((category, term), count)| (category.as_str(), term.as_str(), count))
+            .collect()
+    }
+}
+
+/// The `top_n` most frequent words of `counts` (all of them with `None`),
+/// most frequent first and ties in alphabetical order
+pub fn top_words(counts: HashMap<String, u64>, top_n: Option<usize>) -> Vec<(String, u64)> {
+    let mut words: Vec<(String, u64)> = counts.into_iter().collect();
+    words.sort_unstable_by(|(a_word, a_count), (b_word, b_count)| {
+        b_count.cmp(a_count).then_with(|| a_word.cmp(b_word))
+    });
+    if let Some(top_n) = top_n {
+        words.truncate(top_n);
+    }
+    words
+}
+
+/// Extract metadata from a native PubMed record into the same fields the JATS
+/// parser fills: PMID, ArticleTitle, AbstractText, AuthorList, Journal, PubDate,
+/// KeywordList, MeSH headings and article ids
+fn extract_pubmed_metadata(xml_content: &str, file_path: &str) -> Result<ArticleMetadata> {
+    let mut reader = text_reader(xml_content);
+
+    let mut metadata = ArticleMetadata {
+        schema_version: SCHEMA_VERSION,
+        file_path: file_path.to_string(),
+        ..Default::default()
+    };
+
+    let mut buf = Vec::new();
+    // Names of the open elements, to tell e.g. <Journal><Title> from other titles
+    let mut open_elements: Vec<Vec<u8>> = Vec::new();
+    let mut current_text = String::new();
+    // Attribute of interest on the element being read (AbstractText Label,
+    // Identifier Source, ArticleId IdType, ELocationID EIdType)
+    let mut current_attr: Option<String> = None;
+
+    let mut abstract_sections: Vec<(String, String)> = Vec::new();
+    let mut last_name = String::new();
+    let mut fore_name = String::new();
+    let mut collective_name = String::new();
+    let mut author_orcid: Option<String> = None;
+    let mut author_affiliations: Vec<String> = Vec::new();
+    let (mut year, mut month, mut day) = (String::new(), String::new(), String::new());
+    let mut medline_date = String::new();
+    let mut pub_status: Option<String> = None;
+    let mut current_descriptor = String::new();
+    let mut current_qualifiers: Vec<String> = Vec::new();
+
+    loop {
+        match next_event(&mut reader, &mut buf) {
+            Ok(Event::Start(ref e)) => {
+                let name = e.local_name().as_ref().to_vec();
+                match name.as_slice() {
+                    b"PMID" | b"ArticleTitle" | b"LastName" | b"ForeName" | b"Affiliation"
+                    | b"Title" | b"Year" | b"Month" | b"Day" | b"MedlineDate" | b"Keyword"
+                    | b"DescriptorName" | b"QualifierName" | b"Volume" | b"Issue"
+                    | b"MedlinePgn" | b"StartPage" | b"EndPage" | b"PublicationType"
+                    | b"MedlineTA" | b"ISOAbbreviation" | b"CollectiveName" | b"Initials" => {
+                        current_text.clear();
+                    }
+                    b"AbstractText" => {
+                        current_text.clear();
+                        current_attr = attribute_value(e, b"Label");
+                    }
+                    b"Identifier" => {
+                        current_text.clear();
+                        current_attr = attribute_value(e, b"Source");
+                    }
+                    b"ArticleId" => {
+                        current_text.clear();
+                        current_attr = attribute_value(e, b"IdType");
+                    }
+                    b"ELocationID" => {
+                        current_text.clear();
+                        current_attr = attribute_value(e, b"EIdType");
+                    }
+                    b"ISSN" => {
+                        current_text.clear();
+                        current_attr = attribute_value(e, b"IssnType");
+                    }
+                    b"Author" => {
+                        last_name.clear();
+                        fore_name.clear();
+                        collective_name.clear();
+                        author_orcid = None;
+                        author_affiliations.clear();
+                    }
+                    b"PubDate" => {
+                        year.clear();
+                        month.clear();
+                        day.clear();
+                        medline_date.clear();
+                    }
+                    b"PubMedPubDate" => {
+                        year.clear();
+                        month.clear();
+                        day.clear();
+                        pub_status = attribute_value(e, b"PubStatus");
+                    }
+                    b"MeshHeading" => {
+                        current_descriptor.clear();
+                        current_qualifiers.clear();
+                    }
+                    _ => {}
+                }
+                open_elements.push(name);
+            }
+            Ok(Event::Text(e)) => {
+                current_text.push_str(std::str::from_utf8(e.as_ref()).unwrap_or_default());
+            }
+            Ok(Event::End(ref e)) => {
+                open_elements.pop();
+                let parent = open_elements.last().map(Vec::as_slice).unwrap_or_default();
+                // Only <PubmedData><ArticleIdList> describes this record;
+                // <ReferenceList><Reference> carries ids of the cited works
+                let own_article_id = parent == b"ArticleIdList"
+                    && open_elements.len() >= 2
+                    && open_elements[open_elements.len() - 2] == b"PubmedData";
+                let text = current_text.trim().to_string();
+
+                match e.local_name().as_ref() {
+                    b"PMID" => {
+                        // The record's own PMID comes first; later ones are in
+                        // comments/corrections
+                        if parent == b"MedlineCitation" && metadata.pmid.is_none() {
+                            metadata.pmid = normalize_pmid(&text);
+                        }
+                    }
+                    b"ArticleTitle" => {
+                        if metadata.title.is_none() && !text.is_empty() {
+                            metadata.title = Some(text);
+                        }
+                    }
+                    b"AbstractText" => {
+                        if parent == b"Abstract" && !text.is_empty() {
+                            abstract_sections.push((current_attr.take().unwrap_or_default(), text));
+                        }
+                    }
+                    b"Abstract" => {
+                        if !abstract_sections.is_empty() {
+                            let texts: Vec<&str> = abstract_sections
+                                .iter()
+                                .map(|(_, text)| text.as_str())
+                                .collect();
+                            metadata.abstract_text = Some(texts.join(" "));
+                            metadata.abstract_sections = std::mem::take(&mut abstract_sections);
+                        }
+                    }
+                    b"LastName" => last_name = text,
+                    b"ForeName" => fore_name = text,
+                    b"CollectiveName" => collective_name = text,
+                    b"Affiliation" => {
+                        if !text.is_empty() {
+                            author_affiliations.push(text);
+                        }
+                    }
+                    b"Identifier" => {
+                        if current_attr.take().as_deref() == Some("ORCID") {
+                            author_orcid = normalize_orcid(&text);
+                        }
+                    }
+                    b"Author" => {
+                        let is_collab = last_name.is_empty()
+                            && fore_name.is_empty()
+                            && !collective_name.is_empty();
+                        if parent == b"AuthorList"
+                            && (!last_name.is_empty() || !fore_name.is_empty() || is_collab)
+                        {
+                            let author_name = match (last_name.is_empty(), fore_name.is_empty()) {
+                                _ if is_collab => collective_name.clone(),
+                                (false, false) => format!("{last_name}, {fore_name}"),
+                                (false, true) => last_name.clone(),
+                                _ => fore_name.clone(),
+                            };
+                            metadata.authors.push(author_name);
+                            metadata.author_is_collab.push(is_collab);
+                            metadata
+                                .author_surnames
+                                .push((!last_name.is_empty()).then(|| last_name.clone()));
+                            metadata
+                                .author_given_names
+                                .push((!fore_name.is_empty()).then(|| fore_name.clone()));
+                            metadata.orcids.push(author_orcid.take());
+                            metadata.author_roles.push(Vec::new());
+                            metadata
+                                .affiliations
+                                .push(std::mem::take(&mut author_affiliations));
+                        }
+                    }
+                    b"Title" => {
+                        if parent == b"Journal" && !text.is_empty() {
+                            metadata.journal = Some(text);
+                        }
+                    }
+                    b"ISSN" => match current_attr.take().as_deref() {
+                        Some("Print") if !text.is_empty() => metadata.issn_print = Some(text),
+                        Some("Electronic") if !text.is_empty() => {
+                            metadata.issn_electronic = Some(text)
+                        }
+                        _ => {}
+                    },
+                    // MedlineTA is the NLM abbreviation; ISOAbbreviation only
+                    // fills in when it is missing
+                    b"MedlineTA" => {
+                        if !text.is_empty() {
+                            metadata.journal_abbrev = Some(text);
+                        }
+                    }
+                    b"ISOAbbreviation" => {
+                        if !text.is_empty() {
+                            metadata.journal_abbrev.get_or_insert(text);
+                        }
+                    }
+                    b"Year" if parent == b"PubDate" || parent == b"PubMedPubDate" => year = text,
+                    b"Month" if parent == b"PubDate" || parent == b"PubMedPubDate" => month = text,
+                    b"Day" if parent == b"PubDate" || parent == b"PubMedPubDate" => day = text,
+                    b"MedlineDate" => medline_date = text,
+                    b"PubDate" => {
+                        if year.is_empty() {
+                            // MedlineDate is free text such as "1998 Dec-1999 Jan"
+                            year = medline_date.chars().take(4).collect();
+                        }
+                        if metadata.publication_date.is_none() {
+                            metadata.publication_date = format_date(&year, &month, &day);
+                        }
+                    }
+                    b"PubMedPubDate" => {
+                        // <PubmedData><History>; "epublish" is the online date
+                        let field = match pub_status.take().as_deref() {
+                            Some("received") => Some(&mut metadata.date_received),
+                            Some("accepted") => Some(&mut metadata.date_accepted),
+                            Some("epublish") => Some(&mut metadata.date_published),
+                            _ => None,
+                        };
+                        if let Some(field) = field.filter(|field| field.is_none()) {
+                            *field = format_date(&year, &month, &day);
+                        }
+                    }
+                    b"Keyword" => {
+                        if !text.is_empty() {
+                            metadata.keywords.push(text);
+                        }
+                    }
+                    b"DescriptorName" => current_descriptor = text,
+                    b"QualifierName" => {
+                        if !text.is_empty() {
+                            current_qualifiers.push(text);
+                        }
+                    }
+                    b"MeshHeading" => {
+                        // "Descriptor/Qualifier/..." as shown on PubMed
+                        if !current_descriptor.is_empty() {
+                            let mut term = current_descriptor.clone();
+                            for qualifier in &current_qualifiers {
+                                term.push('/');
+                                term.push_str(qualifier);
+                            }
+                            metadata.mesh_terms.push(term);
+                        }
+                    }
+                    b"ArticleId" if !own_article_id => {
+                        current_attr = None;
+                    }
+                    b"ArticleId" => match current_attr.take().as_deref() {
+                        Some("pubmed") if metadata.pmid.is_none() => {
+                            metadata.pmid = normalize_pmid(&text)
+                        }
+                        Some("pmc") => {
+                            if let Some(pmc_id) = normalize_pmc_id(&text) {
+                                metadata.pmc_id = Some(pmc_id);
+                            }
+                        }
+                        Some("doi") => {
+                            if let Some(doi) = normalize_doi(&text) {
+                                metadata.doi = Some(doi);
+                                metadata.doi_source = Some("article-id".to_string());
+                            }
+                        }
+                        _ => {}
+                    },
+                    b"ELocationID" => {
+                        if current_attr.take().as_deref() == Some("doi") {
+                            if metadata.doi.is_none() {
+                                if let Some(doi) = normalize_doi(&text) {
+                                    metadata.doi = Some(doi);
+                                    metadata.doi_source = Some("elocation-id".to_string());
+                                }
+                            }
+                        } else if metadata.elocation_id.is_none() && !text.is_empty() {
+                            metadata.elocation_id = Some(text);
+                        }
+                    }
+                    b"Volume" | b"Issue" => {
+                        if parent == b"JournalIssue" && !text.is_empty() {
+                            if e.local_name().as_ref() == b"Volume" {
+                                metadata.volume = Some(text);
+                            } else {
+                                metadata.issue = Some(text);
+                            }
+                        }
+                    }
+                    b"PublicationType" => {
+                        if !text.is_empty() && !metadata.publication_types.contains(&text) {
+                            metadata.article_type.get_or_insert_with(|| text.clone());
+                            metadata.publication_types.push(text);
+                        }
+                    }
+                    b"StartPage" => {
+                        if !text.is_empty() {
+                            metadata.first_page = Some(text);
+                        }
+                    }
+                    b"EndPage" => {
+                        if !text.is_empty() {
+                            metadata.last_page = Some(text);
+                        }
+                    }
+                    b"MedlinePgn" => {
+                        // e.g. "123-45", where the end page drops the digits it
+                        // shares with the start page (123-145)
+                        if metadata.first_page.is_none() && !text.is_empty() {
+                            let (first, last) = match text.split_once('-') {
+                                Some((first, last)) => (first.trim(), Some(last.trim())),
+                                None => (text.as_str(), None),
+                            };
+                            metadata.first_page = Some(first.to_string());
+                            metadata.last_page = last.filter(|l| !l.is_empty()).map(|last| {
+                                let numeric = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+                                if numeric(first) && numeric(last) && last.len() < first.len() {
+                                    format!("{}{last}", &first[..first.len() - last.len()])
+                                } else {
+                                    last.to_string()
+                                }
+                            });
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(e.into()),
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    // PubMed gives no language per abstract
+    metadata.abstracts_by_language = metadata
+        .abstract_text
+        .iter()
+        .map(|text| (String::new(), text.clone()))
+        .collect();
+    metadata.is_empty = metadata.title.is_none() && metadata.abstract_text.is_none();
+    Ok(metadata)
+}
+
+/// Extract key metadata and text from PMC (JATS) XML content
+fn extract_jats_metadata(
+    xml_content: &str,
+    file_path: &str,
+    options: &ExtractOptions,
+) -> Result<ArticleMetadata> {
+    let mut reader = text_reader(xml_content);
+
+    let mut metadata = ArticleMetadata {
+        schema_version: SCHEMA_VERSION,
+        file_path: file_path.to_string(),
+        ..Default::default()
+    };
+
+    let mut buf = Vec::new();
+    let mut current_text = String::new();
+    let mut in_title = false;
+    // Subtitle and translated titles of the first front-matter <title-group>
+    // (the journal's <trans-title-group> is outside it)
+    let mut in_title_group = false;
+    let mut title_group_done = false;
+    let mut in_subtitle = false;
+    let mut trans_title_group_lang: Option<String> = None;
+    let mut trans_title_lang: Option<String> = None;
+    let mut title_group_text = String::new();
+    let mut in_abstract = false;
+    // Inside <sup>/<sub>, for `unicode_superscripts`
+    let mut in_sup = false;
+    let mut in_sub = false;
+    // Byte offset just past the open <abstract> tag, for `keep_raw_abstract`
+    let mut abstract_start = 0usize;
+    // `xml:lang` of the abstract being read, and of the one in `abstract_text`
+    let mut abstract_lang = String::new();
+    let mut primary_abstract_lang: Option<String> = None;
+    let mut in_graphical_abstract = false;
+    let mut in_graphical_caption = false;
+    let mut graphical_caption_parts: Vec<String> = Vec::new();
+    let mut in_contrib = false;
+    // An editor's <contrib> is read like an author's but its name goes to
+    // `editors` and nothing else of it is kept
+    let mut in_editor_contrib = false;
+    let mut in_surname = false;
+    let mut in_given_names = false;
+    // <collab>: a group author named as a whole instead of surname/given-names
+    let mut in_collab = false;
+    let mut current_collab = String::new();
+    let mut in_journal = false;
+    // Other <journal-meta> fields: the field the open element fills, its text,
+    // and the two sources of journal_abbrev in order of preference
+    let mut journal_meta_field: Option<&str> = None;
+    let mut journal_meta_text = String::new();
+    let mut journal_nlm_ta: Option<String> = None;
+    let mut journal_abbrev_title: Option<String> = None;
+    let mut in_body = false;
+    // <p> can nest (e.g. inside <list-item>); nested text belongs to the outer one
+    let mut paragraph_depth = 0usize;
+    let mut paragraph_parts: Vec<String> = Vec::new();
+    // Open <xref>s in the body, whose text `strip_xref` leaves out
+    let mut xref_depth = 0usize;
+
+    // Body sections: (sec_type, title, text parts) in document order, plus a
+    // stack of indices of the currently open <sec> elements
+    let mut body_sections: Vec<(String, String, Vec<String>)> = Vec::new();
+    let mut open_body_sections: Vec<usize> = Vec::new();
+    // With `sections_filter`, the enclosing section of each body section and
+    // of each full-text part, to select the parts once titles are known
+    let mut body_section_parents: Vec<Option<usize>> = Vec::new();
+    let mut full_text_part_sections: Vec<Option<usize>> = Vec::new();
+    let mut in_body_sec_title = false;
+    let mut in_pmid = false;
+    let mut in_pmc_id = false;
+    // Version suffix of a versioned PMC id, used without an <article-version>
+    let mut pmc_id_version: Option<String> = None;
+    let mut in_article_version = false;
+    let mut in_doi = false;
+    // DOIs outside <article-id>, used when the article has none there
+    let mut in_elocation_id = false;
+    let mut doi_link_in: Option<&str> = None;
+    let mut current_doi_link = String::new();
+    let mut fallback_dois: Vec<(&str, String)> = Vec::new();
+    // Volume, issue and pages; <fpage> etc. also occur in reference citations,
+    // so only <article-meta> is read
+    let mut in_article_meta = false;
+    let mut in_citation_field = false;
+    let mut current_citation_field = String::new();
+    let mut in_pub_date = false;
+    // A <history><date> is read with the <pub-date> state; its date-type
+    // (received, accepted, ...) says which field it fills
+    let mut in_history = false;
+    let mut history_date_type: Option<String> = None;
+    let mut pub_date_type: Option<String> = None;
+    let mut epub_date: Option<String> = None;
+    let mut in_year = false;
+    let mut in_month = false;
+    let mut in_day = false;
+    let mut in_custom_meta = false;
+    let mut in_meta_name = false;
+    let mut in_meta_value = false;
+    let mut in_license_ref = false;
+    let mut in_kwd_group = false;
+    let mut in_kwd = false;
+    let mut full_text_parts = Vec::new();
+    // Any text in <body>, even when it isn't collected (`skip_body_text`)
+    let mut has_body_text = false;
+
+    // For structured abstracts (<abstract><sec><title>Background</title><p>...)
+    let mut abstract_sec_depth = 0usize;
+    let mut in_abstract_title = false;
+    let mut abstract_sections: Vec<(String, String)> = Vec::new();
+    let mut current_section_label = String::new();
+    let mut current_section_parts: Vec<String> = Vec::new();
+
+    // MeSH headings come from PubMed-style records, which have no <front>, so
+    // this state is independent of in_front_matter
+    let mut in_mesh_heading_list = false;
+    let mut in_mesh_heading = false;
+    let mut in_descriptor = false;
+    let mut in_qualifier = false;
+    let mut current_descriptor = String::new();
+    let mut current_qualifier = String::new();
+    let mut current_qualifiers: Vec<String> = Vec::new();
+
+    // Competing-interest statements are nested <fn>/<sec>/<notes>, usually in
+    // back matter, so track element depth rather than a single flag
+    let mut coi_depth = 0usize;
+    let mut coi_parts = Vec::new();
+
+    // Data-availability blocks are tracked the same way; the block's own
+    // <title> ("Data Availability Statement") is left out
+    let mut data_availability_depth = 0usize;
+    let mut in_data_availability_title = false;
+    let mut data_availability_parts = Vec::new();
+
+    // And so are the <ack> blocks of the back matter
+    let mut ack_depth = 0usize;
+    let mut in_ack_title = false;
+    let mut ack_parts = Vec::new();
+
+    // And the author-contributions statement
+    let mut contributions_depth = 0usize;
+    let mut in_contributions_title = false;
+    let mut contributions_parts = Vec::new();
+
+    // For author extraction
+    let mut current_surname = String::new();
+    let mut current_given_names = String::new();
+    let mut in_orcid = false;
+    let mut current_orcid = String::new();
+    // <role> of the current contrib, with its vocab-term attribute for roles
+    // given only as an attribute
+    let mut in_role = false;
+    let mut current_role = String::new();
+    let mut current_role_term: Option<String> = None;
+    let mut current_roles: Vec<String> = Vec::new();
+
+    // Affiliations are linked to authors by <xref ref-type="aff" rid="...">;
+    // the <aff> blocks may come after the contribs, so ids are resolved once
+    // the whole document has been read
+    let mut in_aff = false;
+    let mut in_aff_label = false;
+    let mut current_aff_id: Option<String> = None;
+    let mut current_aff_parts: Vec<String> = Vec::new();
+    let mut affiliations_by_id: HashMap<String, String> = HashMap::new();
+
+    // For funding extraction; an <award-group> may name several funders and
+    // award ids, which are paired up when the group closes
+    let mut in_award_group = false;
+    let mut in_funding_source = false;
+    let mut in_institution_id = false;
+    let mut in_award_id = false;
+    let mut current_funder = String::new();
+    let mut current_funder_id: Option<String> = None;
+    let mut current_funders: Vec<(String, Option<String>)> = Vec::new();
+    let mut current_award_id = String::new();
+    let mut current_award_ids: Vec<String> = Vec::new();
+    let mut current_aff_rids: Vec<String> = Vec::new();
+    let mut current_inline_affs: Vec<String> = Vec::new();
+    let mut author_aff_rids: Vec<(Vec<String>, Vec<String>)> = Vec::new();
+
+    // Corresponding author: the first contrib marked corresp="yes" or linked
+    // to a <corresp> note, with the note rids and any <email> of its own. The
+    // notes' emails are looked up by id once the document has been read
+    let mut current_is_corresp = false;
+    let mut current_corresp_rids: Vec<String> = Vec::new();
+    let mut current_contrib_email: Option<String> = None;
+    let mut corresponding: Option<(usize, String, Vec<String>, Option<String>)> = None;
+    let mut in_author_notes = false;
+    let mut current_corresp_id: Option<String> = None;
+    let mut in_email = false;
+    let mut current_email = String::new();
+    let mut corresp_emails_by_id: HashMap<String, String> = HashMap::new();
+    let mut first_author_notes_email: Option<String> = None;
+
+    // For keyword extraction (a <kwd> may hold inline markup such as <italic>)
+    let mut current_kwd = String::new();
+
+    // For publication date extraction
+    let mut current_year = String::new();
+    let mut current_month = String::new();
+    let mut current_day = String::new();
+
+    // For open-access detection via <custom-meta> and <ali:license_ref>
+    let mut current_meta_name = String::new();
+    let mut current_meta_value = String::new();
+    let mut current_license_ref = String::new();
+
+    // License and copyright from the front-matter <permissions>; only its
+    // first <license> is read
+    let mut in_permissions = false;
+    let mut in_license = false;
+    let mut license_seen = false;
+    let mut license_link: Option<String> = None;
+    let mut in_license_p = false;
+    let mut license_text_parts: Vec<String> = Vec::new();
+    let mut in_copyright_statement = false;
+    let mut in_copyright_year = false;
+    let mut in_copyright_holder = false;
+    let mut copyright_statement = String::new();
+    let mut copyright_year = String::new();
+    let mut copyright_holder = String::new();
+
+    // Track document structure to avoid extracting from references/supplementary
+    let mut in_front_matter = false;
+    let mut title_extracted = false; // Only extract the first title
+
+    loop {
+        match next_event(&mut reader, &mut buf) {
+            Ok(Event::Start(ref e)) => {
+                if coi_depth > 0 {
+                    coi_depth += 1;
+                }
+                if data_availability_depth > 0 {
+                    data_availability_depth += 1;
+                    if data_availability_depth == 2 && e.local_name().as_ref() == b"title" {
+                        in_data_availability_title = true;
+                    }
+                }
+                if ack_depth > 0 {
+                    ack_depth += 1;
+                    if ack_depth == 2 && e.local_name().as_ref() == b"title" {
+                        in_ack_title = true;
+                    }
+                } else if e.local_name().as_ref() == b"ack" {
+                    ack_depth = 1;
+                }
+                if contributions_depth > 0 {
+                    contributions_depth += 1;
+                    if contributions_depth == 2 && e.local_name().as_ref() == b"title" {
+                        in_contributions_title = true;
+                    }
+                }
+
+                match e.local_name().as_ref() {
+                    b"article" => {
+                        // The root element, so this comes before anything else
+                        if metadata.article_type.is_none() {
+                            metadata.article_type = attribute_value(e, b"article-type")
+                                .filter(|article_type| !article_type.is_empty());
+                        }
+                        if metadata.language.is_none() {
+                            metadata.language = attribute_value(e, b"xml:lang")
+                                .map(|lang| lang.trim().to_string())
+                                .filter(|lang| !lang.is_empty());
+                        }
+                    }
+                    b"fn" | b"sec" | b"notes" => {
+                        if coi_depth == 0 && is_coi_element(e) {
+                            coi_depth = 1;
+                        }
+                        if data_availability_depth == 0 && is_data_availability_element(e) {
+                            data_availability_depth = 1;
+                        }
+                        if contributions_depth == 0 && is_contributions_element(e) {
+                            contributions_depth = 1;
+                        }
+
+                        if in_body && e.local_name().as_ref() == b"sec" {
+                            let sec_type = attribute_value(e, b"sec-type").unwrap_or_default();
+                            body_section_parents.push(open_body_sections.last().copied());
+                            open_body_sections.push(body_sections.len());
+                            body_sections.push((sec_type, String::new(), Vec::new()));
+                        }
+
+                        if in_abstract && e.local_name().as_ref() == b"sec" {
+                            abstract_sec_depth += 1;
+                            // Nested subsections are attributed to the top-level section
+                            if abstract_sec_depth == 1 {
+                                let unsectioned = current_section_parts.join(" ");
+                                if !unsectioned.trim().is_empty() {
+                                    abstract_sections
+                                        .push((String::new(), unsectioned.trim().to_string()));
+                                }
+                                current_section_label.clear();
+                                current_section_parts.clear();
+                            }
+                        }
+                    }
+                    b"title" => {
+                        if in_abstract {
+                            in_abstract_title = true;
+                        }
+
+                        // A section's own title comes before any of its text
+                        if let Some(&idx) = open_body_sections.last() {
+                            let (_, title, parts) = &body_sections[idx];
+                            if in_body && title.is_empty() && parts.is_empty() {
+                                in_body_sec_title = true;
+                            }
+                        }
+                    }
+                    b"front" => {
+                        in_front_matter = true;
+                    }
+                    b"back" => {
+                        in_front_matter = false;
+                    }
+                    b"article-title" => {
+                        // Only extract title if we're in front matter and haven't extracted one yet
+                        if in_front_matter && !title_extracted {
+                            in_title = true;
+                            current_text.clear();
+                        }
+                    }
+                    b"title-group" => {
+                        in_title_group = in_front_matter && !title_group_done;
+                    }
+                    b"subtitle" => {
+                        if in_title_group && metadata.subtitle.is_none() {
+                            in_subtitle = true;
+                            title_group_text.clear();
+                        }
+                    }
+                    b"trans-title-group" => {
+                        if in_title_group {
+                            trans_title_group_lang = attribute_value(e, b"xml:lang");
+                        }
+                    }
+                    b"trans-title" => {
+                        if in_title_group {
+                            trans_title_lang = attribute_value(e, b"xml:lang")
+                                .or_else(|| trans_title_group_lang.clone())
+                                .or(Some(String::new()));
+                            title_group_text.clear();
+                        }
+                    }
+                    b"abstract" => {
+                        // Front matter first; an <abstract> elsewhere (in the
+                        // body of preprint conversions, or in files without a
+                        // <front>) is only used while none has been found
+                        if is_graphical_abstract(e) {
+                            in_graphical_abstract = true;
+                        } else if in_front_matter || metadata.abstract_text.is_none() {
+                            in_abstract = true;
+                            abstract_lang = attribute_value(e, b"xml:lang").unwrap_or_default();
+                            abstract_start = reader.buffer_position() as usize;
+                            current_text.clear();
+                            abstract_sections.clear();
+                            abstract_sec_depth = 0;
+                            current_section_label.clear();
+                            current_section_parts.clear();
+                        }
+                    }
+                    b"caption" => {
+                        if in_graphical_abstract {
+                            in_graphical_caption = true;
+                        }
+                    }
+                    b"graphic" => {
+                        if in_graphical_abstract && metadata.graphical_abstract_href.is_none() {
+                            metadata.graphical_abstract_href = attribute_value(e, b"xlink:href");
+                        }
+                    }
+                    b"contrib" => {
+                        if in_front_matter {
+                            // Check if this is an author contribution
+                            for attr in e.attributes().flatten() {
+                                if attr.key.as_ref() == b"contrib-type" {
+                                    let value = String::from_utf8_lossy(&attr.value);
+                                    if value == "author" || value == "editor" {
+                                        in_contrib = true;
+                                        in_editor_contrib = value == "editor";
+                                        current_surname.clear();
+                                        current_given_names.clear();
+                                        current_collab.clear();
+                                        current_orcid.clear();
+                                        current_roles.clear();
+                                        current_aff_rids.clear();
+                                        current_inline_affs.clear();
+                                        current_is_corresp = attribute_value(e, b"corresp")
+                                            .is_some_and(|v| v.eq_ignore_ascii_case("yes"));
+                                        current_corresp_rids.clear();
+                                        current_contrib_email = None;
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    b"surname" => {
+                        if in_contrib && in_front_matter {
+                            in_surname = true;
+                            current_surname.clear();
+                        }
+                    }
+                    b"given-names" => {
+                        if in_contrib && in_front_matter {
+                            in_given_names = true;
+                            current_given_names.clear();
+                        }
+                    }
+                    b"collab" => {
+                        in_collab = in_contrib && in_front_matter;
+                    }
+                    b"award-group" => {
+                        if in_front_matter {
+                            in_award_group = true;
+                            current_funders.clear();
+                            current_award_ids.clear();
+                        }
+                    }
+                    b"funding-source" => {
+                        if in_award_group {
+                            in_funding_source = true;
+                            current_funder.clear();
+                            current_funder_id = attribute_value(e, b"xlink:href");
+                        }
+                    }
+                    b"institution-id" => {
+                        in_institution_id = in_funding_source;
+                    }
+                    b"award-id" => {
+                        if in_award_group {
+                            in_award_id = true;
+                            current_award_id.clear();
+                        }
+                    }
+                    b"contrib-id" => {
+                        if in_contrib
+                            && in_front_matter
+                            && attribute_value(e, b"contrib-id-type").as_deref() == Some("orcid")
+                        {
+                            in_orcid = true;
+                            current_orcid.clear();
+                        }
+                    }
+                    b"role" => {
+                        if in_contrib && in_front_matter {
+                            in_role = true;
+                            current_role.clear();
+                            current_role_term = attribute_value(e, b"vocab-term");
+                        }
+                    }
+                    b"kwd-group" => {
+                        if in_front_matter {
+                            in_kwd_group = true;
+                        }
+                    }
+                    b"aff" => {
+                        if in_front_matter {
+                            in_aff = true;
+                            current_aff_id = attribute_value(e, b"id");
+                            current_aff_parts.clear();
+                        }
+                    }
+                    b"label" => {
+                        in_aff_label = in_aff;
+                    }
+                    b"author-notes" => {
+                        in_author_notes = in_front_matter;
+                    }
+                    b"corresp" => {
+                        if in_author_notes {
+                            current_corresp_id = attribute_value(e, b"id");
+                        }
+                    }
+                    b"email" => {
+                        if (in_contrib || in_author_notes) && in_front_matter {
+                            in_email = true;
+                            current_email.clear();
+                        }
+                    }
+                    b"xref" => {
+                        if in_contrib && in_front_matter {
+                            current_aff_rids.extend(aff_rids(e));
+                            current_corresp_rids.extend(corresp_rids(e));
+                        }
+                        if in_body {
+                            xref_depth += 1;
+                        }
+                    }
+                    b"sup" => {
+                        in_sup = true;
+                    }
+                    b"sub" => {
+                        in_sub = true;
+                    }
+                    b"MeshHeadingList" => {
+                        in_mesh_heading_list = true;
+                    }
+                    b"MeshHeading" => {
+                        if in_mesh_heading_list {
+                            in_mesh_heading = true;
+                            current_descriptor.clear();
+                            current_qualifiers.clear();
+                        }
+                    }
+                    b"DescriptorName" => {
+                        in_descriptor = in_mesh_heading;
+                    }
+                    b"QualifierName" => {
+                        if in_mesh_heading {
+                            in_qualifier = true;
+                            current_qualifier.clear();
+                        }
+                    }
+                    b"kwd" => {
+                        if in_kwd_group && in_front_matter {
+                            in_kwd = true;
+                            current_kwd.clear();
+                        }
+                    }
+                    b"journal-title" => {
+                        if in_front_matter {
+                            in_journal = true;
+                            current_text.clear();
+                        }
+                    }
+                    b"journal-id" | b"abbrev-journal-title" | b"issn" | b"publisher-name" => {
+                        if in_front_matter {
+                            journal_meta_field = journal_meta_field_of(e);
+                            journal_meta_text.clear();
+                        }
+                    }
+                    b"pub-date" => {
+                        if in_front_matter {
+                            in_pub_date = true;
+                            pub_date_type = attribute_value(e, b"pub-type")
+                                .or_else(|| attribute_value(e, b"date-type"));
+                            current_year.clear();
+                            current_month.clear();
+                            current_day.clear();
+                        }
+                    }
+                    b"history" => {
+                        in_history = in_front_matter;
+                    }
+                    b"date" => {
+                        if in_history {
+                            in_pub_date = true;
+                            history_date_type = attribute_value(e, b"date-type");
+                            current_year.clear();
+                            current_month.clear();
+                            current_day.clear();
+                        }
+                    }
+                    b"year" => {
+                        if in_pub_date && in_front_matter {
+                            in_year = true;
+                            current_year.clear();
+                        }
+                    }
+                    b"month" => {
+                        if in_pub_date && in_front_matter {
+                            in_month = true;
+                            current_month.clear();
+                        }
+                    }
+                    b"day" => {
+                        if in_pub_date && in_front_matter {
+                            in_day = true;
+                            current_day.clear();
+                        }
+                    }
+                    b"body" => {
+                        in_body = true;
+                    }
+                    b"p" => {
+                        if in_body {
+                            paragraph_depth += 1;
+                        }
+                    }
+                    b"open-access" => {
+                        metadata.is_open_access = Some(true);
+                    }
+                    b"related-object" => {
+                        metadata.trial_numbers.extend(trial_registration_number(e));
+                    }
+                    b"license" => {
+                        if let Some(open) = license_open_access(e) {
+                            metadata.is_open_access = Some(open);
+                        }
+                        if in_permissions && !license_seen {
+                            in_license = true;
+                            license_seen = true;
+                            metadata.license_type = attribute_value(e, b"license-type")
+                                .filter(|license_type| !license_type.is_empty());
+                            metadata.license_url = attribute_value(e, b"xlink:href")
+                                .filter(|href| !href.trim().is_empty());
+                        }
+                    }
+                    b"permissions" => {
+                        in_permissions = in_front_matter;
+                    }
+                    b"license-p" => {
+                        in_license_p = in_license;
+                    }
+                    b"copyright-statement" => {
+                        in_copyright_statement = in_permissions;
+                    }
+                    b"copyright-year" => {
+                        in_copyright_year = in_permissions;
+                    }
+                    b"copyright-holder" => {
+                        in_copyright_holder = in_permissions;
+                    }
+                    b"license_ref" => {
+                        in_license_ref = true;
+                        current_license_ref.clear();
+                    }
+                    b"custom-meta" => {
+                        in_custom_meta = true;
+                        current_meta_name.clear();
+                        current_meta_value.clear();
+                    }
+                    b"meta-name" => {
+                        in_meta_name = in_custom_meta;
+                    }
+                    b"meta-value" => {
+                        in_meta_value = in_custom_meta;
+                    }
+                    b"article-meta" => {
+                        in_article_meta = in_front_matter;
+                    }
+                    b"article-version" => {
+                        if in_article_meta {
+                            in_article_version = true;
+                            current_text.clear();
+                        }
+                    }
+                    b"volume" | b"issue" | b"fpage" | b"lpage" => {
+                        if in_article_meta {
+                            in_citation_field = true;
+                            current_citation_field.clear();
+                        }
+                    }
+                    b"elocation-id" => {
+                        if in_front_matter {
+                            in_elocation_id = true;
+                            current_doi_link.clear();
+                        }
+                    }
+                    b"self-uri" | b"ext-link" => {
+                        if in_license_p && license_link.is_none() {
+                            license_link = attribute_value(e, b"xlink:href");
+                        }
+                        if in_front_matter {
+                            if let Some(source) = doi_link_source(e) {
+                                match attribute_value(e, b"xlink:href")
+                                    .and_then(|h| normalize_doi(&h))
+                                {
+                                    Some(doi) => fallback_dois.push((source, doi)),
+                                    None => {
+                                        doi_link_in = Some(source);
+                                        current_doi_link.clear();
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    b"article-id" => {
+                        if in_front_matter {
+                            current_text.clear();
+                            for attr in e.attributes().flatten() {
+                                if attr.key.as_ref() == b"pub-id-type" {
+                                    let value = String::from_utf8_lossy(&attr.value);
+                                    match value.as_ref() {
+                                        "pmid" => in_pmid = true,
+                                        "pmc" | "pmcid-ver" | "pmc-uid" => in_pmc_id = true,
+                                        "doi" => in_doi = true,
+                                        _ => {}
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Ok(Event::Text(e)) => {
+                let text = std::str::from_utf8(e.as_ref()).unwrap_or_default();
+                let script_text = (options.unicode_superscripts
+                    && (in_sup || in_sub)
+                    && (in_abstract
+                        || ((in_title || in_subtitle || trans_title_lang.is_some())
+                            && in_front_matter)))
+                    .then(|| to_unicode_script(text, in_sub))
+                    .flatten();
+                let text = script_text.as_deref().unwrap_or(text);
+
+                // Handle author name components
+                if in_kwd && in_front_matter {
+                    current_kwd.push_str(text);
+                } else if in_subtitle || trans_title_lang.is_some() {
+                    if !title_group_text.is_empty() {
+                        title_group_text.push(' ');
+                    }
+                    title_group_text.push_str(text);
+                } else if in_email {
+                    current_email.push_str(text);
+                } else if in_surname && in_front_matter {
+                    current_surname.push_str(text);
+                } else if in_given_names && in_front_matter {
+                    current_given_names.push_str(text);
+                } else if in_collab {
+                    current_collab.push(' ');
+                    current_collab.push_str(text);
+                } else if in_orcid {
+                    current_orcid.push_str(text);
+                } else if in_role {
+                    current_role.push_str(text);
+                } else if in_institution_id {
+                    current_funder_id = Some(text.trim().to_string());
+                } else if in_funding_source {
+                    if !current_funder.is_empty() {
+                        current_funder.push(' ');
+                    }
+                    current_funder.push_str(text.trim());
+                } else if in_award_id {
+                    current_award_id.push_str(text);
+                } else if in_year && in_pub_date && in_front_matter {
+                    current_year.push_str(text);
+                } else if in_month && in_pub_date && in_front_matter {
+                    current_month.push_str(text);
+                } else if in_day && in_pub_date && in_front_matter {
+                    current_day.push_str(text);
+                } else if in_elocation_id || doi_link_in.is_some() {
+                    current_doi_link.push_str(text);
+                } else if in_citation_field {
+                    current_citation_field.push_str(text);
+                } else if in_abstract
+                    || ((in_title
+                        || in_journal
+                        || in_pmid
+                        || in_pmc_id
+                        || in_doi
+                        || in_article_version)
+                        && in_front_matter)
+                {
+                    current_text.push_str(text);
+                }
+
+                if journal_meta_field.is_some() {
+                    journal_meta_text.push_str(text);
+                }
+
+                if in_abstract {
+                    if in_abstract_title {
+                        // Only section titles become labels; the abstract's own
+                        // <title>Abstract</title> is not part of any section
+                        if abstract_sec_depth == 1 {
+                            current_section_label.push_str(text);
+                        }
+                    } else {
+                        current_section_parts.push(text.to_string());
+                    }
+                }
+
+                has_body_text |= in_body;
+                if in_body && !options.skip_body_text && !(options.strip_xref && xref_depth > 0) {
+                    full_text_parts.push(text.to_string());
+                    if options.sections_filter.is_some() {
+                        full_text_part_sections.push(open_body_sections.last().copied());
+                    }
+                    if paragraph_depth > 0 {
+                        paragraph_parts.push(text.to_string());
+                    }
+
+                    if let Some(&idx) = open_body_sections.last() {
+                        if in_body_sec_title {
+                            body_sections[idx].1.push_str(text);
+                        } else {
+                            body_sections[idx].2.push(text.to_string());
+                        }
+                    }
+                }
+
+                if coi_depth > 0 {
+                    coi_parts.push(text.to_string());
+                }
+
+                if in_graphical_caption {
+                    graphical_caption_parts.push(text.to_string());
+                }
+
+                if data_availability_depth > 0 && !in_data_availability_title {
+                    data_availability_parts.push(text.to_string());
+                }
+
+                if ack_depth > 0 && !in_ack_title {
+                    ack_parts.push(text.to_string());
+                }
+
+                if contributions_depth > 0 && !in_contributions_title {
+                    contributions_parts.push(text.to_string());
+                }
+
+                if in_aff && !in_aff_label {
+                    current_aff_parts.push(text.to_string());
+                }
+
+                if in_descriptor {
+                    current_descriptor.push_str(text);
+                } else if in_qualifier {
+                    current_qualifier.push_str(text);
+                }
+
+                if in_license_p {
+                    license_text_parts.push(text.to_string());
+                }
+
+                if in_copyright_statement {
+                    copyright_statement.push_str(text);
+                } else if in_copyright_year {
+                    copyright_year.push_str(text);
+                } else if in_copyright_holder {
+                    copyright_holder.push_str(text);
+                }
+
+                if in_meta_name {
+                    current_meta_name.push_str(text);
+                } else if in_meta_value {
+                    current_meta_value.push_str(text);
+                } else if in_license_ref {
+                    current_license_ref.push_str(text);
+                }
+            }
+            Ok(Event::Empty(ref e)) => match e.local_name().as_ref() {
+                b"xref" => {
+                    if in_contrib && in_front_matter {
+                        current_aff_rids.extend(aff_rids(e));
+                        current_corresp_rids.extend(corresp_rids(e));
+                    }
+                }
+                b"role" => {
+                    if in_contrib && in_front_matter {
+                        if let Some(term) = attribute_value(e, b"vocab-term") {
+                            current_roles.push(term);
+                        }
+                    }
+                }
+                b"graphic" => {
+                    if in_graphical_abstract && metadata.graphical_abstract_href.is_none() {
+                        metadata.graphical_abstract_href = attribute_value(e, b"xlink:href");
+                    }
+                }
+                b"self-uri" | b"ext-link" => {
+                    if in_license_p && license_link.is_none() {
+                        license_link = attribute_value(e, b"xlink:href");
+                    }
+                    if in_front_matter {
+                        if let Some(source) = doi_link_source(e) {
+                            if let Some(doi) =
+                                attribute_value(e, b"xlink:href").and_then(|h| normalize_doi(&h))
+                            {
+                                fallback_dois.push((source, doi));
+                            }
+                        }
+                    }
+                }
+                b"open-access" => {
+                    metadata.is_open_access = Some(true);
+                }
+                b"related-object" => {
+                    metadata.trial_numbers.extend(trial_registration_number(e));
+                }
+                b"license" => {
+                    if let Some(open) = license_open_access(e) {
+                        metadata.is_open_access = Some(open);
+                    }
+                    if in_permissions && !license_seen {
+                        license_seen = true;
+                        metadata.license_type = attribute_value(e, b"license-type")
+                            .filter(|license_type| !license_type.is_empty());
+                        metadata.license_url = attribute_value(e, b"xlink:href")
+                            .filter(|href| !href.trim().is_empty());
+                    }
+                }
+                _ => {}
+            },
+            Ok(Event::End(ref e)) => {
+                if coi_depth > 0 {
+                    coi_depth -= 1;
+                    if coi_depth == 0 {
+                        let statement = coi_parts.join(" ");
+                        let statement = statement.trim();
+                        if !statement.is_empty() && metadata.conflict_of_interest.is_none() {
+                            metadata.conflict_of_interest = Some(statement.to_string());
+                        }
+                        coi_parts.clear();
+                    }
+                }
+                if data_availability_depth > 0 {
+                    data_availability_depth -= 1;
+                    if data_availability_depth == 1 && e.local_name().as_ref() == b"title" {
+                        in_data_availability_title = false;
+                    }
+                }
+                if ack_depth > 0 {
+                    ack_depth -= 1;
+                    if ack_depth == 1 && e.local_name().as_ref() == b"title" {
+                        in_ack_title = false;
+                    }
+                }
+                if contributions_depth > 0 {
+                    contributions_depth -= 1;
+                    if contributions_depth == 1 && e.local_name().as_ref() == b"title" {
+                        in_contributions_title = false;
+                    }
+                }
+
+                match e.local_name().as_ref() {
+                    b"front" => {
+                        in_front_matter = false;
+                    }
+                    b"article-title" => {
+                        if in_title && in_front_matter && !title_extracted {
+                            let trimmed = current_text.trim();
+                            if !trimmed.is_empty() {
+                                metadata.title = Some(trimmed.to_string());
+                                title_extracted = true;
+                            }
+                            current_text.clear();
+                            in_title = false;
+                        }
+                    }
+                    b"subtitle" => {
+                        if in_subtitle {
+                            let subtitle = title_group_text.trim();
+                            if !subtitle.is_empty() {
+                                metadata.subtitle = Some(subtitle.to_string());
+                            }
+                            in_subtitle = false;
+                        }
+                    }
+                    b"trans-title" => {
+                        if let Some(lang) = trans_title_lang.take() {
+                            let title = title_group_text.trim();
+                            if !title.is_empty() {
+                                metadata.trans_titles.push((lang, title.to_string()));
+                            }
+                        }
+                    }
+                    b"trans-title-group" => {
+                        trans_title_group_lang = None;
+                    }
+                    b"title-group" => {
+                        if in_title_group {
+                            in_title_group = false;
+                            title_group_done = true;
+                        }
+                    }
+                    b"caption" => {
+                        in_graphical_caption = false;
+                    }
+                    b"abstract" => {
+                        in_graphical_abstract = false;
+                        if in_abstract {
+                            let is_english = |lang: &str| {
+                                let lang = lang.to_ascii_lowercase();
+                                lang == "en" || lang.starts_with("en-")
+                            };
+                            // The first abstract, unless it has another
+                            // language and this one is in English
+                            let is_primary = primary_abstract_lang.as_deref().is_none_or(|lang| {
+                                !lang.is_empty() && !is_english(lang) && is_english(&abstract_lang)
+                            });
+                            let trimmed = current_text.trim();
+                            if !trimmed.is_empty() {
+                                metadata
+                                    .abstracts_by_language
+                                    .push((abstract_lang.clone(), trimmed.to_string()));
+                                if is_primary {
+                                    metadata.abstract_text = Some(trimmed.to_string());
+                                    primary_abstract_lang = Some(abstract_lang.clone());
+                                }
+                            }
+                            current_text.clear();
+
+                            if options.keep_raw_abstract && is_primary {
+                                // The reader is just past </abstract>
+                                let end = reader.buffer_position() as usize;
+                                let end_tag = xml_content[..end].rfind("</").unwrap_or(end);
+                                let inner = xml_content[abstract_start..end_tag].trim();
+                                metadata.abstract_xml =
+                                    (!inner.is_empty()).then(|| inner.to_string());
+                            }
+
+                            // Unstructured abstract, or trailing text after the last <sec>
+                            let remaining = current_section_parts.join(" ");
+                            if !remaining.trim().is_empty() {
+                                abstract_sections
+                                    .push((String::new(), remaining.trim().to_string()));
+                            }
+                            current_section_parts.clear();
+                            if is_primary {
+                                metadata.abstract_sections = std::mem::take(&mut abstract_sections);
+                            }
+                            in_abstract = false;
+                        }
+                    }
+                    b"sec" => {
+                        if in_body {
+                            open_body_sections.pop();
+                        }
+
+                        if in_abstract && abstract_sec_depth > 0 {
+                            abstract_sec_depth -= 1;
+                            if abstract_sec_depth == 0 {
+                                let text = current_section_parts.join(" ");
+                                let label = current_section_label.trim();
+                                if !label.is_empty() || !text.trim().is_empty() {
+                                    abstract_sections
+                                        .push((label.to_string(), text.trim().to_string()));
+                                }
+                                current_section_label.clear();
+                                current_section_parts.clear();
+                            }
+                        }
+                    }
+                    b"title" => {
+                        in_abstract_title = false;
+                        in_body_sec_title = false;
+                    }
+                    b"contrib" => {
+                        if in_contrib && in_front_matter {
+                            // Construct author name from surname and given names
+                            let surname = current_surname.trim();
+                            let given_names = current_given_names.trim();
+                            let collab = normalize_whitespace(&current_collab);
+                            let is_collab =
+                                surname.is_empty() && given_names.is_empty() && !collab.is_empty();
+
+                            if !surname.is_empty() || !given_names.is_empty() || is_collab {
+                                let author_name = if is_collab {
+                                    collab
+                                } else if !surname.is_empty() && !given_names.is_empty() {
+                                    format!("{surname}, {given_names}")
+                                } else if !surname.is_empty() {
+                                    surname.to_string()
+                                } else {
+                                    given_names.to_string()
+                                };
+
+                                if in_editor_contrib {
+                                    metadata.editors.push(author_name);
+                                } else {
+                                    metadata.authors.push(author_name.clone());
+                                    metadata.author_is_collab.push(is_collab);
+                                    metadata
+                                        .author_surnames
+                                        .push((!surname.is_empty()).then(|| surname.to_string()));
+                                    metadata.author_given_names.push(
+                                        (!given_names.is_empty()).then(|| given_names.to_string()),
+                                    );
+                                    metadata.orcids.push(normalize_orcid(&current_orcid));
+                                    metadata
+                                        .author_roles
+                                        .push(std::mem::take(&mut current_roles));
+                                    if corresponding.is_none()
+                                        && (current_is_corresp || !current_corresp_rids.is_empty())
+                                    {
+                                        corresponding = Some((
+                                            metadata.authors.len() - 1,
+                                            author_name,
+                                            std::mem::take(&mut current_corresp_rids),
+                                            current_contrib_email.take(),
+                                        ));
+                                    }
+                                    author_aff_rids.push((
+                                        std::mem::take(&mut current_aff_rids),
+                                        std::mem::take(&mut current_inline_affs),
+                                    ));
+                                }
+                            }
+
+                            in_contrib = false;
+                            in_editor_contrib = false;
+                            current_surname.clear();
+                            current_given_names.clear();
+                        }
+                    }
+                    b"surname" => {
+                        in_surname = false;
+                    }
+                    b"given-names" => {
+                        in_given_names = false;
+                    }
+                    b"collab" => {
+                        in_collab = false;
+                    }
+                    b"role" => {
+                        if in_role {
+                            let role = current_role.trim();
+                            let role = match current_role_term.take() {
+                                Some(term) if role.is_empty() => term,
+                                _ => role.to_string(),
+                            };
+                            if !role.is_empty() {
+                                current_roles.push(role);
+                            }
+                            in_role = false;
+                        }
+                    }
+                    b"contrib-id" => {
+                        in_orcid = false;
+                    }
+                    b"institution-id" => {
+                        in_institution_id = false;
+                    }
+                    b"funding-source" => {
+                        if in_funding_source {
+                            let funder = current_funder.trim();
+                            if !funder.is_empty() {
+                                current_funders
+                                    .push((funder.to_string(), current_funder_id.take()));
+                            }
+                            in_funding_source = false;
+                        }
+                    }
+                    b"award-id" => {
+                        if in_award_id {
+                            let award_id = current_award_id.trim();
+                            if !award_id.is_empty() {
+                                current_award_ids.push(award_id.to_string());
+                            }
+                            in_award_id = false;
+                        }
+                    }
+                    b"award-group" => {
+                        if in_award_group {
+                            for (funder, funder_id) in current_funders.drain(..) {
+                                if current_award_ids.is_empty() {
+                                    metadata.funding.push(Award {
+                                        funder,
+                                        funder_id,
+                                        award_id: None,
+                                    });
+                                    continue;
+                                }
+                                for award_id in &current_award_ids {
+                                    metadata.funding.push(Award {
+                                        funder: funder.clone(),
+                                        funder_id: funder_id.clone(),
+                                        award_id: Some(award_id.clone()),
+                                    });
+                                }
+                            }
+                            in_award_group = false;
+                        }
+                    }
+                    b"kwd" => {
+                        if in_kwd {
+                            let keyword = current_kwd.trim();
+                            if !keyword.is_empty()
+                                && !metadata.keywords.iter().any(|k| k == keyword)
+                            {
+                                metadata.keywords.push(keyword.to_string());
+                            }
+                            current_kwd.clear();
+                            in_kwd = false;
+                        }
+                    }
+                    b"kwd-group" => {
+                        in_kwd_group = false;
+                    }
+                    b"label" => {
+                        in_aff_label = false;
+                    }
+                    b"email" => {
+                        if in_email {
+                            let email = current_email.trim();
+                            if !email.is_empty() {
+                                if in_contrib {
+                                    current_contrib_email.get_or_insert(email.to_string());
+                                } else {
+                                    if let Some(id) = &current_corresp_id {
+                                        corresp_emails_by_id
+                                            .entry(id.clone())
+                                            .or_insert(email.to_string());
+                                    }
+                                    first_author_notes_email.get_or_insert(email.to_string());
+                                }
+                            }
+                            in_email = false;
+                        }
+                    }
+                    b"corresp" => {
+                        current_corresp_id = None;
+                    }
+                    b"author-notes" => {
+                        in_author_notes = false;
+                    }
+                    b"xref" => {
+                        xref_depth = xref_depth.saturating_sub(1);
+                    }
+                    b"sup" => {
+                        in_sup = false;
+                    }
+                    b"sub" => {
+                        in_sub = false;
+                    }
+                    b"aff" => {
+                        if in_aff {
+                            let text = current_aff_parts.join(" ").replace(" ,", ",");
+                            let text = text.trim();
+                            if !text.is_empty() {
+                                if in_contrib {
+                                    // <aff> written inside the <contrib> itself
+                                    current_inline_affs.push(text.to_string());
+                                } else if let Some(id) = current_aff_id.take() {
+                                    affiliations_by_id.insert(id, text.to_string());
+                                }
+                            }
+                            current_aff_parts.clear();
+                            in_aff = false;
+                        }
+                    }
+                    b"DescriptorName" => {
+                        in_descriptor = false;
+                    }
+                    b"QualifierName" => {
+                        if in_qualifier {
+                            let qualifier = current_qualifier.trim();
+                            if !qualifier.is_empty() {
+                                current_qualifiers.push(qualifier.to_string());
+                            }
+                            in_qualifier = false;
+                        }
+                    }
+                    b"MeshHeading" => {
+                        if in_mesh_heading {
+                            // "Descriptor/Qualifier/..." as shown on PubMed
+                            let descriptor = current_descriptor.trim();
+                            if !descriptor.is_empty() {
+                                let mut term = descriptor.to_string();
+                                for qualifier in &current_qualifiers {
+                                    term.push('/');
+                                    term.push_str(qualifier);
+                                }
+                                metadata.mesh_terms.push(term);
+                            }
+                            in_mesh_heading = false;
+                        }
+                    }
+                    b"MeshHeadingList" => {
+                        in_mesh_heading_list = false;
+                    }
+                    b"journal-title" => {
+                        if in_journal && in_front_matter {
+                            let trimmed = current_text.trim();
+                            if !trimmed.is_empty() {
+                                metadata.journal = Some(trimmed.to_string());
+                            }
+                            current_text.clear();
+                            in_journal = false;
+                        }
+                    }
+                    b"journal-id" | b"abbrev-journal-title" | b"issn" | b"publisher-name" => {
+                        if let Some(field) = journal_meta_field.take() {
+                            let value = journal_meta_text.trim();
+                            let target = match field {
+                                "nlm_ta" => &mut journal_nlm_ta,
+                                "abbrev_title" => &mut journal_abbrev_title,
+                                "issn_print" => &mut metadata.issn_print,
+                                "issn_electronic" => &mut metadata.issn_electronic,
+                                _ => &mut metadata.publisher_name,
+                            };
+                            if target.is_none() && !value.is_empty() {
+                                *target = Some(value.to_string());
+                            }
+                        }
+                    }
+                    b"pub-date" => {
+                        if in_pub_date && in_front_matter {
+                            if let Some(date) =
+                                format_date(&current_year, &current_month, &current_day)
+                            {
+                                if pub_date_type.take().as_deref() == Some("epub") {
+                                    epub_date = Some(date.clone());
+                                }
+                                metadata.publication_date = Some(date);
+                            }
+
+                            in_pub_date = false;
+                            current_year.clear();
+                            current_month.clear();
+                            current_day.clear();
+                        }
+                    }
+                    b"history" => {
+                        in_history = false;
+                    }
+                    b"date" => {
+                        if in_history && in_pub_date {
+                            let field = match history_date_type.take().as_deref() {
+                                Some("received") => Some(&mut metadata.date_received),
+                                Some("accepted") => Some(&mut metadata.date_accepted),
+                                Some("pub" | "published" | "epub") => {
+                                    Some(&mut metadata.date_published)
+                                }
+                                _ => None,
+                            };
+                            if let Some(field) = field.filter(|field| field.is_none()) {
+                                *field = format_date(&current_year, &current_month, &current_day);
+                            }
+
+                            in_pub_date = false;
+                            current_year.clear();
+                            current_month.clear();
+                            current_day.clear();
+                        }
+                    }
+                    b"year" => {
+                        in_year = false;
+                    }
+                    b"month" => {
+                        in_month = false;
+                    }
+                    b"day" => {
+                        in_day = false;
+                    }
+                    b"article-version" if in_article_version => {
+                        let version = current_text.trim();
+                        // The first one of an <article-version-alternatives>
+                        if metadata.article_version.is_none() && !version.is_empty() {
+                            metadata.article_version = Some(version.to_string());
+                        }
+                        in_article_version = false;
+                        current_text.clear();
+                    }
+                    b"article-id" => {
+                        if in_front_matter {
+                            let text_content = current_text.trim();
+                            if in_pmid && !text_content.is_empty() {
+                                if let Some(pmid) = normalize_pmid(text_content) {
+                                    metadata.pmid = Some(pmid);
+                                }
+                                in_pmid = false;
+                            } else if in_pmc_id && !text_content.is_empty() {
+                                // `PMC12345.2`: version 2 of PMC12345
+                                let (id, version) = match text_content.split_once('.') {
+                                    Some((id, version)) => (id, Some(version)),
+                                    None => (text_content, None),
+                                };
+                                if let Some(pmc_id) = normalize_pmc_id(id) {
+                                    metadata.pmc_id = Some(pmc_id);
+                                    if let Some(version) =
+                                        version.filter(|v| v.bytes().all(|b| b.is_ascii_digit()))
+                                    {
+                                        pmc_id_version = Some(version.to_string());
+                                    }
+                                }
+                                in_pmc_id = false;
+                            } else if in_doi {
+                                if let Some(doi) = normalize_doi(text_content) {
+                                    metadata.doi = Some(doi);
+                                    metadata.doi_source = Some("article-id".to_string());
+                                }
+                                in_doi = false;
+                            }
+                            current_text.clear();
+                        }
+                    }
+                    b"elocation-id" => {
+                        if in_elocation_id {
+                            // Usually an article number such as "e1234"
+                            if let Some(doi) = normalize_doi(&current_doi_link) {
+                                fallback_dois.push(("elocation-id", doi));
+                            }
+                            let elocation_id = current_doi_link.trim();
+                            if in_article_meta
+                                && metadata.elocation_id.is_none()
+                                && !elocation_id.is_empty()
+                            {
+                                metadata.elocation_id = Some(elocation_id.to_string());
+                            }
+                            in_elocation_id = false;
+                        }
+                    }
+                    b"article-meta" => {
+                        in_article_meta = false;
+                    }
+                    b"volume" | b"issue" | b"fpage" | b"lpage" => {
+                        if in_citation_field {
+                            let value = current_citation_field.trim();
+                            // The first occurrence wins over e.g. a <related-article>
+                            let field = match e.local_name().as_ref() {
+                                b"volume" => &mut metadata.volume,
+                                b"issue" => &mut metadata.issue,
+                                b"fpage" => &mut metadata.first_page,
+                                _ => &mut metadata.last_page,
+                            };
+                            if field.is_none() && !value.is_empty() {
+                                *field = Some(value.to_string());
+                            }
+                            in_citation_field = false;
+                        }
+                    }
+                    b"self-uri" | b"ext-link" => {
+                        if let Some(source) = doi_link_in.take() {
+                            if let Some(doi) = normalize_doi(&current_doi_link) {
+                                fallback_dois.push((source, doi));
+                            }
+                        }
+                    }
+                    b"body" => {
+                        in_body = false;
+                    }
+                    b"p" => {
+                        if in_body && paragraph_depth > 0 {
+                            paragraph_depth -= 1;
+                            if paragraph_depth == 0 {
+                                let paragraph = paragraph_parts.join(" ");
+                                if !paragraph.trim().is_empty() {
+                                    metadata.body_paragraphs.push(paragraph.trim().to_string());
+                                }
+                                paragraph_parts.clear();
+                            }
+                        }
+                    }
+                    b"license" => {
+                        in_license = false;
+                    }
+                    b"license-p" => {
+                        in_license_p = false;
+                    }
+                    b"permissions" => {
+                        in_permissions = false;
+                    }
+                    b"copyright-statement" => {
+                        in_copyright_statement = false;
+                    }
+                    b"copyright-year" => {
+                        in_copyright_year = false;
+                    }
+                    b"copyright-holder" => {
+                        in_copyright_holder = false;
+                    }
+                    b"license_ref" => {
+                        if in_license && metadata.license_url.is_none() {
+                            let url = current_license_ref.trim();
+                            if !url.is_empty() {
+                                metadata.license_url = Some(url.to_string());
+                            }
+                        }
+                        if is_open_license("", current_license_ref.trim()) {
+                            metadata.is_open_access = Some(true);
+                        }
+                        in_license_ref = false;
+                    }
+                    b"meta-name" => {
+                        in_meta_name = false;
+                    }
+                    b"meta-value" => {
+                        in_meta_value = false;
+                    }
+                    b"custom-meta" => {
+                        // e.g. <meta-name>open-access</meta-name><meta-value>yes</meta-value>
+                        let name = current_meta_name.trim().to_lowercase();
+                        if name == "open-access" || name == "open access" || name == "oa" {
+                            match current_meta_value.trim().to_lowercase().as_str() {
+                                "yes" | "true" | "1" => metadata.is_open_access = Some(true),
+                                "no" | "false" | "0" => {
+                                    // An explicit license/OA element elsewhere wins
+                                    if metadata.is_open_access.is_none() {
+                                        metadata.is_open_access = Some(false);
+                                    }
+                                }
+                                _ => {}
+                            }
+                        }
+                        in_custom_meta = false;
+                    }
+                    _ => {}
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(e.into()),
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    if let Some(filter) = &options.sections_filter {
+        let wanted: HashSet<String> = filter.iter().map(|name| section_key(name)).collect();
+        // Parents come before their subsections, so one pass settles them all
+        let mut selected: Vec<bool> = Vec::with_capacity(body_sections.len());
+        for (idx, (sec_type, title, _)) in body_sections.iter().enumerate() {
+            let inherited = body_section_parents[idx].is_some_and(|parent| selected[parent]);
+            selected.push(
+                inherited
+                    || wanted.contains(&section_key(sec_type))
+                    || wanted.contains(&section_key(title)),
+            );
+        }
+        full_text_parts = full_text_parts
+            .into_iter()
+            .zip(full_text_part_sections)
+            .filter(|(_, section)| section.is_some_and(|idx| selected[idx]))
+            .map(|(part, _)| part)
+            .collect();
+    }
+
+    if !full_text_parts.is_empty() {
+        metadata.full_text = Some(full_text_parts.join(" "));
+    }
+
+    metadata.journal_abbrev = journal_nlm_ta.or(journal_abbrev_title);
+
+    if metadata.date_published.is_none() {
+        metadata.date_published = epub_date;
+    }
+
+    if metadata.doi.is_none() {
+        // <elocation-id> first, then links in document order
+        fallback_dois.sort_by_key(|(source, _)| *source != "elocation-id");
+        if let Some((source, doi)) = fallback_dois.into_iter().next() {
+            metadata.doi = Some(doi);
+            metadata.doi_source = Some(source.to_string());
+        }
+    }
+
+    if options.skip_body_text {
+        body_sections.clear();
+    }
+    metadata.full_text_sections = body_sections
+        .into_iter()
+        .map(|(sec_type, title, parts)| {
+            (
+                sec_type,
+                title.trim().to_string(),
+                parts.join(" ").trim().to_string(),
+            )
+        })
+        .collect();
+
+    // Second pass: resolve each author's aff rids now that every <aff> is known
+    metadata.affiliations = author_aff_rids
+        .into_iter()
+        .map(|(rids, mut affiliations)| {
+            for rid in rids {
+                if let Some(text) = affiliations_by_id.get(&rid) {
+                    if !affiliations.contains(text) {
+                        affiliations.push(text.clone());
+                    }
+                }
+            }
+            affiliations
+        })
+        .collect();
+
+    metadata.has_competing_interests =
+        classify_competing_interests(metadata.conflict_of_interest.as_deref());
+
+    let graphical_caption = graphical_caption_parts.join(" ");
+    if !graphical_caption.trim().is_empty() {
+        metadata.graphical_abstract_caption = Some(graphical_caption.trim().to_string());
+    }
+
+    #[cfg(feature = "langdetect")]
+    if metadata.language.is_none() {
+        let text = [&metadata.title, &metadata.abstract_text]
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(" ");
+        metadata.language = crate::langdetect::guess_language(&text).map(str::to_string);
+    }
+
+    let acknowledgements = ack_parts.join(" ");
+    if !acknowledgements.trim().is_empty() {
+        metadata.acknowledgements = Some(acknowledgements.trim().to_string());
+    }
+
+    let contributions_text = contributions_parts.join(" ");
+    if !contributions_text.trim().is_empty() {
+        metadata.contributions_text = Some(contributions_text.trim().to_string());
+    }
+
+    let data_availability = data_availability_parts.join(" ");
+    if !data_availability.trim().is_empty() {
+        metadata.data_availability = Some(data_availability.trim().to_string());
+    }
+
+    let mentioned_trials: Vec<String> = [&metadata.abstract_text, &metadata.full_text]
+        .into_iter()
+        .flatten()
+        .flat_map(|text| TRIAL_NUMBER_PATTERN.find_iter(text))
+        .map(|m| m.as_str().to_string())
+        .collect();
+    for trial_number in mentioned_trials {
+        if !metadata.trial_numbers.contains(&trial_number) {
+            metadata.trial_numbers.push(trial_number);
+        }
+    }
+
+    if metadata.license_url.is_none() {
+        metadata.license_url = license_link;
+    }
+    if metadata.license_type.is_none() {
+        metadata.license_type = metadata.license_url.as_deref().and_then(cc_license_type);
+    }
+    let license_text = license_text_parts.join(" ");
+    if !license_text.trim().is_empty() {
+        metadata.license_text = Some(license_text.trim().to_string());
+    }
+    metadata.copyright_statement = if !copyright_statement.trim().is_empty() {
+        Some(copyright_statement.trim().to_string())
+    } else if !copyright_year.trim().is_empty() || !copyright_holder.trim().is_empty() {
+        Some(
+            format!("© {} {}", copyright_year.trim(), copyright_holder.trim())
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" "),
+        )
+    } else {
+        None
+    };
+
+    // An explicitly linked email wins over the first one in <author-notes>
+    if let Some((index, name, rids, email)) = corresponding {
+        metadata.corresponding_author = Some(name);
+        metadata.corresponding_author_index = Some(index);
+        metadata.corresponding_email = email.or_else(|| {
+            rids.iter()
+                .find_map(|rid| corresp_emails_by_id.get(rid).cloned())
+        });
+    }
+    if metadata.corresponding_email.is_none() {
+        metadata.corresponding_email = first_author_notes_email;
+    }
+
+    if metadata.article_version.is_none() {
+        metadata.article_version = pmc_id_version;
+    }
+    metadata.is_empty =
+        metadata.title.is_none() && metadata.abstract_text.is_none() && !has_body_text;
+    Ok(metadata)
+}
+/// Extract the reference list (`<ref-list>`/`<ref>`) from PMC XML content
+pub fn extract_references(xml_content: &str) -> Result<Vec<Reference>> {
+    let mut reader = text_reader(xml_content);
+
+    let mut buf = Vec::new();
+    let mut references = Vec::new();
+    let mut current_ref: Option<Reference> = None;
+    let mut text_parts = Vec::new();
+    let mut current_text = String::new();
+    let mut in_label = false;
+    let mut in_doi = false;
+    let mut in_pmid = false;
+    let mut in_title = false;
+    let mut in_year = false;
+    let mut title_parts = Vec::new();
+
+    loop {
+        match next_event(&mut reader, &mut buf) {
+            Ok(Event::Start(ref e)) => match e.local_name().as_ref() {
+                b"ref" => {
+                    let mut reference = Reference::default();
+                    for attr in e.attributes().flatten() {
+                        if attr.key.as_ref() == b"id" {
+                            reference.id = Some(String::from_utf8_lossy(&attr.value).into_owned());
+                        }
+                    }
+                    current_ref = Some(reference);
+                    text_parts.clear();
+                }
+                b"article-title" => {
+                    if current_ref.is_some() {
+                        in_title = true;
+                        title_parts.clear();
+                    }
+                }
+                b"year" => {
+                    if current_ref.is_some() {
+                        in_year = true;
+                        current_text.clear();
+                    }
+                }
+                b"label" => {
+                    if current_ref.is_some() {
+                        in_label = true;
+                        current_text.clear();
+                    }
+                }
+                b"pub-id" => {
+                    if current_ref.is_some() {
+                        current_text.clear();
+                        for attr in e.attributes().flatten() {
+                            if attr.key.as_ref() == b"pub-id-type" {
+                                match attr.value.as_ref() {
+                                    b"doi" => in_doi = true,
+                                    b"pmid" => in_pmid = true,
+                                    _ => {}
+                                }
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            },
+            Ok(Event::Text(e)) => {
+                if current_ref.is_some() {
+                    let text = std::str::from_utf8(e.as_ref()).unwrap_or_default();
+                    if in_label {
+                        current_text.push_str(text);
+                    } else {
+                        if in_doi || in_pmid || in_year {
+                            current_text.push_str(text);
+                        }
+                        if in_title {
+                            title_parts.push(text.to_string());
+                        }
+                        text_parts.push(text.to_string());
+                    }
+                }
+            }
+            Ok(Event::End(ref e)) => match e.local_name().as_ref() {
+                b"ref" => {
+                    if let Some(mut reference) = current_ref.take() {
+                        let text = text_parts.join(" ");
+                        if !text.trim().is_empty() {
+                            reference.text = Some(text.trim().to_string());
+                        }
+                        references.push(reference);
+                    }
+                    text_parts.clear();
+                }
+                b"label" => {
+                    if in_label {
+                        if let Some(reference) = current_ref.as_mut() {
+                            let trimmed = current_text.trim();
+                            if !trimmed.is_empty() {
+                                reference.label = Some(trimmed.to_string());
+                            }
+                        }
+                        current_text.clear();
+                        in_label = false;
+                    }
+                }
+                b"article-title" => {
+                    if in_title {
+                        if let Some(reference) = current_ref.as_mut() {
+                            let title = title_parts.join(" ");
+                            if !title.trim().is_empty() {
+                                reference.title = Some(title.trim().to_string());
+                            }
+                        }
+                        title_parts.clear();
+                        in_title = false;
+                    }
+                }
+                b"year" => {
+                    if in_year {
+                        if let Some(reference) = current_ref.as_mut() {
+                            let trimmed = current_text.trim();
+                            if !trimmed.is_empty() {
+                                reference.year = Some(trimmed.to_string());
+                            }
+                        }
+                        current_text.clear();
+                        in_year = false;
+                    }
+                }
+                b"pub-id" => {
+                    if let Some(reference) = current_ref.as_mut() {
+                        let trimmed = current_text.trim();
+                        if !trimmed.is_empty() {
+                            if in_doi {
+                                reference.doi = Some(trimmed.to_string());
+                            } else if in_pmid {
+                                reference.pmid = Some(trimmed.to_string());
+                            }
+                        }
+                    }
+                    current_text.clear();
+                    in_doi = false;
+                    in_pmid = false;
+                }
+                _ => {}
+            },
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(e.into()),
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(references)
+}
+
+/// Extract every `<fig>` in the document (body and floats), in document order
+pub fn extract_figures(xml_content: &str) -> Result<Vec<Figure>> {
+    let mut reader = text_reader(xml_content);
+
+    let mut buf = Vec::new();
+    let mut figures = Vec::new();
+    let mut current_fig: Option<Figure> = None;
+    let mut caption_parts = Vec::new();
+    let mut current_label = String::new();
+    let mut in_caption = false;
+    let mut in_label = false;
+
+    loop {
+        match next_event(&mut reader, &mut buf) {
+            Ok(Event::Start(ref e)) => match e.local_name().as_ref() {
+                b"fig" => {
+                    current_fig = Some(Figure {
+                        id: attribute_value(e, b"id"),
+                        ..Default::default()
+                    });
+                    caption_parts.clear();
+                }
+                b"caption" => {
+                    in_caption = current_fig.is_some();
+                }
+                b"label" => {
+                    if current_fig.is_some() && !in_caption {
+                        in_label = true;
+                        current_label.clear();
+                    }
+                }
+                b"graphic" => {
+                    if let Some(figure) = current_fig.as_mut() {
+                        if figure.graphic_href.is_none() {
+                            figure.graphic_href = attribute_value(e, b"xlink:href");
+                        }
+                    }
+                }
+                _ => {}
+            },
+            Ok(Event::Empty(ref e)) => {
+                if e.local_name().as_ref() == b"graphic" {
+                    if let Some(figure) = current_fig.as_mut() {
+                        if figure.graphic_href.is_none() {
+                            figure.graphic_href = attribute_value(e, b"xlink:href");
+                        }
+                    }
+                }
+            }
+            Ok(Event::Text(e)) => {
+                let text = std::str::from_utf8(e.as_ref()).unwrap_or_default();
+                if in_label {
+                    current_label.push_str(text);
+                } else if in_caption {
+                    caption_parts.push(text.to_string());
+                }
+            }
+            Ok(Event::End(ref e)) => match e.local_name().as_ref() {
+                b"fig" => {
+                    if let Some(mut figure) = current_fig.take() {
+                        figure.caption = caption_parts.join(" ").trim().to_string();
+                        figures.push(figure);
+                    }
+                    caption_parts.clear();
+                    in_caption = false;
+                }
+                b"caption" => {
+                    in_caption = false;
+                }
+                b"label" => {
+                    if in_label {
+                        if let Some(figure) = current_fig.as_mut() {
+                            let trimmed = current_label.trim();
+                            if !trimmed.is_empty() {
+                                figure.label = Some(trimmed.to_string());
+                            }
+                        }
+                        in_label = false;
+                    }
+                }
+                _ => {}
+            },
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(e.into()),
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(figures)
+}
+
+/// The file an element with `xlink:href` and `mimetype`/`mime-subtype`
+/// attributes points to
+fn supplementary_file(e: &quick_xml::events::BytesStart) -> SupplementaryFile {
+    let mimetype = match (
+        attribute_value(e, b"mimetype"),
+        attribute_value(e, b"mime-subtype"),
+    ) {
+        (Some(mimetype), Some(subtype)) => Some(format!("{mimetype}/{subtype}")),
+        (mimetype, _) => mimetype,
+    };
+    SupplementaryFile {
+        label: None,
+        href: attribute_value(e, b"xlink:href"),
+        mimetype,
+    }
+}
+
+/// Extract the files of every `<supplementary-material>` in the document (body
+/// or back matter), in document order: one per `<media>` in a block, or the
+/// block's own `xlink:href` when it has no media
+pub fn extract_supplementary(xml_content: &str) -> Result<Vec<SupplementaryFile>> {
+    let mut reader = text_reader(xml_content);
+
+    let mut buf = Vec::new();
+    let mut files = Vec::new();
+    // The open block as a file of its own, and the media found in it
+    let mut current_block: Option<SupplementaryFile> = None;
+    let mut block_media: Vec<SupplementaryFile> = Vec::new();
+    let mut current_label = String::new();
+    let mut in_caption = false;
+    let mut in_label = false;
+
+    loop {
+        match next_event(&mut reader, &mut buf) {
+            Ok(Event::Start(ref e)) => match e.local_name().as_ref() {
+                b"supplementary-material" => {
+                    current_block = Some(supplementary_file(e));
+                    block_media.clear();
+                    current_label.clear();
+                }
+                b"media" if current_block.is_some() => {
+                    block_media.push(supplementary_file(e));
+                }
+                b"caption" => {
+                    in_caption = current_block.is_some();
+                }
+                b"label" => {
+                    in_label = current_block.is_some() && !in_caption;
+                }
+                _ => {}
+            },
+            Ok(Event::Empty(ref e)) => {
+                if e.local_name().as_ref() == b"media" && current_block.is_some() {
+                    block_media.push(supplementary_file(e));
+                }
+            }
+            Ok(Event::Text(e)) => {
+                if in_label {
+                    current_label.push_str(std::str::from_utf8(e.as_ref()).unwrap_or_default());
+                }
+            }
+            Ok(Event::End(ref e)) => match e.local_name().as_ref() {
+                b"supplementary-material" => {
+                    if let Some(block) = current_block.take() {
+                        let label = Some(current_label.trim().to_string())
+                            .filter(|label| !label.is_empty());
+                        if block_media.is_empty() {
+                            block_media.push(block);
+                        }
+                        for mut file in block_media.drain(..) {
+                            file.label = label.clone();
+                            files.push(file);
+                        }
+                    }
+                    in_caption = false;
+                }
+                b"caption" => {
+                    in_caption = false;
+                }
+                b"label" => {
+                    in_label = false;
+                }
+                _ => {}
+            },
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(e.into()),
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(files)
+}
+
+/// Collect the `<named-content>` elements of the document by `content-type`,
+/// one entry per distinct (type, text) pair in order of first appearance.
+/// Markup inside the element (italics, sub/superscripts, nested
+/// `<named-content>`) only contributes its text; elements without a
+/// `content-type` are left out.
+pub fn extract_named_entities(xml_content: &str) -> Result<Vec<NamedEntity>> {
+    let mut reader = text_reader(xml_content);
+
+    let mut buf = Vec::new();
+    let mut entities: Vec<NamedEntity> = Vec::new();
+    let mut entity_index: HashMap<(String, String), usize> = HashMap::new();
+    // Open `<named-content>` elements, innermost last, with their text so far
+    let mut open: Vec<(Option<String>, Vec<String>)> = Vec::new();
+
+    loop {
+        match next_event(&mut reader, &mut buf) {
+            Ok(Event::Start(ref e)) if e.local_name().as_ref() == b"named-content" => {
+                open.push((attribute_value(e, b"content-type"), Vec::new()));
+            }
+            Ok(Event::Text(e)) => {
+                let text = std::str::from_utf8(e.as_ref()).unwrap_or_default();
+                for (_, parts) in &mut open {
+                    parts.push(text.to_string());
+                }
+            }
+            Ok(Event::End(ref e)) if e.local_name().as_ref() == b"named-content" => {
+                if let Some((Some(entity_type), parts)) = open.pop() {
+                    let text = normalize_whitespace(&parts.join(" "));
+                    if !text.is_empty() {
+                        match entity_index.entry((entity_type.clone(), text.clone())) {
+                            Entry::Occupied(entry) => entities[*entry.get()].count += 1,
+                            Entry::Vacant(entry) => {
+                                entry.insert(entities.len());
+                                entities.push(NamedEntity {
+                                    entity_type,
+                                    text,
+                                    count: 1,
+                                });
+                            }
+                        }
+                    }
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(e.into()),
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(entities)
+}
+
+/// Characters of paragraph text kept on each side of an xref as its context
+const XREF_CONTEXT_CHARS: usize = 60;
+
+/// Byte range of `text[start..end]` widened by up to `context_chars`
+/// characters on each side, clamped to the text
+pub(crate) fn context_bounds(
+    text: &str,
+    start: usize,
+    end: usize,
+    context_chars: usize,
+) -> (usize, usize) {
+    let context_start = text[..start]
+        .char_indices()
+        .rev()
+        .take(context_chars)
+        .last()
+        .map_or(start, |(i, _)| i);
+    let context_end = text[end..]
+        .char_indices()
+        .nth(context_chars)
+        .map_or(text.len(), |(i, _)| end + i);
+    (context_start, context_end)
+}
+
+/// Collect the `<xref>` targets of the body in document order, with the text
+/// around each xref in its paragraph as context. Xrefs without a `rid` are
+/// left out.
+pub fn extract_cross_references(xml_content: &str) -> Result<Vec<CrossReference>> {
+    let mut reader = text_reader(xml_content);
+
+    let mut buf = Vec::new();
+    let mut xrefs = Vec::new();
+    let mut in_body = false;
+    let mut in_xref = false;
+    // Text of the current paragraph, and its xrefs (ref-type, rids) with their
+    // byte range in it
+    type PendingXref = (Option<String>, String, usize, usize);
+    let mut paragraph = String::new();
+    let mut pending: Vec<PendingXref> = Vec::new();
+
+    let mut flush = |paragraph: &mut String, pending: &mut Vec<PendingXref>| {
+        for (ref_type, rids, start, end) in pending.drain(..) {
+            let (context_start, context_end) =
+                context_bounds(paragraph, start, end, XREF_CONTEXT_CHARS);
+            let context = normalize_whitespace(&paragraph[context_start..context_end]);
+            for rid in rids.split_whitespace() {
+                xrefs.push(CrossReference {
+                    ref_type: ref_type.clone(),
+                    rid: rid.to_string(),
+                    context: context.clone(),
+                });
+            }
+        }
+        paragraph.clear();
+    };
+
+    loop {
+        match next_event(&mut reader, &mut buf) {
+            Ok(Event::Start(ref e)) => match e.local_name().as_ref() {
+                b"body" => in_body = true,
+                b"p" if in_body => flush(&mut paragraph, &mut pending),
+                b"xref" if in_body => {
+                    if let Some(rid) = attribute_value(e, b"rid") {
+                        let offset = paragraph.len();
+                        pending.push((attribute_value(e, b"ref-type"), rid, offset, offset));
+                        in_xref = true;
+                    }
+                }
+                _ => {}
+            },
+            Ok(Event::Empty(ref e)) if in_body && e.local_name().as_ref() == b"xref" => {
+                if let Some(rid) = attribute_value(e, b"rid") {
+                    let offset = paragraph.len();
+                    pending.push((attribute_value(e, b"ref-type"), rid, offset, offset));
+                }
+            }
+            Ok(Event::Text(e)) if in_body => {
+                paragraph.push_str(std::str::from_utf8(e.as_ref()).unwrap_or_default());
+            }
+            Ok(Event::End(ref e)) => match e.local_name().as_ref() {
+                b"body" => {
+                    flush(&mut paragraph, &mut pending);
+                    in_body = false;
+                }
+                b"p" if in_body => flush(&mut paragraph, &mut pending),
+                b"xref" if in_xref => {
+                    if let Some(last) = pending.last_mut() {
+                        last.3 = paragraph.len();
+                    }
+                    in_xref = false;
+                }
+                _ => {}
+            },
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(e.into()),
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(xrefs)
+}
+
+/// Extract every `<table-wrap>` in the document, in document order. Cells keep
+/// the text of their inline markup; several `<table>`s in one wrap are stacked
+pub fn extract_tables(xml_content: &str) -> Result<Vec<Table>> {
+    let mut reader = text_reader(xml_content);
+
+    let mut buf = Vec::new();
+    let mut tables = Vec::new();
+    let mut current_table: Option<Table> = None;
+    let mut grid = TableGrid::default();
+    let mut caption_parts = Vec::new();
+    let mut cell_parts = Vec::new();
+    let mut current_label = String::new();
+    let mut cell_spans = (1, 1);
+    let mut in_caption = false;
+    let mut in_label = false;
+    let mut in_cell = false;
+
+    loop {
+        match next_event(&mut reader, &mut buf) {
+            Ok(Event::Start(ref e)) => match e.local_name().as_ref() {
+                b"table-wrap" => {
+                    current_table = Some(Table {
+                        id: attribute_value(e, b"id"),
+                        ..Default::default()
+                    });
+                    grid = TableGrid::default();
+                    caption_parts.clear();
+                }
+                b"table" => {
+                    // Spans never reach across separate tables
+                    grid.carried.clear();
+                }
+                b"caption" => {
+                    in_caption = current_table.is_some();
+                }
+                b"label" => {
+                    if current_table.is_some() && !in_caption && !in_cell {
+                        in_label = true;
+                        current_label.clear();
+                    }
+                }
+                b"td" | b"th" => {
+                    if current_table.is_some() {
+                        in_cell = true;
+                        cell_parts.clear();
+                        cell_spans = (span_attribute(e, b"colspan"), span_attribute(e, b"rowspan"));
+                    }
+                }
+                _ => {}
+            },
+            Ok(Event::Empty(ref e)) => match e.local_name().as_ref() {
+                b"td" | b"th" => {
+                    if current_table.is_some() {
+                        grid.push_cell(
+                            String::new(),
+                            span_attribute(e, b"colspan"),
+                            span_attribute(e, b"rowspan"),
+                        );
+                    }
+                }
+                _ => {}
+            },
+            Ok(Event::Text(e)) => {
+                let text = std::str::from_utf8(e.as_ref()).unwrap_or_default();
+                if in_label {
+                    current_label.push_str(text);
+                } else if in_cell {
+                    cell_parts.push(text.to_string());
+                } else if in_caption {
+                    caption_parts.push(text.to_string());
+                }
+            }
+            Ok(Event::End(ref e)) => match e.local_name().as_ref() {
+                b"table-wrap" => {
+                    if let Some(mut table) = current_table.take() {
+                        table.caption = caption_parts.join(" ").trim().to_string();
+                        table.rows = std::mem::take(&mut grid.rows);
+                        tables.push(table);
+                    }
+                    caption_parts.clear();
+                    in_caption = false;
+                }
+                b"caption" => {
+                    in_caption = false;
+                }
+                b"label" => {
+                    if in_label {
+                        if let Some(table) = current_table.as_mut() {
+                            let trimmed = current_label.trim();
+                            if !trimmed.is_empty() {
+                                table.label = Some(trimmed.to_string());
+                            }
+                        }
+                        in_label = false;
+                    }
+                }
+                b"td" | b"th" => {
+                    if in_cell {
+                        let text = cell_parts.join(" ").trim().to_string();
+                        grid.push_cell(text, cell_spans.0, cell_spans.1);
+                        in_cell = false;
+                    }
+                }
+                b"tr" => {
+                    if current_table.is_some() {
+                        grid.end_row();
+                    }
+                }
+                _ => {}
+            },
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(e.into()),
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(tables)
+}
+
+/// Read one XML file and extract its metadata
+pub fn read_and_extract(xml_path: &str, options: &ExtractOptions) -> Result<ArticleMetadata> {
+    let xml_content = load_xml_with_retries(xml_path, options)?;
+    let mut metadata = extract_article_metadata_with_options(&xml_content, xml_path, options)?;
+    metadata.source_mtime = modified_time(xml_path);
+    Ok(metadata)
+}
+
+/// Modification time of a file in Unix seconds, if the filesystem reports one
+fn modified_time(path: &str) -> Option<i64> {
+    let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+    let seconds = match modified.duration_since(std::time::UNIX_EPOCH) {
+        Ok(since) => since.as_secs() as i64,
+        Err(before) => -(before.duration().as_secs() as i64),
+    };
+    Some(seconds)
+}
+
+/// Number of files being read through [`load_xml_with_retries`], across
+/// threads and calls, and the signal that one of them finished
+static OPEN_FILES: (Mutex<usize>, Condvar) = (Mutex::new(0), Condvar::new());
+
+/// A turn to read a file, taken from [`OPEN_FILES`] and handed back on drop
+struct OpenFileSlot;
+
+impl OpenFileSlot {
+    /// Wait until fewer than `limit` files are being read, then take a turn
+    fn acquire(limit: usize) -> Self {
+        let (open, finished) = &OPEN_FILES;
+        let mut open = open.lock().unwrap_or_else(|e| e.into_inner());
+        while *open >= limit.max(1) {
+            open = finished.wait(open).unwrap_or_else(|e| e.into_inner());
+        }
+        *open += 1;
+        OpenFileSlot
+    }
+}
+
+impl Drop for OpenFileSlot {
+    fn drop(&mut self) {
+        let (open, finished) = &OPEN_FILES;
+        *open.lock().unwrap_or_else(|e| e.into_inner()) -= 1;
+        // Callers may wait with different limits
+        finished.notify_all();
+    }
+}
+
+/// [`load_xml`] after the `max_bytes` check, tried again up to
+/// `options.retries` times while it fails with an I/O error. At most
+/// `options.max_open_files` files are read at once; the turn is held only
+/// while reading, not while repairing or parsing.
+fn load_xml_with_retries(xml_path: &str, options: &ExtractOptions) -> Result<String> {
+    let mut attempt = 0;
+    loop {
+        let slot = OpenFileSlot::acquire(options.max_open_files.unwrap_or(DEFAULT_MAX_OPEN_FILES));
+        let result = match options.max_bytes {
+            Some(limit) => check_file_size(xml_path, limit),
+            None => Ok(()),
+        }
+        .and_then(|_| read_xml_text(xml_path));
+        // Not held through the retry delay
+        drop(slot);
+
+        match result {
+            Err(XmlProcessorError::IoError(e)) if attempt < options.retries => {
+                attempt += 1;
+                log::info!(
+                    "Retrying {xml_path} ({attempt}/{}) after: {e}",
+                    options.retries
+                );
+                std::thread::sleep(std::time::Duration::from_millis(options.retry_delay_ms));
+            }
+            result => {
+                return result.map(|xml_content| {
+                    repair_if_lenient(xml_content, xml_path, options.lenient_xml)
+                })
+            }
+        }
+    }
+}
+
+/// Open an NDJSON output file: truncated, or with `append` positioned after
+/// its existing lines (a missing final newline is added first so the next
+/// record starts on a line of its own)
+pub fn open_ndjson_output(output_path: &str, append: bool) -> std::io::Result<File> {
+    if !append {
+        return File::create(output_path);
+    }
+
+    let mut file = std::fs::OpenOptions::new()
+        .read(true)
+        .append(true)
+        .create(true)
+        .open(output_path)?;
+    if file.metadata()?.len() > 0 {
+        let mut last_byte = [0u8];
+        file.seek(SeekFrom::End(-1))?;
+        file.read_exact(&mut last_byte)?;
+        if last_byte[0] != b'\n' {
+            file.write_all(b"\n")?;
+        }
+    }
+    Ok(file)
+}
+
+/// Tallies of a batch run, by outcome of each input file
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ProcessingSummary {
+    pub total_files: usize,
+    pub succeeded: usize,
+    /// Files that couldn't be read, aren't XML text or exceed `max_bytes`
+    pub read_failures: usize,
+    /// Files that were read but aren't well-formed XML
+    pub parse_failures: usize,
+    /// Files without a root element, or without title, abstract and body text
+    /// (see [`ArticleMetadata::is_empty`]); the latter are still written
+    pub empty_documents: usize,
+    /// On-disk size of the files that were read (compressed size for `.gz`)
+    pub bytes_read: u64,
+}
+
+impl ProcessingSummary {
+    /// Count the outcome of one file
+    pub fn record(&mut self, xml_path: &str, outcome: &Result<ArticleMetadata>) {
+        self.total_files += 1;
+        let was_read = match outcome {
+            Ok(metadata) if metadata.is_empty => {
+                self.empty_documents += 1;
+                true
+            }
+            Ok(_) => {
+                self.succeeded += 1;
+                true
+            }
+            Err(XmlProcessorError::EmptyDocument) => {
+                self.empty_documents += 1;
+                true
+            }
+            Err(e) if matches!(e.status(), "read_error" | "too_large") => {
+                self.read_failures += 1;
+                false
+            }
+            Err(_) => {
+                self.parse_failures += 1;
+                true
+            }
+        };
+        if was_read {
+            self.bytes_read += std::fs::metadata(xml_path).map_or(0, |m| m.len());
+        }
+    }
+}
+
+/// Write the articles of `xml_paths` to `writer` as NDJSON, one line each,
+/// and return the tallies of the run. Files that fail are reported and left
+/// out.
+pub fn write_ndjson_with_summary<W: Write>(
+    xml_paths: &[String],
+    writer: &mut W,
+    options: &ExtractOptions,
+) -> Result<ProcessingSummary> {
+    let mut summary = ProcessingSummary::default();
+
+    for xml_path in xml_paths {
+        let outcome = read_and_extract(xml_path, options);
+        summary.record(xml_path, &outcome);
+        match outcome {
+            Ok(metadata) => {
+                serde_json::to_writer(&mut *writer, &metadata)?;
+                writer.write_all(b"\n")?;
+            }
+            Err(e) => e.report(xml_path),
+        }
+    }
+
+    writer.flush()?;
+    Ok(summary)
+}
+
+/// Write the articles of `xml_paths` as two NDJSON streams: every record
+/// without its body text (`full_text`, `body_paragraphs`,
+/// `full_text_sections`) to `abstracts`, and a `{pmid, file_path, full_text}`
+/// record to `full_texts` for the articles that have body text. Returns the
+/// number of lines written to each. Files that fail are reported and left out.
+pub fn write_split_ndjson<A: Write, B: Write>(
+    xml_paths: &[String],
+    abstracts: &mut A,
+    full_texts: &mut B,
+    options: &ExtractOptions,
+) -> Result<(usize, usize)> {
+    let mut abstract_count = 0;
+    let mut full_text_count = 0;
+
+    for xml_path in xml_paths {
+        let mut metadata = match read_and_extract(xml_path, options) {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                e.report(xml_path);
+                continue;
+            }
+        };
+
+        let full_text = metadata
+            .full_text
+            .take()
+            .filter(|text| !text.trim().is_empty());
+        metadata.body_paragraphs.clear();
+        metadata.full_text_sections.clear();
+        serde_json::to_writer(&mut *abstracts, &metadata)?;
+        abstracts.write_all(b"\n")?;
+        abstract_count += 1;
+
+        if let Some(full_text) = full_text {
+            let record = serde_json::json!({
+                "pmid": metadata.pmid,
+                "file_path": metadata.file_path,
+                "full_text": full_text,
+            });
+            serde_json::to_writer(&mut *full_texts, &record)?;
+            full_texts.write_all(b"\n")?;
+            full_text_count += 1;
+        }
+    }
+
+    abstracts.flush()?;
+    full_texts.flush()?;
+    Ok((abstract_count, full_text_count))
+}
+
+/// Write the articles of `xml_paths` to `writer` as one JSON array, each
+/// object serialized as soon as it is extracted, and return how many were
+/// written. Files that fail are reported and left out.
+pub fn write_json_array<W: Write>(
+    xml_paths: &[String],
+    writer: &mut W,
+    options: &ExtractOptions,
+) -> Result<usize> {
+    let mut written = 0;
+    writer.write_all(b"[")?;
+
+    for xml_path in xml_paths {
+        match read_and_extract(xml_path, options) {
+            Ok(metadata) => {
+                if written > 0 {
+                    writer.write_all(b",")?;
+                }
+                writer.write_all(b"\n")?;
+                serde_json::to_writer(&mut *writer, &metadata)?;
+                written += 1;
+            }
+            Err(e) => e.report(xml_path),
+        }
+    }
+
+    writer.write_all(if written > 0 { b"\n]\n" } else { b"]\n" })?;
+    writer.flush()?;
+    Ok(written)
+}
+
+/// List-of-strings cell for a list column
+fn string_list(values: Vec<String>) -> Series {
+    Series::new("".into(), values)
+}
+
+/// List-of-lists-of-strings cell for a nested list column
+fn nested_string_list(values: Vec<Vec<String>>) -> Series {
+    if values.is_empty() {
+        return Series::new_empty("".into(), &DataType::List(Box::new(DataType::String)));
+    }
+    let inner: Vec<Series> = values.into_iter().map(string_list).collect();
+    Series::new("".into(), inner)
+}
+
+/// `List(Struct{surname, given_names})` cell holding an article's authors
+fn author_structs(
+    surnames: Vec<Option<String>>,
+    given_names: Vec<Option<String>>,
+) -> PolarsResult<Series> {
+    let fields = [
+        Series::new("surname".into(), surnames),
+        Series::new("given_names".into(), given_names),
+    ];
+    let authors = StructChunked::from_series("".into(), fields[0].len(), fields.iter())?;
+    Ok(authors.into_series())
+}
+
+/// `List(Struct{name, orcid, affiliations, email, roles})` cell with one entry
+/// per author, assembled from the per-author fields; `email` is only set on
+/// the corresponding author
+fn author_detail_structs(metadata: &ArticleMetadata) -> PolarsResult<Series> {
+    let author_count = metadata.authors.len();
+    // Records read back from NDJSON written before the index was kept fall
+    // back to the first author of that name
+    let corresponding_index = metadata.corresponding_author_index.or_else(|| {
+        metadata
+            .authors
+            .iter()
+            .position(|name| metadata.corresponding_author.as_ref() == Some(name))
+    });
+    let emails: Vec<Option<String>> = (0..author_count)
+        .map(|i| {
+            (corresponding_index == Some(i))
+                .then(|| metadata.corresponding_email.clone())
+                .flatten()
+        })
+        .collect();
+    // The per-author vectors are aligned with `authors`; pad defensively
+    let per_author = |lists: &[Vec<String>]| -> Vec<Vec<String>> {
+        (0..author_count)
+            .map(|i| lists.get(i).cloned().unwrap_or_default())
+            .collect()
+    };
+    let orcids: Vec<Option<String>> = (0..author_count)
+        .map(|i| metadata.orcids.get(i).cloned().flatten())
+        .collect();
+    let is_collab: Vec<bool> = (0..author_count)
+        .map(|i| {
+            metadata
+                .author_is_collab
+                .get(i)
+                .copied()
+                .unwrap_or_default()
+        })
+        .collect();
+
+    let fields = [
+        Series::new("name".into(), &metadata.authors),
+        Series::new("orcid".into(), orcids),
+        nested_string_list(per_author(&metadata.affiliations)).with_name("affiliations".into()),
+        Series::new("email".into(), emails),
+        nested_string_list(per_author(&metadata.author_roles)).with_name("roles".into()),
+        Series::new("is_collab".into(), is_collab),
+    ];
+    let authors = StructChunked::from_series("".into(), author_count, fields.iter())?;
+    Ok(authors.into_series())
+}
+
+/// `List(Struct{lang, <text_field>})` cell holding (language, text) pairs
+/// such as an article's translated titles
+fn lang_text_structs(pairs: Vec<(String, String)>, text_field: &str) -> PolarsResult<Series> {
+    let (langs, texts): (Vec<String>, Vec<String>) = pairs.into_iter().unzip();
+    let fields = [
+        Series::new("lang".into(), langs),
+        Series::new(text_field.into(), texts),
+    ];
+    let structs = StructChunked::from_series("".into(), fields[0].len(), fields.iter())?;
+    Ok(structs.into_series())
+}
+
+/// Number of whitespace-separated words; `None` when there is no text at all
+fn word_count(text: Option<&str>) -> Option<u32> {
+    text.map(|text| text.split_whitespace().count() as u32)
+}
+
+/// Column buffers for the article DataFrame; each pushed article is one row
+#[derive(Default)]
+pub(crate) struct ArticleColumns {
+    pmids: Vec<Option<String>>,
+    pmc_ids: Vec<Option<String>>,
+    article_versions: Vec<Option<String>>,
+    titles: Vec<Option<String>>,
+    subtitles: Vec<Option<String>>,
+    trans_titles: Vec<Option<Series>>,
+    article_types: Vec<Option<String>>,
+    languages: Vec<Option<String>>,
+    publication_types: Vec<Option<Series>>,
+    abstracts: Vec<Option<String>>,
+    abstract_xmls: Vec<Option<String>>,
+    abstract_word_counts: Vec<Option<u32>>,
+    abstract_section_labels: Vec<Option<Series>>,
+    abstract_section_texts: Vec<Option<Series>>,
+    abstracts_by_language: Vec<Option<Series>>,
+    graphical_abstract_hrefs: Vec<Option<String>>,
+    graphical_abstract_captions: Vec<Option<String>>,
+    journals: Vec<Option<String>>,
+    issns_print: Vec<Option<String>>,
+    issns_electronic: Vec<Option<String>>,
+    journal_abbrevs: Vec<Option<String>>,
+    publisher_names: Vec<Option<String>>,
+    volumes: Vec<Option<String>>,
+    issues: Vec<Option<String>>,
+    first_pages: Vec<Option<String>>,
+    last_pages: Vec<Option<String>>,
+    elocation_ids: Vec<Option<String>>,
+    dates_received: Vec<Option<String>>,
+    dates_accepted: Vec<Option<String>>,
+    dates_published: Vec<Option<String>>,
+    authors: Vec<Option<Series>>,
+    authors_detailed: Vec<Option<Series>>,
+    first_authors: Vec<Option<String>>,
+    last_authors: Vec<Option<String>>,
+    author_counts: Vec<Option<u32>>,
+    affiliations: Vec<Option<Series>>,
+    orcids: Vec<Option<Series>>,
+    author_roles: Vec<Option<Series>>,
+    corresponding_authors: Vec<Option<String>>,
+    corresponding_emails: Vec<Option<String>>,
+    editors: Vec<Option<Series>>,
+    keywords: Vec<Option<Series>>,
+    mesh_terms: Vec<Option<Series>>,
+    full_texts: Vec<Option<String>>,
+    full_text_word_counts: Vec<Option<u32>>,
+    open_access_flags: Vec<Option<bool>>,
+    license_types: Vec<Option<String>>,
+    license_urls: Vec<Option<String>>,
+    license_texts: Vec<Option<String>>,
+    copyright_statements: Vec<Option<String>>,
+    acknowledgements: Vec<Option<String>>,
+    contributions_texts: Vec<Option<String>>,
+    conflicts_of_interest: Vec<Option<String>>,
+    competing_interest_flags: Vec<Option<bool>>,
+    data_availability: Vec<Option<String>>,
+    trial_numbers: Vec<Option<Series>>,
+    empty_flags: Vec<Option<bool>>,
+    source_mtimes: Vec<Option<i64>>,
+}
+
+impl ArticleColumns {
+    /// Append an article, or a row of nulls (`None`) for a file that couldn't be
+    /// read or parsed, so rows stay aligned with the input paths
+    pub(crate) fn push(&mut self, metadata: Option<ArticleMetadata>) -> PolarsResult<()> {
+        let parsed = metadata.is_some();
+        let metadata = metadata.unwrap_or_default();
+
+        // Built from several fields before they are moved into their columns
+        self.authors_detailed.push(if parsed {
+            Some(author_detail_structs(&metadata)?)
+        } else {
+            None
+        });
+        self.pmids.push(metadata.pmid);
+        self.pmc_ids.push(metadata.pmc_id);
+        self.article_versions.push(metadata.article_version);
+        self.titles.push(metadata.title);
+        self.subtitles.push(metadata.subtitle);
+        self.trans_titles.push(if parsed {
+            Some(lang_text_structs(metadata.trans_titles, "title")?)
+        } else {
+            None
+        });
+        self.article_types.push(metadata.article_type);
+        self.languages.push(metadata.language);
+        self.publication_types
+            .push(parsed.then(|| string_list(metadata.publication_types)));
+        self.abstract_word_counts
+            .push(word_count(metadata.abstract_text.as_deref()));
+        self.abstracts.push(metadata.abstract_text);
+        self.abstract_xmls.push(metadata.abstract_xml);
+        let (labels, texts): (Vec<String>, Vec<String>) =
+            metadata.abstract_sections.into_iter().unzip();
+        self.abstract_section_labels
+            .push(parsed.then(|| string_list(labels)));
+        self.abstract_section_texts
+            .push(parsed.then(|| string_list(texts)));
+        self.abstracts_by_language.push(if parsed {
+            Some(lang_text_structs(metadata.abstracts_by_language, "text")?)
+        } else {
+            None
+        });
+        self.graphical_abstract_hrefs
+            .push(metadata.graphical_abstract_href);
+        self.graphical_abstract_captions
+            .push(metadata.graphical_abstract_caption);
+        self.journals.push(metadata.journal);
+        self.issns_print.push(metadata.issn_print);
+        self.issns_electronic.push(metadata.issn_electronic);
+        self.journal_abbrevs.push(metadata.journal_abbrev);
+        self.publisher_names.push(metadata.publisher_name);
+        self.volumes.push(metadata.volume);
+        self.issues.push(metadata.issue);
+        self.first_pages.push(metadata.first_page);
+        self.last_pages.push(metadata.last_page);
+        self.elocation_ids.push(metadata.elocation_id);
+        self.dates_received.push(metadata.date_received);
+        self.dates_accepted.push(metadata.date_accepted);
+        self.dates_published.push(metadata.date_published);
+        self.first_authors.push(metadata.authors.first().cloned());
+        self.last_authors.push(metadata.authors.last().cloned());
+        self.author_counts
+            .push(parsed.then_some(metadata.authors.len() as u32));
+        self.authors.push(if parsed {
+            Some(author_structs(
+                metadata.author_surnames,
+                metadata.author_given_names,
+            )?)
+        } else {
+            None
+        });
+        self.affiliations
+            .push(parsed.then(|| nested_string_list(metadata.affiliations)));
+        self.orcids
+            .push(parsed.then(|| Series::new("".into(), metadata.orcids)));
+        self.author_roles
+            .push(parsed.then(|| nested_string_list(metadata.author_roles)));
+        self.corresponding_authors
+            .push(metadata.corresponding_author);
+        self.corresponding_emails.push(metadata.corresponding_email);
+        self.editors
+            .push(parsed.then(|| string_list(metadata.editors)));
+        self.keywords
+            .push(parsed.then(|| string_list(metadata.keywords)));
+        self.mesh_terms
+            .push(parsed.then(|| string_list(metadata.mesh_terms)));
+        self.full_text_word_counts
+            .push(word_count(metadata.full_text.as_deref()));
+        self.full_texts.push(metadata.full_text);
+        self.open_access_flags.push(metadata.is_open_access);
+        self.license_types.push(metadata.license_type);
+        self.license_urls.push(metadata.license_url);
+        self.license_texts.push(metadata.license_text);
+        self.copyright_statements.push(metadata.copyright_statement);
+        self.acknowledgements.push(metadata.acknowledgements);
+        self.contributions_texts.push(metadata.contributions_text);
+        self.conflicts_of_interest
+            .push(metadata.conflict_of_interest);
+        self.competing_interest_flags
+            .push(metadata.has_competing_interests);
+        self.data_availability.push(metadata.data_availability);
+        self.trial_numbers
+            .push(parsed.then(|| string_list(metadata.trial_numbers)));
+        self.empty_flags.push(parsed.then_some(metadata.is_empty));
+        self.source_mtimes.push(metadata.source_mtime);
+        Ok(())
+    }
+
+    pub(crate) fn into_dataframe(self) -> PolarsResult<DataFrame> {
+        let mut df = df! {
+            "pmid" => &self.pmids,
+            "pmc_id" => &self.pmc_ids,
+            "article_version" => &self.article_versions,
+            "title" => &self.titles,
+            "subtitle" => &self.subtitles,
+            "trans_titles" => &self.trans_titles,
+            "article_type" => &self.article_types,
+            "language" => &self.languages,
+            "publication_types" => &self.publication_types,
+            "abstract" => &self.abstracts,
+            "abstract_xml" => &self.abstract_xmls,
+            "abstract_word_count" => &self.abstract_word_counts,
+            "abstract_section_labels" => &self.abstract_section_labels,
+            "abstract_section_texts" => &self.abstract_section_texts,
+            "abstracts_by_language" => &self.abstracts_by_language,
+            "graphical_abstract_href" => &self.graphical_abstract_hrefs,
+            "graphical_abstract_caption" => &self.graphical_abstract_captions,
+            "journal" => &self.journals,
+            "issn_print" => &self.issns_print,
+            "issn_electronic" => &self.issns_electronic,
+            "journal_abbrev" => &self.journal_abbrevs,
+            "publisher_name" => &self.publisher_names,
+            "volume" => &self.volumes,
+            "issue" => &self.issues,
+            "first_page" => &self.first_pages,
+            "last_page" => &self.last_pages,
+            "elocation_id" => &self.elocation_ids,
+            "date_received" => &self.dates_received,
+            "date_accepted" => &self.dates_accepted,
+            "date_published" => &self.dates_published,
+            "authors" => &self.authors,
+            "first_author" => &self.first_authors,
+            "last_author" => &self.last_authors,
+            "author_count" => &self.author_counts,
+            "affiliations" => &self.affiliations,
+            "orcids" => &self.orcids,
+            "author_roles" => &self.author_roles,
+            "authors_detailed" => &self.authors_detailed,
+            "corresponding_author" => &self.corresponding_authors,
+            "corresponding_email" => &self.corresponding_emails,
+            "editors" => &self.editors,
+            "keywords" => &self.keywords,
+            "mesh_terms" => &self.mesh_terms,
+            "full_text" => &self.full_texts,
+            "full_text_word_count" => &self.full_text_word_counts,
+            "is_open_access" => &self.open_access_flags,
+            "license_type" => &self.license_types,
+            "license_url" => &self.license_urls,
+            "license_text" => &self.license_texts,
+            "copyright_statement" => &self.copyright_statements,
+            "acknowledgements" => &self.acknowledgements,
+            "contributions_text" => &self.contributions_texts,
+            "conflict_of_interest" => &self.conflicts_of_interest,
+            "has_competing_interests" => &self.competing_interest_flags,
+            "data_availability" => &self.data_availability,
+            "trial_numbers" => &self.trial_numbers,
+            "is_empty" => &self.empty_flags,
+            "source_mtime" => &self.source_mtimes,
+        }?;
+
+        // When every file in a batch failed, the list columns hold only nulls
+        // and come out as List(Null); pin their types so that separately
+        // built batches (e.g. Parquet dataset parts) share one schema
+        let author_fields = vec![
+            Field::new("surname".into(), DataType::String),
+            Field::new("given_names".into(), DataType::String),
+        ];
+        let author_detail_fields = vec![
+            Field::new("name".into(), DataType::String),
+            Field::new("orcid".into(), DataType::String),
+            Field::new(
+                "affiliations".into(),
+                DataType::List(Box::new(DataType::String)),
+            ),
+            Field::new("email".into(), DataType::String),
+            Field::new("roles".into(), DataType::List(Box::new(DataType::String))),
+            Field::new("is_collab".into(), DataType::Boolean),
+        ];
+        let trans_title_fields = vec![
+            Field::new("lang".into(), DataType::String),
+            Field::new("title".into(), DataType::String),
+        ];
+        let abstract_language_fields = vec![
+            Field::new("lang".into(), DataType::String),
+            Field::new("text".into(), DataType::String),
+        ];
+        for (name, inner) in [
+            ("trans_titles", DataType::Struct(trans_title_fields)),
+            ("publication_types", DataType::String),
+            ("abstract_section_labels", DataType::String),
+            ("abstract_section_texts", DataType::String),
+            (
+                "abstracts_by_language",
+                DataType::Struct(abstract_language_fields),
+            ),
+            ("authors", DataType::Struct(author_fields)),
+            ("affiliations", DataType::List(Box::new(DataType::String))),
+            ("orcids", DataType::String),
+            ("author_roles", DataType::List(Box::new(DataType::String))),
+            ("authors_detailed", DataType::Struct(author_detail_fields)),
+            ("editors", DataType::String),
+            ("keywords", DataType::String),
+            ("mesh_terms", DataType::String),
+            ("trial_numbers", DataType::String),
+        ] {
+            let dtype = DataType::List(Box::new(inner));
+            df.try_apply(name, |column| column.cast(&dtype))?;
+        }
+
+        Ok(df)
+    }
+}
+
+/// Build the article DataFrame for `xml_paths`, one row per path; files that
+/// can't be read or parsed are logged and give a row of nulls
+pub fn articles_to_dataframe(xml_paths: &[String]) -> PolarsResult<DataFrame> {
+    articles_to_dataframe_with_options(xml_paths, &ExtractOptions::default())
+}
+
+/// [`articles_to_dataframe_with_options`] building only `columns`, in that
+/// order; the body text isn't even collected unless a column derived from it
+/// is among them
+pub fn articles_to_dataframe_with_columns(
+    xml_paths: &[String],
+    options: &ExtractOptions,
+    columns: &[String],
+) -> PolarsResult<DataFrame> {
+    let known = ArticleColumns::default().into_dataframe()?;
+    if let Some(unknown) = columns
+        .iter()
+        .find(|name| known.column(name.as_str()).is_err())
+    {
+        polars_bail!(
+            ColumnNotFound: "unknown column '{unknown}' (expected one of {})",
+            known.get_column_names_str().join(", ")
+        );
+    }
+
+    let mut options = options.clone();
+    options.skip_body_text = !columns.iter().any(|name| {
+        matches!(
+            name.as_str(),
+            "full_text" | "full_text_word_count" | "trial_numbers"
+        )
+    });
+    articles_to_dataframe_with_options(xml_paths, &options)?.select(columns)
+}
+
+/// [`articles_to_dataframe`] with non-default [`ExtractOptions`]
+pub fn articles_to_dataframe_with_options(
+    xml_paths: &[String],
+    options: &ExtractOptions,
+) -> PolarsResult<DataFrame> {
+    let mut columns = ArticleColumns::default();
+
+    for xml_path in xml_paths {
+        match read_and_extract(xml_path, options) {
+            Ok(metadata) => columns.push(Some(metadata))?,
+            Err(e) => {
+                e.report(xml_path);
+                // Add None values to maintain alignment
+                columns.push(None)?;
+            }
+        }
+    }
+
+    columns.into_dataframe()
+}
+
+/// Fields `validate_articles` can check for
+pub const VALIDATABLE_FIELDS: &[&str] = &[
+    "pmid",
+    "pmc_id",
+    "doi",
+    "title",
+    "abstract",
+    "authors",
+    "affiliations",
+    "keywords",
+    "mesh_terms",
+    "journal",
+    "volume",
+    "issue",
+    "publication_date",
+    "article_type",
+    "language",
+    "full_text",
+    "funding",
+    "license",
+    "data_availability",
+];
+
+/// Whether `field` (one of `VALIDATABLE_FIELDS`) was extracted with a
+/// non-blank value; list fields must have at least one entry
+pub fn has_field(metadata: &ArticleMetadata, field: &str) -> bool {
+    let present = |value: &Option<String>| value.as_deref().is_some_and(|v| !v.trim().is_empty());
+    match field {
+        "pmid" => present(&metadata.pmid),
+        "pmc_id" => present(&metadata.pmc_id),
+        "doi" => present(&metadata.doi),
+        "title" => present(&metadata.title),
+        "abstract" => present(&metadata.abstract_text),
+        "authors" => !metadata.authors.is_empty(),
+        "affiliations" => metadata.affiliations.iter().any(|affs| !affs.is_empty()),
+        "keywords" => !metadata.keywords.is_empty(),
+        "mesh_terms" => !metadata.mesh_terms.is_empty(),
+        "journal" => present(&metadata.journal),
+        "volume" => present(&metadata.volume),
+        "issue" => present(&metadata.issue),
+        "publication_date" => present(&metadata.publication_date),
+        "article_type" => present(&metadata.article_type),
+        "language" => present(&metadata.language),
+        "full_text" => present(&metadata.full_text),
+        "funding" => !metadata.funding.is_empty(),
+        "license" => present(&metadata.license_type) || present(&metadata.license_url),
+        "data_availability" => present(&metadata.data_availability),
+        _ => false,
+    }
+}
+
+/// Check which of `required_fields` each file of `xml_paths` provides: a
+/// `file_path` column, a boolean `has_<field>` column per field and
+/// `is_complete` when all are present. Files that can't be read or parsed
+/// are logged and have every field missing.
+pub fn validate_articles(
+    xml_paths: &[String],
+    required_fields: &[String],
+    options: &ExtractOptions,
+) -> PolarsResult<DataFrame> {
+    let mut presence: Vec<Vec<bool>> =
+        vec![Vec::with_capacity(xml_paths.len()); required_fields.len()];
+    let mut complete = Vec::with_capacity(xml_paths.len());
+
+    for xml_path in xml_paths {
+        let metadata = read_and_extract(xml_path, options)
+            .inspect_err(|e| e.report(xml_path))
+            .ok();
+        let mut all_present = true;
+        for (field, column) in required_fields.iter().zip(presence.iter_mut()) {
+            let present = metadata.as_ref().is_some_and(|m| has_field(m, field));
+            all_present &= present;
+            column.push(present);
+        }
+        complete.push(all_present);
+    }
+
+    let mut columns = vec![Column::new("file_path".into(), xml_paths)];
+    for (field, column) in required_fields.iter().zip(presence) {
+        columns.push(Column::new(format!("has_{field}").into(), column));
+    }
+    columns.push(Column::new("is_complete".into(), complete));
+    DataFrame::new(columns)
+}
@@ -0,0 +1,100 @@
+//! Error type of the extraction functions, and (with the `python` feature)
+//! the Python exceptions its variants are raised as. Each exception subclasses
+//! the built-in exception raised before these existed (`OSError` or
+//! `ValueError`), so existing `except` clauses keep working.
+
+#[cfg(feature = "python")]
+use pyo3::create_exception;
+#[cfg(feature = "python")]
+use pyo3::exceptions::{PyOSError, PyValueError};
+#[cfg(feature = "python")]
+use pyo3::PyErr;
+use thiserror::Error;
+
+#[cfg(feature = "python")]
+create_exception!(xml_processor, XmlIoError, PyOSError);
+#[cfg(feature = "python")]
+create_exception!(xml_processor, XmlParseError, PyValueError);
+#[cfg(feature = "python")]
+create_exception!(xml_processor, EmptyDocumentError, XmlParseError);
+#[cfg(feature = "python")]
+create_exception!(xml_processor, UnsupportedFormatError, PyValueError);
+#[cfg(feature = "python")]
+create_exception!(xml_processor, SerializationError, PyValueError);
+#[cfg(feature = "python")]
+create_exception!(xml_processor, InputTooLargeError, XmlIoError);
+
+/// Why an XML file or document couldn't be turned into a record
+#[derive(Debug, Error)]
+pub enum XmlProcessorError {
+    /// The file couldn't be opened or read
+    #[error("{0}")]
+    IoError(#[from] std::io::Error),
+    /// The document is not well-formed XML
+    #[error("Error parsing XML: {0}")]
+    XmlParseError(#[from] quick_xml::Error),
+    /// The document has no root element
+    #[error("Empty XML document")]
+    EmptyDocument,
+    /// The file is not UTF-8 XML text, e.g. a zip archive
+    #[error("Unsupported format: {0}")]
+    UnsupportedFormat(String),
+    /// The extracted record couldn't be serialized
+    #[error("{0}")]
+    SerializationError(#[from] serde_json::Error),
+    /// The file (or one article of a multi-article file) is larger than the
+    /// `max_bytes` limit, so it wasn't read
+    #[error("{size} bytes exceeds the limit of {limit} bytes")]
+    TooLarge { size: u64, limit: u64 },
+}
+
+impl XmlProcessorError {
+    /// Status label used in batch reports
+    pub fn status(&self) -> &'static str {
+        match self {
+            Self::IoError(_) | Self::UnsupportedFormat(_) => "read_error",
+            Self::TooLarge { .. } => "too_large",
+            Self::EmptyDocument => "empty",
+            _ => "parse_error",
+        }
+    }
+
+    /// Log the failure for `xml_path` as a warning
+    pub fn report(&self, xml_path: &str) {
+        match self {
+            Self::IoError(_) | Self::UnsupportedFormat(_) => {
+                log::warn!("Failed to read {xml_path}: {self}")
+            }
+            Self::TooLarge { .. } => log::warn!("Skipping {xml_path}: {self}"),
+            _ => log::warn!("Failed to extract metadata from {xml_path}: {self}"),
+        }
+    }
+
+    /// The Python exception for this error, its message prefixed with `context`
+    #[cfg(feature = "python")]
+    pub fn into_py_err(self, context: &str) -> PyErr {
+        let message = format!("{context}: {self}");
+        match self {
+            Self::IoError(_) => XmlIoError::new_err(message),
+            Self::XmlParseError(_) => XmlParseError::new_err(message),
+            Self::EmptyDocument => EmptyDocumentError::new_err(message),
+            Self::UnsupportedFormat(_) => UnsupportedFormatError::new_err(message),
+            Self::SerializationError(_) => SerializationError::new_err(message),
+            Self::TooLarge { .. } => InputTooLargeError::new_err(message),
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+impl From<XmlProcessorError> for PyErr {
+    fn from(e: XmlProcessorError) -> Self {
+        let context = match &e {
+            XmlProcessorError::IoError(_)
+            | XmlProcessorError::UnsupportedFormat(_)
+            | XmlProcessorError::TooLarge { .. } => "Failed to read XML file",
+            XmlProcessorError::SerializationError(_) => "Failed to serialize to JSON",
+            _ => "Failed to extract metadata",
+        };
+        e.into_py_err(context)
+    }
+}
@@ -0,0 +1,78 @@
+//! Lightweight language guess for articles whose root element has no
+//! `xml:lang`: the share of common function words of each language among the
+//! words of the title and abstract. Only tells apart the languages listed in
+//! `STOPWORDS`, and only for text long enough to contain a few of them.
+
+/// (ISO 639-1 code, frequent function words), chosen to overlap little
+const STOPWORDS: &[(&str, &[&str])] = &[
+    (
+        "en",
+        &[
+            "the", "and", "of", "to", "is", "that", "with", "for", "was", "were", "are", "this",
+            "these", "by", "from", "which", "we", "in",
+        ],
+    ),
+    (
+        "es",
+        &[
+            "el", "los", "las", "del", "y", "que", "por", "para", "se", "es", "fue", "entre",
+            "como", "sus", "estudio",
+        ],
+    ),
+    (
+        "fr",
+        &[
+            "le", "les", "des", "et", "du", "une", "est", "pour", "dans", "sur", "avec", "sont",
+            "au", "aux", "ont",
+        ],
+    ),
+    (
+        "de",
+        &[
+            "der", "die", "das", "und", "mit", "von", "den", "dem", "ist", "ein", "eine", "zu",
+            "bei", "für", "nicht", "auf", "wurde",
+        ],
+    ),
+    (
+        "pt",
+        &[
+            "os", "do", "da", "dos", "das", "em", "com", "uma", "um", "foi", "não", "são", "pelo",
+            "pela", "ao",
+        ],
+    ),
+    (
+        "it",
+        &[
+            "il", "gli", "di", "della", "dei", "delle", "che", "con", "per", "sono", "nel",
+            "nella", "è", "stato",
+        ],
+    ),
+];
+
+/// Fewest function words of the winning language for a guess to be made
+const MIN_HITS: usize = 3;
+
+/// ISO 639-1 code of the language `text` is most likely written in, or `None`
+/// when no language's function words stand out
+pub(crate) fn guess_language(text: &str) -> Option<&'static str> {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+
+    let mut best: Option<(&str, usize)> = None;
+    for (code, stopwords) in STOPWORDS {
+        let hits = words
+            .iter()
+            .filter(|word| stopwords.contains(&word.as_str()))
+            .count();
+        if best.is_none_or(|(_, best_hits)| hits > best_hits) {
+            best = Some((code, hits));
+        }
+    }
+
+    // At least one word in ten should be a function word of the language
+    best.filter(|&(_, hits)| hits >= MIN_HITS && hits * 10 >= words.len())
+        .map(|(code, _)| code)
+}
@@ -1,14 +1,101 @@
-use pyo3::prelude::*;
+pub mod core;
+pub mod error;
+#[cfg(feature = "langdetect")]
+mod langdetect;
+#[cfg(feature = "python")]
 mod nxml;
+#[cfg(feature = "python-logging")]
+mod py_logging;
+
+pub use crate::core::{
+    articles_to_dataframe, articles_to_dataframe_with_columns, articles_to_dataframe_with_options,
+    extract_article_metadata, extract_article_metadata_with_options, ArticleMetadata,
+    ExtractOptions, SCHEMA_VERSION,
+};
+pub use crate::error::XmlProcessorError;
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
 
+#[cfg(feature = "python")]
 #[pymodule]
 fn xml_processor(py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
+    #[cfg(feature = "python-logging")]
+    py_logging::init();
+
     // Add nxml functions to a submodule
     let nxml_mod = PyModule::new(py, "nxml")?;
+    nxml_mod.add_function(wrap_pyfunction!(nxml::scan_directory, py)?)?;
     nxml_mod.add_function(wrap_pyfunction!(nxml::xml_to_ndjson, py)?)?;
+    nxml_mod.add_function(wrap_pyfunction!(nxml::current_schema_version, py)?)?;
+    nxml_mod.add_function(wrap_pyfunction!(nxml::normalize_identifiers, py)?)?;
     nxml_mod.add_function(wrap_pyfunction!(nxml::batch_xml_to_ndjson, py)?)?;
+    nxml_mod.add_function(wrap_pyfunction!(nxml::batch_xml_to_ndjson_with_stats, py)?)?;
+    nxml_mod.add_function(wrap_pyfunction!(nxml::batch_xml_to_ndjson_chunked, py)?)?;
+    nxml_mod.add_function(wrap_pyfunction!(nxml::batch_xml_to_ndjson_with_report, py)?)?;
+    nxml_mod.add_function(wrap_pyfunction!(
+        nxml::batch_xml_to_ndjson_with_summary,
+        py
+    )?)?;
+    nxml_mod.add_function(wrap_pyfunction!(
+        nxml::batch_xml_to_ndjson_from_archive,
+        py
+    )?)?;
     nxml_mod.add_function(wrap_pyfunction!(nxml::xml_to_polars, py)?)?;
+    nxml_mod.add_function(wrap_pyfunction!(nxml::xml_to_polars_chunks, py)?)?;
+    nxml_mod.add_function(wrap_pyfunction!(nxml::validate_xml, py)?)?;
+    nxml_mod.add_function(wrap_pyfunction!(nxml::ndjson_to_polars, py)?)?;
+    nxml_mod.add_function(wrap_pyfunction!(nxml::parse_xml_string, py)?)?;
+    nxml_mod.add_function(wrap_pyfunction!(nxml::parse_multi_article_xml, py)?)?;
+    nxml_mod.add_function(wrap_pyfunction!(nxml::build_article_index, py)?)?;
+    nxml_mod.add_function(wrap_pyfunction!(nxml::read_article_at, py)?)?;
+    nxml_mod.add_function(wrap_pyfunction!(nxml::parse_article, py)?)?;
+    nxml_mod.add_function(wrap_pyfunction!(nxml::xml_to_paragraphs, py)?)?;
+    nxml_mod.add_function(wrap_pyfunction!(nxml::xml_to_sentences, py)?)?;
+    nxml_mod.add_function(wrap_pyfunction!(nxml::xml_to_sections, py)?)?;
+    nxml_mod.add_function(wrap_pyfunction!(nxml::xml_to_funding, py)?)?;
+    nxml_mod.add_function(wrap_pyfunction!(nxml::xml_to_figures, py)?)?;
+    nxml_mod.add_function(wrap_pyfunction!(nxml::xml_to_supplementary, py)?)?;
+    nxml_mod.add_function(wrap_pyfunction!(nxml::xml_to_entities, py)?)?;
+    nxml_mod.add_function(wrap_pyfunction!(nxml::xml_to_xrefs, py)?)?;
+    nxml_mod.add_function(wrap_pyfunction!(nxml::xml_to_tables, py)?)?;
+    nxml_mod.add_function(wrap_pyfunction!(nxml::batch_xml_split_output, py)?)?;
+    nxml_mod.add_function(wrap_pyfunction!(nxml::batch_xml_to_json_array, py)?)?;
+    nxml_mod.add_function(wrap_pyfunction!(nxml::batch_xml_to_parquet, py)?)?;
+    nxml_mod.add_function(wrap_pyfunction!(nxml::xml_to_parquet_dataset, py)?)?;
+    nxml_mod.add_function(wrap_pyfunction!(nxml::batch_xml_to_ipc, py)?)?;
+    nxml_mod.add_function(wrap_pyfunction!(nxml::references_to_polars, py)?)?;
+    nxml_mod.add_function(wrap_pyfunction!(nxml::xml_to_references, py)?)?;
     nxml_mod.add_function(wrap_pyfunction!(nxml::search_xml_content, py)?)?;
+    nxml_mod.add_function(wrap_pyfunction!(nxml::search_dataframe, py)?)?;
+    nxml_mod.add_function(wrap_pyfunction!(nxml::count_xml_matches, py)?)?;
+    nxml_mod.add_function(wrap_pyfunction!(nxml::word_frequencies, py)?)?;
+    nxml_mod.add_function(wrap_pyfunction!(nxml::tag_terms, py)?)?;
+    nxml_mod.add_function(wrap_pyfunction!(nxml::extract_accessions, py)?)?;
+    nxml_mod.add_class::<crate::core::ArticleMetadata>()?;
+    nxml_mod.add_class::<crate::core::Award>()?;
+    nxml_mod.add_class::<nxml::ArticleChunks>()?;
+    nxml_mod.add_class::<nxml::XmlNdjsonWriter>()?;
+
+    // Exceptions raised for XmlProcessorError variants
+    m.add("XmlIoError", py.get_type::<error::XmlIoError>())?;
+    m.add("XmlParseError", py.get_type::<error::XmlParseError>())?;
+    m.add(
+        "EmptyDocumentError",
+        py.get_type::<error::EmptyDocumentError>(),
+    )?;
+    m.add(
+        "UnsupportedFormatError",
+        py.get_type::<error::UnsupportedFormatError>(),
+    )?;
+    m.add(
+        "SerializationError",
+        py.get_type::<error::SerializationError>(),
+    )?;
+    m.add(
+        "InputTooLargeError",
+        py.get_type::<error::InputTooLargeError>(),
+    )?;
 
     // Add submodules to the main module
     m.add_submodule(&nxml_mod)?;
@@ -1,481 +1,807 @@
-use anyhow::Result;
+//! Python functions of the `xml_processor.nxml` submodule, thin wrappers
+//! around [`crate::core`] that release the GIL and convert errors and
+//! DataFrames.
+
+use crate::core::*;
+use crate::error::XmlProcessorError;
 use polars::prelude::*;
 use pyo3::prelude::*;
+use pyo3::types::PyDict;
 use pyo3_polars::PyDataFrame;
-use quick_xml::events::Event;
-use quick_xml::Reader;
+use rayon::prelude::*;
 use regex::Regex;
-use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
-use std::io::Write;
-
-/// Metadata for an article
-#[derive(Serialize, Deserialize, Default)]
-pub struct ArticleMetadata {
-    pub pmid: Option<String>,
-    pub pmc_id: Option<String>,
-    pub title: Option<String>,
-    pub abstract_text: Option<String>,
-    pub authors: Vec<String>,
-    pub journal: Option<String>,
-    pub publication_date: Option<String>,
-    pub doi: Option<String>,
-    pub full_text: Option<String>,
-    pub file_path: String,
-}
-
-/// Extract key metadata and text from PMC XML content
-pub fn extract_article_metadata(xml_content: &str, file_path: &str) -> Result<ArticleMetadata> {
-    let mut reader = Reader::from_str(xml_content);
-    reader.config_mut().trim_text(true);
-
-    let mut metadata = ArticleMetadata::default();
-    metadata.file_path = file_path.to_string();
-
-    let mut buf = Vec::new();
-    let mut current_text = String::new();
-    let mut in_title = false;
-    let mut in_abstract = false;
-    let mut in_contrib = false;
-    let mut in_surname = false;
-    let mut in_given_names = false;
-    let mut in_journal = false;
-    let mut in_body = false;
-    let mut in_pmid = false;
-    let mut in_pmc_id = false;
-    let mut in_doi = false;
-    let mut in_pub_date = false;
-    let mut in_year = false;
-    let mut in_month = false;
-    let mut in_day = false;
-    let mut full_text_parts = Vec::new();
-
-    // For author extraction
-    let mut current_surname = String::new();
-    let mut current_given_names = String::new();
-
-    // For publication date extraction
-    let mut current_year = String::new();
-    let mut current_month = String::new();
-    let mut current_day = String::new();
-
-    // Track document structure to avoid extracting from references/supplementary
-    let mut in_front_matter = false;
-    let mut title_extracted = false; // Only extract the first title
-
-    loop {
-        match reader.read_event_into(&mut buf) {
-            Ok(Event::Start(ref e)) => {
-                match e.name().as_ref() {
-                    b"front" => {
-                        in_front_matter = true;
-                    }
-                    b"back" => {
-                        in_front_matter = false;
-                    }
-                    b"article-title" => {
-                        // Only extract title if we're in front matter and haven't extracted one yet
-                        if in_front_matter && !title_extracted {
-                            in_title = true;
-                            current_text.clear();
-                        }
-                    }
-                    b"abstract" => {
-                        if in_front_matter {
-                            in_abstract = true;
-                            current_text.clear();
-                        }
-                    }
-                    b"contrib" => {
-                        if in_front_matter {
-                            // Check if this is an author contribution
-                            for attr in e.attributes() {
-                                if let Ok(attr) = attr {
-                                    if attr.key.as_ref() == b"contrib-type" {
-                                        let value = String::from_utf8_lossy(&attr.value);
-                                        if value == "author" {
-                                            in_contrib = true;
-                                            current_surname.clear();
-                                            current_given_names.clear();
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    b"surname" => {
-                        if in_contrib && in_front_matter {
-                            in_surname = true;
-                            current_surname.clear();
-                        }
-                    }
-                    b"given-names" => {
-                        if in_contrib && in_front_matter {
-                            in_given_names = true;
-                            current_given_names.clear();
-                        }
-                    }
-                    b"journal-title" => {
-                        if in_front_matter {
-                            in_journal = true;
-                            current_text.clear();
-                        }
-                    }
-                    b"pub-date" => {
-                        if in_front_matter {
-                            in_pub_date = true;
-                            current_year.clear();
-                            current_month.clear();
-                            current_day.clear();
-                        }
-                    }
-                    b"year" => {
-                        if in_pub_date && in_front_matter {
-                            in_year = true;
-                            current_year.clear();
-                        }
-                    }
-                    b"month" => {
-                        if in_pub_date && in_front_matter {
-                            in_month = true;
-                            current_month.clear();
-                        }
-                    }
-                    b"day" => {
-                        if in_pub_date && in_front_matter {
-                            in_day = true;
-                            current_day.clear();
-                        }
-                    }
-                    b"body" => {
-                        in_body = true;
-                    }
-                    b"article-id" => {
-                        if in_front_matter {
-                            current_text.clear();
-                            for attr in e.attributes() {
-                                if let Ok(attr) = attr {
-                                    if attr.key.as_ref() == b"pub-id-type" {
-                                        let value = String::from_utf8_lossy(&attr.value);
-                                        match value.as_ref() {
-                                            "pmid" => in_pmid = true,
-                                            "pmc" => in_pmc_id = true,
-                                            "doi" => in_doi = true,
-                                            _ => {}
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    _ => {}
-                }
-            }
-            Ok(Event::Text(e)) => {
-                let text = std::str::from_utf8(e.as_ref()).unwrap_or_default();
-
-                // Handle author name components
-                if in_surname && in_front_matter {
-                    current_surname.push_str(text);
-                } else if in_given_names && in_front_matter {
-                    current_given_names.push_str(text);
-                } else if in_year && in_pub_date && in_front_matter {
-                    current_year.push_str(text);
-                } else if in_month && in_pub_date && in_front_matter {
-                    current_month.push_str(text);
-                } else if in_day && in_pub_date && in_front_matter {
-                    current_day.push_str(text);
-                } else if (in_title || in_abstract || in_journal || in_pmid || in_pmc_id || in_doi)
-                    && in_front_matter
+use std::io::{BufRead, BufReader, BufWriter, Write};
+
+/// List the files under `root_dir` whose names match the glob `pattern`
+/// (e.g. `*.xml`, `*.nxml`, `*.xml.gz`), sorted, for the batch functions.
+/// Symlinks are followed; a link back into one of its own ancestors is
+/// reported and skipped instead of being walked forever.
+#[pyfunction(signature = (root_dir, pattern="*.xml", recursive=true))]
+pub fn scan_directory(root_dir: &str, pattern: &str, recursive: bool) -> PyResult<Vec<String>> {
+    let pattern = glob::Pattern::new(pattern).map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Invalid glob pattern '{pattern}': {e}"
+        ))
+    })?;
+    if !std::path::Path::new(root_dir).is_dir() {
+        return Err(PyErr::new::<pyo3::exceptions::PyIOError, _>(format!(
+            "Not a directory: {root_dir}"
+        )));
+    }
+
+    let mut walker = walkdir::WalkDir::new(root_dir).follow_links(true);
+    if !recursive {
+        walker = walker.max_depth(1);
+    }
+
+    let mut paths = Vec::new();
+    for entry in walker {
+        match entry {
+            Ok(entry) => {
+                if entry.file_type().is_file()
+                    && pattern.matches(&entry.file_name().to_string_lossy())
                 {
-                    current_text.push_str(text);
+                    paths.push(entry.path().to_string_lossy().into_owned());
                 }
+            }
+            Err(e) => log::warn!("Skipping {root_dir} entry: {e}"),
+        }
+    }
 
-                if in_body {
-                    full_text_parts.push(text.to_string());
-                }
+    paths.sort();
+    Ok(paths)
+}
+
+/// Convert a single XML file to NDJSON format, replacing `output_path` or with
+/// `append` adding a line to it
+#[pyfunction(signature = (xml_path, output_path, lenient_xml=false, strip_xref=false, normalize_whitespace=false, keep_raw_abstract=false, append=false, sections_filter=None, lowercase=false, unicode_superscripts=false))]
+#[allow(clippy::too_many_arguments)]
+pub fn xml_to_ndjson(
+    xml_path: &str,
+    output_path: &str,
+    lenient_xml: bool,
+    strip_xref: bool,
+    normalize_whitespace: bool,
+    keep_raw_abstract: bool,
+    append: bool,
+    sections_filter: Option<Vec<String>>,
+    lowercase: bool,
+    unicode_superscripts: bool,
+) -> PyResult<()> {
+    let options = ExtractOptions {
+        lenient_xml,
+        strip_xref,
+        normalize_whitespace,
+        keep_raw_abstract,
+        sections_filter,
+        lowercase,
+        unicode_superscripts,
+        ..Default::default()
+    };
+    let xml_content = load_xml(xml_path, lenient_xml)?;
+    let metadata = extract_article_metadata_with_options(&xml_content, xml_path, &options)?;
+    let json_line = serde_json::to_string(&metadata).map_err(XmlProcessorError::from)?;
+
+    let mut output_file = open_ndjson_output(output_path, append).map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to create output file: {e}"))
+    })?;
+
+    writeln!(output_file, "{json_line}").map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to write to output file: {e}"))
+    })?;
+
+    Ok(())
+}
+
+/// Version of the NDJSON record layout written by this module, to check
+/// records against the `schema_version` they carry
+#[pyfunction]
+pub fn current_schema_version() -> u32 {
+    SCHEMA_VERSION
+}
+
+/// Normalize identifiers the way extraction does, to join records from other
+/// sources against extracted ones: `(pmid, doi, pmc_id)`, each `None` when
+/// missing or malformed
+#[pyfunction(signature = (pmid=None, doi=None, pmc_id=None))]
+pub fn normalize_identifiers(
+    pmid: Option<&str>,
+    doi: Option<&str>,
+    pmc_id: Option<&str>,
+) -> (Option<String>, Option<String>, Option<String>) {
+    (
+        pmid.and_then(normalize_pmid),
+        doi.and_then(normalize_doi),
+        pmc_id.and_then(normalize_pmc_id),
+    )
+}
+
+/// Path of the `chunk_index`-th rotated output file (`out.ndjson` -> `out.00003.ndjson`)
+fn rotated_output_path(output_path: &str, chunk_index: usize) -> String {
+    let path = std::path::Path::new(output_path);
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let file_name = match path.extension() {
+        Some(ext) => format!("{stem}.{chunk_index:05}.{}", ext.to_string_lossy()),
+        None => format!("{stem}.{chunk_index:05}"),
+    };
+    path.with_file_name(file_name)
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Number of files read and parsed in parallel before their records are
+/// written, which bounds how many extracted articles are held in memory
+const PARALLEL_BATCH_SIZE: usize = 1024;
+
+/// Options for the batch NDJSON writers
+#[derive(Default)]
+struct BatchOptions {
+    /// Flush (and with `rotate_output`, switch files) every N input files
+    chunk_files: Option<usize>,
+    rotate_output: bool,
+    /// Add to existing output files instead of replacing them
+    append: bool,
+    extract: ExtractOptions,
+    /// Size of a dedicated rayon pool; `None` uses the global pool
+    num_threads: Option<usize>,
+    /// Python callable invoked with the number of files processed so far
+    progress: Option<PyObject>,
+    report_every: usize,
+    /// Identifier (one of `DEDUPE_KEYS`) whose repeats are not written again
+    dedupe_by: Option<String>,
+    /// Publication-year window; records outside it are not written
+    min_year: Option<i32>,
+    max_year: Option<i32>,
+    /// Whether a record without a parseable publication year is written when
+    /// a year window is set
+    keep_undated: bool,
+}
+
+/// Identifiers `batch_xml_to_ndjson` can deduplicate records by
+const DEDUPE_KEYS: &[&str] = &["doi", "pmid", "pmc_id"];
+
+/// The `key` identifier of an article, if it has one
+fn article_identifier<'a>(metadata: &'a ArticleMetadata, key: &str) -> Option<&'a str> {
+    match key {
+        "doi" => metadata.doi.as_deref(),
+        "pmid" => metadata.pmid.as_deref(),
+        "pmc_id" => metadata.pmc_id.as_deref(),
+        _ => None,
+    }
+}
+
+impl BatchOptions {
+    /// Call the progress callback if `files_done` is a reporting point. The
+    /// callback is Python, so the GIL is re-acquired around the call; an
+    /// exception raised by it is returned so the run can be aborted.
+    fn report_progress(&self, files_done: usize) -> PyResult<()> {
+        let Some(progress) = &self.progress else {
+            return Ok(());
+        };
+        if !files_done.is_multiple_of(self.report_every.max(1)) {
+            return Ok(());
+        }
+        Python::with_gil(|py| progress.call1(py, (files_done,)).map(|_| ()))
+    }
+
+    /// Whether `metadata` falls inside the `min_year`/`max_year` window, going
+    /// by the year of its `publication_date`
+    fn in_year_range(&self, metadata: &ArticleMetadata) -> bool {
+        if self.min_year.is_none() && self.max_year.is_none() {
+            return true;
+        }
+        let year = metadata
+            .publication_date
+            .as_deref()
+            .and_then(|date| date.get(..4))
+            .and_then(|year| year.parse::<i32>().ok());
+        match year {
+            Some(year) => {
+                self.min_year.is_none_or(|min| year >= min)
+                    && self.max_year.is_none_or(|max| year <= max)
             }
-            Ok(Event::End(ref e)) => {
-                match e.name().as_ref() {
-                    b"front" => {
-                        in_front_matter = false;
-                    }
-                    b"article-title" => {
-                        if in_title && in_front_matter && !title_extracted {
-                            let trimmed = current_text.trim();
-                            if !trimmed.is_empty() {
-                                metadata.title = Some(trimmed.to_string());
-                                title_extracted = true;
-                            }
-                            current_text.clear();
-                            in_title = false;
-                        }
-                    }
-                    b"abstract" => {
-                        if in_abstract && in_front_matter {
-                            let trimmed = current_text.trim();
-                            if !trimmed.is_empty() {
-                                metadata.abstract_text = Some(trimmed.to_string());
-                            }
-                            current_text.clear();
-                            in_abstract = false;
-                        }
-                    }
-                    b"contrib" => {
-                        if in_contrib && in_front_matter {
-                            // Construct author name from surname and given names
-                            let surname = current_surname.trim();
-                            let given_names = current_given_names.trim();
-
-                            if !surname.is_empty() || !given_names.is_empty() {
-                                let author_name = if !surname.is_empty() && !given_names.is_empty()
-                                {
-                                    format!("{surname}, {given_names}")
-                                } else if !surname.is_empty() {
-                                    surname.to_string()
-                                } else {
-                                    given_names.to_string()
-                                };
-
-                                metadata.authors.push(author_name);
-                            }
+            None => self.keep_undated,
+        }
+    }
+}
 
-                            in_contrib = false;
-                            current_surname.clear();
-                            current_given_names.clear();
-                        }
-                    }
-                    b"surname" => {
-                        in_surname = false;
-                    }
-                    b"given-names" => {
-                        in_given_names = false;
-                    }
-                    b"journal-title" => {
-                        if in_journal && in_front_matter {
-                            let trimmed = current_text.trim();
-                            if !trimmed.is_empty() {
-                                metadata.journal = Some(trimmed.to_string());
-                            }
-                            current_text.clear();
-                            in_journal = false;
-                        }
-                    }
-                    b"pub-date" => {
-                        if in_pub_date && in_front_matter {
-                            // Construct publication date from year, month, day
-                            let year = current_year.trim();
-                            let month = current_month.trim();
-                            let day = current_day.trim();
-
-                            if !year.is_empty() {
-                                let mut date_parts = vec![year];
-                                if !month.is_empty() {
-                                    // Convert month name to number if needed
-                                    let month_num = match month.to_lowercase().as_str() {
-                                        "january" | "jan" => "01",
-                                        "february" | "feb" => "02",
-                                        "march" | "mar" => "03",
-                                        "april" | "apr" => "04",
-                                        "may" => "05",
-                                        "june" | "jun" => "06",
-                                        "july" | "jul" => "07",
-                                        "august" | "aug" => "08",
-                                        "september" | "sep" => "09",
-                                        "october" | "oct" => "10",
-                                        "november" | "nov" => "11",
-                                        "december" | "dec" => "12",
-                                        _ => month, // Assume it's already a number
-                                    };
-                                    date_parts.push(month_num);
-
-                                    if !day.is_empty() {
-                                        date_parts.push(day);
-                                    }
-                                }
+fn ndjson_io_error(e: std::io::Error) -> PyErr {
+    PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to write NDJSON output: {e}"))
+}
 
-                                metadata.publication_date = Some(date_parts.join("-"));
-                            }
+/// Run `op` on a dedicated rayon pool of `num_threads` threads, or on the
+/// global pool when `num_threads` is `None`
+fn run_in_pool<T: Send>(num_threads: Option<usize>, op: impl FnOnce() -> T + Send) -> PyResult<T> {
+    match num_threads {
+        Some(n) => {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(n)
+                .build()
+                .map_err(|e| {
+                    PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
+                        "Failed to create thread pool: {e}"
+                    ))
+                })?;
+            Ok(pool.install(op))
+        }
+        None => Ok(op()),
+    }
+}
+
+/// Write one NDJSON line per XML file, flushing the output every `chunk_files`
+/// input files so a crash loses at most one chunk of work. With `rotate_output`
+/// every chunk goes to its own numbered file next to `output_path`.
+///
+/// Files are read and parsed in parallel with rayon, but records are written
+/// from this thread in input order, so the output lines are in the same order
+/// as `xml_paths` regardless of thread count. Records outside the
+/// `min_year`/`max_year` window are skipped. With `dedupe_by`, a record whose
+/// identifier was already written is skipped too (the first one in input
+/// order wins); records without that identifier are always written.
+/// Returns the number of records written per chunk, the number skipped, and
+/// the paths of the files left unread for exceeding `extract.max_bytes`.
+fn write_ndjson_chunks(
+    xml_paths: &[String],
+    output_path: &str,
+    options: &BatchOptions,
+) -> PyResult<(Vec<usize>, usize, Vec<String>)> {
+    run_in_pool(options.num_threads, || {
+        let chunk_size = options
+            .chunk_files
+            .filter(|&n| n > 0)
+            .unwrap_or_else(|| xml_paths.len().max(1));
+
+        let first_path = if options.rotate_output {
+            rotated_output_path(output_path, 0)
+        } else {
+            output_path.to_string()
+        };
+        let mut output_file = BufWriter::new(
+            open_ndjson_output(&first_path, options.append).map_err(ndjson_io_error)?,
+        );
+        let mut chunk_counts = Vec::new();
+        let mut files_done = 0;
+        let mut seen_ids: HashSet<String> = HashSet::new();
+        let mut skipped_count = 0;
+        let mut oversized_paths = Vec::new();
 
-                            in_pub_date = false;
-                            current_year.clear();
-                            current_month.clear();
-                            current_day.clear();
+        for (chunk_index, chunk) in xml_paths.chunks(chunk_size).enumerate() {
+            if options.rotate_output && chunk_index > 0 {
+                output_file = BufWriter::new(
+                    open_ndjson_output(
+                        &rotated_output_path(output_path, chunk_index),
+                        options.append,
+                    )
+                    .map_err(ndjson_io_error)?,
+                );
+            }
+
+            let mut processed_count = 0;
+            for batch in chunk.chunks(PARALLEL_BATCH_SIZE) {
+                // par_iter + collect keeps results in input order
+                let results: Vec<_> = batch
+                    .par_iter()
+                    .map(|xml_path| read_and_extract(xml_path, &options.extract))
+                    .collect();
+
+                for (xml_path, result) in batch.iter().zip(results) {
+                    let result = result.map(|metadata| {
+                        if !options.in_year_range(&metadata) {
+                            return None;
                         }
-                    }
-                    b"year" => {
-                        in_year = false;
-                    }
-                    b"month" => {
-                        in_month = false;
-                    }
-                    b"day" => {
-                        in_day = false;
-                    }
-                    b"article-id" => {
-                        if in_front_matter {
-                            let text_content = current_text.trim();
-                            if in_pmid && !text_content.is_empty() {
-                                metadata.pmid = Some(text_content.to_string());
-                                in_pmid = false;
-                            } else if in_pmc_id && !text_content.is_empty() {
-                                if text_content.starts_with("PMC") {
-                                    metadata.pmc_id = Some(text_content.to_string());
-                                } else {
-                                    metadata.pmc_id = Some(format!("PMC{text_content}"));
+                        let id = options
+                            .dedupe_by
+                            .as_deref()
+                            .and_then(|key| article_identifier(&metadata, key));
+                        match id {
+                            Some(id) if !seen_ids.insert(id.to_string()) => None,
+                            _ => Some(metadata),
+                        }
+                    });
+                    match result {
+                        Ok(None) => skipped_count += 1,
+                        Ok(Some(metadata)) => match serde_json::to_string(&metadata) {
+                            Ok(json_line) => {
+                                if writeln!(output_file, "{json_line}").is_ok() {
+                                    processed_count += 1;
                                 }
-                                in_pmc_id = false;
-                            } else if in_doi && !text_content.is_empty() {
-                                metadata.doi = Some(text_content.to_string());
-                                in_doi = false;
                             }
-                            current_text.clear();
+                            Err(e) => {
+                                log::error!("Failed to serialize metadata for {xml_path}: {e}")
+                            }
+                        },
+                        Err(e) => {
+                            e.report(xml_path);
+                            if matches!(e, XmlProcessorError::TooLarge { .. }) {
+                                oversized_paths.push(xml_path.clone());
+                            }
                         }
                     }
-                    b"body" => {
-                        in_body = false;
+
+                    files_done += 1;
+                    if let Err(e) = options.report_progress(files_done) {
+                        // Keep what was written so far before aborting
+                        let _ = output_file.flush();
+                        return Err(e);
                     }
-                    _ => {}
                 }
             }
-            Ok(Event::Eof) => break,
-            Err(e) => return Err(anyhow::anyhow!("Error parsing XML: {}", e)),
-            _ => {}
+
+            output_file.flush().map_err(ndjson_io_error)?;
+            chunk_counts.push(processed_count);
         }
-        buf.clear();
-    }
 
-    if !full_text_parts.is_empty() {
-        metadata.full_text = Some(full_text_parts.join(" "));
+        output_file.flush().map_err(ndjson_io_error)?;
+        Ok((chunk_counts, skipped_count, oversized_paths))
+    })?
+}
+
+/// Convert multiple XML files to a single NDJSON file, returning the number of
+/// records written. With `dedupe_by`, records whose identifier was already
+/// written are left out, as are, with `min_year`/`max_year`, those published
+/// outside that window; `batch_xml_to_ndjson_with_stats` also returns how many
+/// were. Files larger than `max_bytes` are not read. With `append` the records
+/// are added after those already in the output, and the count is of the new
+/// records only. A file that fails with an I/O error is tried again up to
+/// `retries` times, `retry_delay_ms` apart, before it is reported. At most
+/// `max_open_files` files are read at once while parsing runs on every thread.
+#[pyfunction(signature = (xml_paths, output_path, chunk_files=None, rotate_output=false, lenient_xml=false, num_threads=None, progress=None, report_every=1000, strip_xref=false, normalize_whitespace=false, dedupe_by=None, min_year=None, max_year=None, keep_undated=true, keep_raw_abstract=false, max_bytes=None, append=false, sections_filter=None, retries=0, retry_delay_ms=100, lowercase=false, unicode_superscripts=false, max_open_files=None))]
+#[allow(clippy::too_many_arguments)]
+pub fn batch_xml_to_ndjson(
+    py: Python,
+    xml_paths: Vec<String>,
+    output_path: &str,
+    chunk_files: Option<usize>,
+    rotate_output: bool,
+    lenient_xml: bool,
+    num_threads: Option<usize>,
+    progress: Option<PyObject>,
+    report_every: usize,
+    strip_xref: bool,
+    normalize_whitespace: bool,
+    dedupe_by: Option<String>,
+    min_year: Option<i32>,
+    max_year: Option<i32>,
+    keep_undated: bool,
+    keep_raw_abstract: bool,
+    max_bytes: Option<u64>,
+    append: bool,
+    sections_filter: Option<Vec<String>>,
+    retries: u32,
+    retry_delay_ms: u64,
+    lowercase: bool,
+    unicode_superscripts: bool,
+    max_open_files: Option<usize>,
+) -> PyResult<usize> {
+    batch_xml_to_ndjson_with_stats(
+        py,
+        xml_paths,
+        output_path,
+        chunk_files,
+        rotate_output,
+        lenient_xml,
+        num_threads,
+        progress,
+        report_every,
+        strip_xref,
+        normalize_whitespace,
+        dedupe_by,
+        min_year,
+        max_year,
+        keep_undated,
+        keep_raw_abstract,
+        max_bytes,
+        append,
+        sections_filter,
+        retries,
+        retry_delay_ms,
+        lowercase,
+        unicode_superscripts,
+        max_open_files,
+    )
+    .map(|(written, _, _)| written)
+}
+
+/// `batch_xml_to_ndjson`, returning `(written, skipped, oversized_paths)`:
+/// the records written, those left out as duplicates or outside the year
+/// window, and the files not read for being larger than `max_bytes`
+#[pyfunction(signature = (xml_paths, output_path, chunk_files=None, rotate_output=false, lenient_xml=false, num_threads=None, progress=None, report_every=1000, strip_xref=false, normalize_whitespace=false, dedupe_by=None, min_year=None, max_year=None, keep_undated=true, keep_raw_abstract=false, max_bytes=None, append=false, sections_filter=None, retries=0, retry_delay_ms=100, lowercase=false, unicode_superscripts=false, max_open_files=None))]
+#[allow(clippy::too_many_arguments)]
+pub fn batch_xml_to_ndjson_with_stats(
+    py: Python,
+    xml_paths: Vec<String>,
+    output_path: &str,
+    chunk_files: Option<usize>,
+    rotate_output: bool,
+    lenient_xml: bool,
+    num_threads: Option<usize>,
+    progress: Option<PyObject>,
+    report_every: usize,
+    strip_xref: bool,
+    normalize_whitespace: bool,
+    dedupe_by: Option<String>,
+    min_year: Option<i32>,
+    max_year: Option<i32>,
+    keep_undated: bool,
+    keep_raw_abstract: bool,
+    max_bytes: Option<u64>,
+    append: bool,
+    sections_filter: Option<Vec<String>>,
+    retries: u32,
+    retry_delay_ms: u64,
+    lowercase: bool,
+    unicode_superscripts: bool,
+    max_open_files: Option<usize>,
+) -> PyResult<(usize, usize, Vec<String>)> {
+    if let Some(key) = &dedupe_by {
+        if !DEDUPE_KEYS.contains(&key.as_str()) {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Unknown dedupe_by identifier '{key}' (expected one of {})",
+                DEDUPE_KEYS.join(", ")
+            )));
+        }
     }
 
-    Ok(metadata)
+    let options = BatchOptions {
+        chunk_files,
+        rotate_output,
+        append,
+        extract: ExtractOptions {
+            lenient_xml,
+            strip_xref,
+            normalize_whitespace,
+            keep_raw_abstract,
+            max_bytes,
+            sections_filter,
+            retries,
+            retry_delay_ms,
+            lowercase,
+            unicode_superscripts,
+            max_open_files,
+            ..Default::default()
+        },
+        num_threads,
+        progress,
+        report_every,
+        dedupe_by,
+        min_year,
+        max_year,
+        keep_undated,
+    };
+
+    let (chunk_counts, skipped_count, oversized_paths) =
+        py.allow_threads(|| write_ndjson_chunks(&xml_paths, output_path, &options))?;
+    Ok((chunk_counts.iter().sum(), skipped_count, oversized_paths))
 }
-/// Convert a single XML file to NDJSON format
-#[pyfunction]
-pub fn xml_to_ndjson(xml_path: &str, output_path: &str) -> PyResult<()> {
-    let xml_content = std::fs::read_to_string(xml_path).map_err(|e| {
-        PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to read XML file: {e}"))
-    })?;
 
-    let metadata = extract_article_metadata(&xml_content, xml_path).map_err(|e| {
-        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to extract metadata: {e}"))
-    })?;
+/// Convert multiple XML files to NDJSON in chunks of `chunk_files` files,
+/// returning the number of records written per chunk. Files larger than
+/// `max_bytes` are not read; I/O errors are retried as in
+/// `batch_xml_to_ndjson`.
+#[pyfunction(signature = (xml_paths, output_path, chunk_files, rotate_output=false, lenient_xml=false, num_threads=None, strip_xref=false, normalize_whitespace=false, max_bytes=None, retries=0, retry_delay_ms=100))]
+#[allow(clippy::too_many_arguments)]
+pub fn batch_xml_to_ndjson_chunked(
+    py: Python,
+    xml_paths: Vec<String>,
+    output_path: &str,
+    chunk_files: usize,
+    rotate_output: bool,
+    lenient_xml: bool,
+    num_threads: Option<usize>,
+    strip_xref: bool,
+    normalize_whitespace: bool,
+    max_bytes: Option<u64>,
+    retries: u32,
+    retry_delay_ms: u64,
+) -> PyResult<Vec<usize>> {
+    let options = BatchOptions {
+        chunk_files: Some(chunk_files),
+        rotate_output,
+        extract: ExtractOptions {
+            lenient_xml,
+            strip_xref,
+            normalize_whitespace,
+            max_bytes,
+            retries,
+            retry_delay_ms,
+            ..Default::default()
+        },
+        num_threads,
+        ..Default::default()
+    };
 
-    let json_line = serde_json::to_string(&metadata).map_err(|e| {
-        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to serialize to JSON: {e}"))
-    })?;
+    py.allow_threads(|| write_ndjson_chunks(&xml_paths, output_path, &options))
+        .map(|(chunk_counts, _, _)| chunk_counts)
+}
+
+/// Convert multiple XML files to a single NDJSON file and return the tallies
+/// of the run as a dict: `total_files`, `succeeded`, `read_failures`,
+/// `parse_failures`, `empty_documents` and `bytes_read`
+#[pyfunction(signature = (xml_paths, output_path, lenient_xml=false, strip_xref=false, normalize_whitespace=false, max_bytes=None, retries=0, retry_delay_ms=100))]
+#[allow(clippy::too_many_arguments)]
+pub fn batch_xml_to_ndjson_with_summary<'py>(
+    py: Python<'py>,
+    xml_paths: Vec<String>,
+    output_path: &str,
+    lenient_xml: bool,
+    strip_xref: bool,
+    normalize_whitespace: bool,
+    max_bytes: Option<u64>,
+    retries: u32,
+    retry_delay_ms: u64,
+) -> PyResult<Bound<'py, PyDict>> {
+    let options = ExtractOptions {
+        lenient_xml,
+        strip_xref,
+        normalize_whitespace,
+        max_bytes,
+        retries,
+        retry_delay_ms,
+        ..Default::default()
+    };
 
-    let mut output_file = File::create(output_path).map_err(|e| {
+    let output_file = File::create(output_path).map_err(|e| {
         PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to create output file: {e}"))
     })?;
 
-    writeln!(output_file, "{json_line}").map_err(|e| {
-        PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to write to output file: {e}"))
+    let summary = py.allow_threads(|| {
+        write_ndjson_with_summary(&xml_paths, &mut BufWriter::new(output_file), &options)
     })?;
 
-    Ok(())
+    let dict = PyDict::new(py);
+    dict.set_item("total_files", summary.total_files)?;
+    dict.set_item("succeeded", summary.succeeded)?;
+    dict.set_item("read_failures", summary.read_failures)?;
+    dict.set_item("parse_failures", summary.parse_failures)?;
+    dict.set_item("empty_documents", summary.empty_documents)?;
+    dict.set_item("bytes_read", summary.bytes_read)?;
+    Ok(dict)
 }
 
-/// Convert multiple XML files to a single NDJSON file
-#[pyfunction]
-pub fn batch_xml_to_ndjson(
-    py: Python, // <‑‑ new
+/// Convert multiple XML files to a single NDJSON file and report the outcome
+/// of every input file, so failures can be filtered and retried from Python.
+/// Files larger than `max_bytes` are not read and get the status `too_large`;
+/// I/O errors are retried as in `batch_xml_to_ndjson`.
+#[pyfunction(signature = (xml_paths, output_path, lenient_xml=false, strip_xref=false, normalize_whitespace=false, max_bytes=None, retries=0, retry_delay_ms=100))]
+#[allow(clippy::too_many_arguments)]
+pub fn batch_xml_to_ndjson_with_report(
+    py: Python,
     xml_paths: Vec<String>,
     output_path: &str,
-) -> PyResult<usize> {
-    let result: std::result::Result<usize, _> = py.allow_threads(|| {
-        let mut output_file: File = File::create(output_path).map_err(|e: std::io::Error| {
-            PyErr::new::<pyo3::exceptions::PyIOError, _>(format!(
-                "Failed to create output file: {e}"
-            ))
-        })?;
-
-        let mut processed_count = 0;
+    lenient_xml: bool,
+    strip_xref: bool,
+    normalize_whitespace: bool,
+    max_bytes: Option<u64>,
+    retries: u32,
+    retry_delay_ms: u64,
+) -> PyResult<PyDataFrame> {
+    let options = ExtractOptions {
+        lenient_xml,
+        strip_xref,
+        normalize_whitespace,
+        max_bytes,
+        retries,
+        retry_delay_ms,
+        ..Default::default()
+    };
+    let result = py.allow_threads(|| -> std::io::Result<_> {
+        let mut output_file = BufWriter::new(File::create(output_path)?);
+        let mut statuses = Vec::with_capacity(xml_paths.len());
+        let mut error_messages = Vec::with_capacity(xml_paths.len());
 
         for xml_path in &xml_paths {
-            match std::fs::read_to_string(xml_path) {
-                Ok(xml_content) => match extract_article_metadata(&xml_content, xml_path) {
-                    Ok(metadata) => match serde_json::to_string(&metadata) {
-                        Ok(json_line) => {
-                            if writeln!(output_file, "{json_line}").is_ok() {
-                                processed_count += 1;
-                            }
-                        }
-                        Err(e) => eprintln!("Failed to serialize metadata for {xml_path}: {e}"),
-                    },
-                    Err(e) => eprintln!("Failed to extract metadata from {xml_path}: {e}"),
-                },
-                Err(e) => eprintln!("Failed to read {xml_path}: {e}"),
+            let json_line = read_and_extract(xml_path, &options).and_then(|metadata| {
+                let json_line = serde_json::to_string(&metadata)?;
+                Ok((json_line, metadata.is_empty))
+            });
+            match json_line {
+                Ok((json_line, is_empty)) => {
+                    writeln!(output_file, "{json_line}")?;
+                    statuses.push(if is_empty { "empty" } else { "ok" });
+                    error_messages.push(None);
+                }
+                Err(e) => {
+                    statuses.push(e.status());
+                    error_messages.push(Some(e.to_string()));
+                }
             }
         }
 
-        Ok(processed_count)
+        output_file.flush()?;
+        Ok((statuses, error_messages))
     });
 
-    // Explicit, **Send + Sync** error type so the closure satisfies the
-    // `Ungil` requirement of `allow_threads`.
-    result.map_err(|e: Box<dyn std::error::Error + Send + Sync>| {
-        PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{:?}", e))
+    let (statuses, error_messages) = result.map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to write NDJSON output: {e}"))
+    })?;
+
+    let df = df! {
+        "file_path" => &xml_paths,
+        "status" => &statuses,
+        "error_message" => &error_messages,
+    }
+    .map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Failed to create report DataFrame: {e}"
+        ))
+    })?;
+
+    Ok(PyDataFrame(df))
+}
+
+/// Convert every `.xml`/`.nxml` member of a `.tar.gz` archive (such as a PMC
+/// OA bundle) to one NDJSON line, reading the archive as a stream instead of
+/// extracting it. `file_path` is the member's path inside the archive; members
+/// that can't be parsed, or are larger than `max_bytes`, are logged and
+/// skipped. Opening the archive is retried `retries` times on an I/O error.
+/// Returns the number written.
+#[pyfunction(signature = (archive_path, output_path, lenient_xml=false, strip_xref=false, normalize_whitespace=false, max_bytes=None, retries=0, retry_delay_ms=100))]
+#[allow(clippy::too_many_arguments)]
+pub fn batch_xml_to_ndjson_from_archive(
+    py: Python,
+    archive_path: &str,
+    output_path: &str,
+    lenient_xml: bool,
+    strip_xref: bool,
+    normalize_whitespace: bool,
+    max_bytes: Option<u64>,
+    retries: u32,
+    retry_delay_ms: u64,
+) -> PyResult<usize> {
+    let options = ExtractOptions {
+        lenient_xml,
+        strip_xref,
+        normalize_whitespace,
+        max_bytes,
+        retries,
+        retry_delay_ms,
+        ..Default::default()
+    };
+    let mut output_file = BufWriter::new(File::create(output_path).map_err(ndjson_io_error)?);
+
+    py.allow_threads(|| {
+        let mut written = 0;
+        let mut write_error = None;
+        for_each_archive_article(archive_path, &options, |member_path, metadata| {
+            if write_error.is_some() {
+                return;
+            }
+            let json_line = metadata.and_then(|metadata| {
+                serde_json::to_string(&metadata).map_err(XmlProcessorError::from)
+            });
+            match json_line {
+                Ok(json_line) => match writeln!(output_file, "{json_line}") {
+                    Ok(()) => written += 1,
+                    Err(e) => write_error = Some(e),
+                },
+                Err(e) => e.report(member_path),
+            }
+        })
+        .map_err(|e| e.into_py_err(&format!("Failed to read archive {archive_path}")))?;
+
+        if let Some(e) = write_error {
+            return Err(ndjson_io_error(e));
+        }
+        output_file.flush().map_err(ndjson_io_error)?;
+        Ok(written)
     })
 }
 
-/// Read XML files (list of strings for paths) directly into a Polars DataFrame
-#[pyfunction(signature = (xml_paths))]
-pub fn xml_to_polars(py: Python, xml_paths: Vec<String>) -> PyResult<PyDataFrame> {
-    let result = py.allow_threads(|| {
-        let mut pmids = Vec::new();
-        let mut pmc_ids = Vec::new();
-        let mut titles = Vec::new();
-        let mut abstracts = Vec::new();
-        let mut journals = Vec::new();
-        let mut full_texts = Vec::new();
+/// NDJSON writer fed one path at a time, for pipelines that discover files
+/// lazily instead of having the full path list up front. Each `write_file`
+/// appends (and flushes) one line.
+#[pyclass(module = "xml_processor.nxml")]
+pub struct XmlNdjsonWriter {
+    /// `None` once closed
+    output_file: Option<BufWriter<File>>,
+    written: usize,
+    options: ExtractOptions,
+}
 
-        for xml_path in &xml_paths {
-            match std::fs::read_to_string(xml_path) {
-                Ok(xml_content) => {
-                    match extract_article_metadata(&xml_content, xml_path) {
-                        Ok(metadata) => {
-                            pmids.push(metadata.pmid);
-                            pmc_ids.push(metadata.pmc_id);
-                            titles.push(metadata.title);
-                            abstracts.push(metadata.abstract_text);
-                            journals.push(metadata.journal);
-                            full_texts.push(metadata.full_text);
-                        }
-                        Err(e) => {
-                            eprintln!("Failed to extract metadata from {xml_path}: {e}");
-                            // Add None values to maintain alignment
-                            pmids.push(None);
-                            pmc_ids.push(None);
-                            titles.push(None);
-                            abstracts.push(None);
-                            journals.push(None);
-                            full_texts.push(None);
-                        }
-                    }
-                }
-                Err(e) => {
-                    eprintln!("Failed to read {xml_path}: {e}");
-                    // Add None values to maintain alignment
-                    pmids.push(None);
-                    pmc_ids.push(None);
-                    titles.push(None);
-                    abstracts.push(None);
-                    journals.push(None);
-                    full_texts.push(None);
-                }
+#[pymethods]
+impl XmlNdjsonWriter {
+    /// Create (or truncate) `output_path` and return a writer for it; a file
+    /// that fails with an I/O error is tried again up to `retries` times
+    #[staticmethod]
+    #[pyo3(signature = (output_path, lenient_xml=false, strip_xref=false, normalize_whitespace=false, retries=0, retry_delay_ms=100))]
+    fn open(
+        output_path: &str,
+        lenient_xml: bool,
+        strip_xref: bool,
+        normalize_whitespace: bool,
+        retries: u32,
+        retry_delay_ms: u64,
+    ) -> PyResult<Self> {
+        Ok(Self {
+            output_file: Some(BufWriter::new(
+                File::create(output_path).map_err(ndjson_io_error)?,
+            )),
+            written: 0,
+            options: ExtractOptions {
+                lenient_xml,
+                strip_xref,
+                normalize_whitespace,
+                retries,
+                retry_delay_ms,
+                ..Default::default()
+            },
+        })
+    }
+
+    /// Extract `xml_path` and append its record; `False` (after logging why)
+    /// if the file couldn't be read or parsed
+    fn write_file(&mut self, py: Python, xml_path: &str) -> PyResult<bool> {
+        let options = self.options.clone();
+        let output_file = self.output_file.as_mut().ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>("XmlNdjsonWriter is closed")
+        })?;
+
+        let json_line = py.allow_threads(|| {
+            read_and_extract(xml_path, &options).and_then(|metadata| {
+                serde_json::to_string(&metadata).map_err(XmlProcessorError::from)
+            })
+        });
+        match json_line {
+            Ok(json_line) => {
+                writeln!(output_file, "{json_line}").map_err(ndjson_io_error)?;
+                output_file.flush().map_err(ndjson_io_error)?;
+                self.written += 1;
+                Ok(true)
+            }
+            Err(e) => {
+                e.report(xml_path);
+                Ok(false)
             }
         }
+    }
 
-        df! {
-            "pmid" => &pmids,
-            "pmc_id" => &pmc_ids,
-            "title" => &titles,
-            "abstract" => &abstracts,
-            "journal" => &journals,
-            "full_text" => &full_texts,
+    /// Flush and close the file, returning the number of records written;
+    /// closing again is a no-op
+    fn close(&mut self) -> PyResult<usize> {
+        if let Some(mut output_file) = self.output_file.take() {
+            output_file.flush().map_err(ndjson_io_error)?;
         }
+        Ok(self.written)
+    }
+}
+
+/// Read XML files (list of strings for paths) directly into a Polars DataFrame;
+/// with `columns`, only those columns are built. At most `max_open_files`
+/// files are read at once.
+#[pyfunction(signature = (xml_paths, lenient_xml=false, strip_xref=false, normalize_whitespace=false, keep_raw_abstract=false, sections_filter=None, lowercase=false, unicode_superscripts=false, columns=None, max_open_files=None))]
+#[allow(clippy::too_many_arguments)]
+pub fn xml_to_polars(
+    py: Python,
+    xml_paths: Vec<String>,
+    lenient_xml: bool,
+    strip_xref: bool,
+    normalize_whitespace: bool,
+    keep_raw_abstract: bool,
+    sections_filter: Option<Vec<String>>,
+    lowercase: bool,
+    unicode_superscripts: bool,
+    columns: Option<Vec<String>>,
+    max_open_files: Option<usize>,
+) -> PyResult<PyDataFrame> {
+    let options = ExtractOptions {
+        lenient_xml,
+        strip_xref,
+        normalize_whitespace,
+        keep_raw_abstract,
+        sections_filter,
+        lowercase,
+        unicode_superscripts,
+        max_open_files,
+        ..Default::default()
+    };
+    let result = py.allow_threads(|| match &columns {
+        Some(columns) => articles_to_dataframe_with_columns(&xml_paths, &options, columns),
+        None => articles_to_dataframe_with_options(&xml_paths, &options),
     });
 
     let df = result.map_err(|e| {
@@ -485,61 +811,1994 @@ pub fn xml_to_polars(py: Python, xml_paths: Vec<String>) -> PyResult<PyDataFrame
     Ok(PyDataFrame(df))
 }
 
-/// Search for patterns in XML content and return matching articles
-#[pyfunction(signature = (xml_paths, patterns, case_sensitive=None))]
-pub fn search_xml_content(
+/// Report which of `required_fields` (see `VALIDATABLE_FIELDS`) each XML file
+/// provides, without writing any output
+#[pyfunction(signature = (xml_paths, required_fields, lenient_xml=false))]
+pub fn validate_xml(
+    py: Python,
     xml_paths: Vec<String>,
-    patterns: Vec<String>,
-    case_sensitive: Option<bool>,
+    required_fields: Vec<String>,
+    lenient_xml: bool,
 ) -> PyResult<PyDataFrame> {
-    let case_sensitive = case_sensitive.unwrap_or(false);
-    let mut regex_patterns = Vec::new();
+    if let Some(field) = required_fields
+        .iter()
+        .find(|field| !VALIDATABLE_FIELDS.contains(&field.as_str()))
+    {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Unknown field '{field}' (expected one of {})",
+            VALIDATABLE_FIELDS.join(", ")
+        )));
+    }
 
-    // Compile regex patterns
-    for pattern in &patterns {
-        let regex = if case_sensitive {
-            Regex::new(pattern)
-        } else {
-            Regex::new(&format!("(?i){pattern}"))
+    let options = ExtractOptions {
+        lenient_xml,
+        ..Default::default()
+    };
+    let result = py.allow_threads(|| validate_articles(&xml_paths, &required_fields, &options));
+
+    let df = result.map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Failed to create validation DataFrame: {e}"
+        ))
+    })?;
+
+    Ok(PyDataFrame(df))
+}
+
+/// Iterator returned by `xml_to_polars_chunks`; each step reads the next
+/// `chunk_size` files into a fresh set of columns, so only one chunk's
+/// articles are held in memory at a time
+#[pyclass(module = "xml_processor.nxml")]
+pub struct ArticleChunks {
+    xml_paths: Vec<String>,
+    next_index: usize,
+    chunk_size: usize,
+    options: ExtractOptions,
+}
+
+#[pymethods]
+impl ArticleChunks {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(mut slf: PyRefMut<'_, Self>, py: Python) -> PyResult<Option<PyDataFrame>> {
+        let start = slf.next_index;
+        if start >= slf.xml_paths.len() {
+            return Ok(None);
         }
-        .map_err(|e| {
-            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
-                "Invalid regex pattern '{pattern}': {e}"
-            ))
-        })?;
+        let end = (start + slf.chunk_size).min(slf.xml_paths.len());
+        let chunk_paths = slf.xml_paths[start..end].to_vec();
+        let options = slf.options.clone();
+        slf.next_index = end;
 
-        regex_patterns.push(regex);
+        let df = py
+            .allow_threads(|| articles_to_dataframe_with_options(&chunk_paths, &options))
+            .map_err(|e| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "Failed to create DataFrame: {e}"
+                ))
+            })?;
+
+        Ok(Some(PyDataFrame(df)))
     }
+}
 
-    let mut matched_file_paths = Vec::new();
-    let mut matched_patterns = Vec::new();
-    let mut match_contexts = Vec::new();
+/// Like `xml_to_polars`, but yields DataFrames of at most `chunk_size` rows
+/// (one per path, in order) so that large path lists can be processed with
+/// bounded memory. Every chunk has the full, fixed schema.
+#[pyfunction(signature = (xml_paths, chunk_size, lenient_xml=false, strip_xref=false, normalize_whitespace=false))]
+pub fn xml_to_polars_chunks(
+    xml_paths: Vec<String>,
+    chunk_size: usize,
+    lenient_xml: bool,
+    strip_xref: bool,
+    normalize_whitespace: bool,
+) -> PyResult<ArticleChunks> {
+    if chunk_size == 0 {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "chunk_size must be at least 1",
+        ));
+    }
 
-    for xml_path in &xml_paths {
-        if let Ok(xml_content) = std::fs::read_to_string(xml_path) {
-            for (pattern_idx, regex) in regex_patterns.iter().enumerate() {
-                for mat in regex.find_iter(&xml_content) {
-                    matched_file_paths.push(Some(xml_path.to_string()));
-                    matched_patterns.push(Some(patterns[pattern_idx].clone()));
-
-                    // Extract context around the match (100 chars before and after)
-                    let start = mat.start().saturating_sub(100);
-                    let end = (mat.end() + 100).min(xml_content.len());
-                    let context = xml_content[start..end].to_string();
-                    match_contexts.push(Some(context));
-                }
+    Ok(ArticleChunks {
+        xml_paths,
+        next_index: 0,
+        chunk_size,
+        options: ExtractOptions {
+            lenient_xml,
+            strip_xref,
+            normalize_whitespace,
+            ..Default::default()
+        },
+    })
+}
+
+/// Read NDJSON written by this module back into a DataFrame with the columns of
+/// `xml_to_polars`, without reparsing the XML. Blank lines are ignored and
+/// lines that aren't an article record are skipped and counted.
+#[pyfunction]
+pub fn ndjson_to_polars(py: Python, ndjson_path: &str) -> PyResult<PyDataFrame> {
+    let file = File::open(ndjson_path).map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to read NDJSON file: {e}"))
+    })?;
+
+    let result = py.allow_threads(|| -> PyResult<DataFrame> {
+        let mut columns = ArticleColumns::default();
+        let mut malformed_count = 0;
+
+        for line in BufReader::new(file).lines() {
+            let line = line.map_err(|e| {
+                PyErr::new::<pyo3::exceptions::PyIOError, _>(format!(
+                    "Failed to read NDJSON file: {e}"
+                ))
+            })?;
+            if line.trim().is_empty() {
+                continue;
             }
+            let Ok(metadata) = serde_json::from_str::<ArticleMetadata>(&line) else {
+                malformed_count += 1;
+                continue;
+            };
+            columns.push(Some(metadata)).map_err(|e| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "Failed to create DataFrame: {e}"
+                ))
+            })?;
         }
-    }
 
-    let df = df! {
-        "file_path" => &matched_file_paths,
-        "matched_pattern" => &matched_patterns,
-        "match_context" => &match_contexts,
-    }
-    .map_err(|e| {
-        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
-            "Failed to create search results DataFrame: {e}"
+        if malformed_count > 0 {
+            log::warn!("Skipped {malformed_count} malformed lines in {ndjson_path}");
+        }
+
+        columns.into_dataframe().map_err(|e| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Failed to create DataFrame: {e}"
+            ))
+        })
+    })?;
+
+    Ok(PyDataFrame(result))
+}
+
+/// Parse XML content that is already in memory into a single-row DataFrame with
+/// the same columns as `xml_to_polars`. `file_path_label` is only recorded for
+/// provenance.
+#[pyfunction(signature = (xml_content, file_path_label="", lenient_xml=false, strip_xref=false, normalize_whitespace=false, keep_raw_abstract=false, sections_filter=None, lowercase=false, unicode_superscripts=false))]
+#[allow(clippy::too_many_arguments)]
+pub fn parse_xml_string(
+    py: Python,
+    xml_content: &str,
+    file_path_label: &str,
+    lenient_xml: bool,
+    strip_xref: bool,
+    normalize_whitespace: bool,
+    keep_raw_abstract: bool,
+    sections_filter: Option<Vec<String>>,
+    lowercase: bool,
+    unicode_superscripts: bool,
+) -> PyResult<PyDataFrame> {
+    let options = ExtractOptions {
+        lenient_xml,
+        strip_xref,
+        normalize_whitespace,
+        keep_raw_abstract,
+        sections_filter,
+        lowercase,
+        unicode_superscripts,
+        ..Default::default()
+    };
+    let result = py.allow_threads(|| {
+        let metadata = if lenient_xml {
+            extract_article_metadata_with_options(
+                &repair_xml(xml_content).0,
+                file_path_label,
+                &options,
+            )
+        } else {
+            extract_article_metadata_with_options(xml_content, file_path_label, &options)
+        }?;
+
+        let mut columns = ArticleColumns::default();
+        columns
+            .push(Some(metadata))
+            .and_then(|_| columns.into_dataframe())
+            .map_err(|e| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "Failed to create DataFrame: {e}"
+                ))
+            })
+    })?;
+
+    Ok(PyDataFrame(result))
+}
+
+/// Read a single XML file into an `ArticleMetadata` object, for callers that
+/// want one article's fields as attributes rather than a DataFrame row
+#[pyfunction(signature = (xml_path, lenient_xml=false, strip_xref=false, normalize_whitespace=false, keep_raw_abstract=false, sections_filter=None, lowercase=false, unicode_superscripts=false))]
+#[allow(clippy::too_many_arguments)]
+pub fn parse_article(
+    py: Python,
+    xml_path: &str,
+    lenient_xml: bool,
+    strip_xref: bool,
+    normalize_whitespace: bool,
+    keep_raw_abstract: bool,
+    sections_filter: Option<Vec<String>>,
+    lowercase: bool,
+    unicode_superscripts: bool,
+) -> PyResult<ArticleMetadata> {
+    let options = ExtractOptions {
+        lenient_xml,
+        strip_xref,
+        normalize_whitespace,
+        keep_raw_abstract,
+        sections_filter,
+        lowercase,
+        unicode_superscripts,
+        ..Default::default()
+    };
+    Ok(py.allow_threads(|| read_and_extract(xml_path, &options))?)
+}
+
+/// Read every article of a multi-article file (e.g. a PubMed baseline
+/// `<PubmedArticleSet>`) into a DataFrame with the columns of `xml_to_polars`,
+/// one row per article. The file is streamed, one article at a time; articles
+/// longer than `max_bytes` are logged and left out.
+#[pyfunction(signature = (xml_path, strip_xref=false, normalize_whitespace=false, max_bytes=None))]
+pub fn parse_multi_article_xml(
+    py: Python,
+    xml_path: &str,
+    strip_xref: bool,
+    normalize_whitespace: bool,
+    max_bytes: Option<u64>,
+) -> PyResult<PyDataFrame> {
+    let options = ExtractOptions {
+        strip_xref,
+        normalize_whitespace,
+        max_bytes,
+        ..Default::default()
+    };
+    let result = py.allow_threads(|| {
+        let mut columns = ArticleColumns::default();
+        let mut push_result = Ok(());
+        let mut index = 0;
+
+        for_each_article(xml_path, &options, |metadata| {
+            match metadata {
+                Ok(metadata) => {
+                    if push_result.is_ok() {
+                        push_result = columns.push(Some(metadata));
+                    }
+                }
+                Err(e) => log::warn!("Failed to extract article #{index} of {xml_path}: {e}"),
+            }
+            index += 1;
+        })
+        .map_err(|e| e.into_py_err(&format!("Failed to read articles from {xml_path}")))?;
+
+        push_result
+            .and_then(|_| columns.into_dataframe())
+            .map_err(|e| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "Failed to create DataFrame: {e}"
+                ))
+            })
+    })?;
+
+    Ok(PyDataFrame(result))
+}
+
+/// Convert multiple XML files to two NDJSON files: the records without body
+/// text in `abstract_output`, and `{pmid, file_path, full_text}` records in
+/// `fulltext_output` for the articles that have body text. Returns the number
+/// of lines written to each file.
+#[pyfunction(signature = (xml_paths, abstract_output, fulltext_output, lenient_xml=false, strip_xref=false, normalize_whitespace=false))]
+pub fn batch_xml_split_output(
+    py: Python,
+    xml_paths: Vec<String>,
+    abstract_output: &str,
+    fulltext_output: &str,
+    lenient_xml: bool,
+    strip_xref: bool,
+    normalize_whitespace: bool,
+) -> PyResult<(usize, usize)> {
+    let options = ExtractOptions {
+        lenient_xml,
+        strip_xref,
+        normalize_whitespace,
+        ..Default::default()
+    };
+
+    let create = |output_path: &str| {
+        File::create(output_path).map(BufWriter::new).map_err(|e| {
+            PyErr::new::<pyo3::exceptions::PyIOError, _>(format!(
+                "Failed to create output file {output_path}: {e}"
+            ))
+        })
+    };
+    let mut abstract_file = create(abstract_output)?;
+    let mut fulltext_file = create(fulltext_output)?;
+
+    let counts = py.allow_threads(|| {
+        write_split_ndjson(&xml_paths, &mut abstract_file, &mut fulltext_file, &options)
+    })?;
+
+    Ok(counts)
+}
+
+/// Convert multiple XML files to a single JSON array of articles, for
+/// consumers that cannot read NDJSON, returning the number of articles written
+#[pyfunction(signature = (xml_paths, output_path, lenient_xml=false, strip_xref=false, normalize_whitespace=false))]
+pub fn batch_xml_to_json_array(
+    py: Python,
+    xml_paths: Vec<String>,
+    output_path: &str,
+    lenient_xml: bool,
+    strip_xref: bool,
+    normalize_whitespace: bool,
+) -> PyResult<usize> {
+    let options = ExtractOptions {
+        lenient_xml,
+        strip_xref,
+        normalize_whitespace,
+        ..Default::default()
+    };
+
+    let output_file = File::create(output_path).map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to create output file: {e}"))
+    })?;
+
+    let written = py.allow_threads(|| {
+        write_json_array(&xml_paths, &mut BufWriter::new(output_file), &options)
+    })?;
+
+    Ok(written)
+}
+
+/// Index the `<PubmedArticle>`/`<article>` elements of a multi-article file
+/// by byte offset and PMID, writing a tab-separated `offset`/`pmid` file to
+/// `index_path` for `read_article_at`; returns the number of articles indexed
+#[pyfunction]
+pub fn build_article_index(py: Python, xml_path: &str, index_path: &str) -> PyResult<usize> {
+    py.allow_threads(|| crate::core::build_article_index(xml_path, index_path))
+        .map_err(|e| e.into_py_err(&format!("Failed to index articles of {xml_path}")))
+}
+
+/// Read the one article starting at byte `offset` of a multi-article file
+/// (an offset from `build_article_index`) without scanning the articles
+/// before it
+#[pyfunction(signature = (xml_path, offset, strip_xref=false, normalize_whitespace=false))]
+pub fn read_article_at(
+    py: Python,
+    xml_path: &str,
+    offset: u64,
+    strip_xref: bool,
+    normalize_whitespace: bool,
+) -> PyResult<ArticleMetadata> {
+    let options = ExtractOptions {
+        strip_xref,
+        normalize_whitespace,
+        ..Default::default()
+    };
+    Ok(py.allow_threads(|| crate::core::read_article_at(xml_path, offset, &options))?)
+}
+
+/// Map a compression name ("snappy", "zstd", "lz4", "gzip", "uncompressed") to
+/// a Parquet codec; `None` keeps the Polars default
+fn parquet_compression(compression: Option<&str>) -> PyResult<ParquetCompression> {
+    Ok(match compression.map(|c| c.to_lowercase()).as_deref() {
+        None => ParquetCompression::default(),
+        Some("snappy") => ParquetCompression::Snappy,
+        Some("zstd") => ParquetCompression::Zstd(None),
+        Some("lz4") => ParquetCompression::Lz4Raw,
+        Some("gzip") => ParquetCompression::Gzip(None),
+        Some("uncompressed") | Some("none") => ParquetCompression::Uncompressed,
+        Some(other) => {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Unknown Parquet compression '{other}' (expected snappy, zstd, lz4, gzip or uncompressed)"
+            )))
+        }
+    })
+}
+
+/// Convert multiple XML files straight to a Parquet file with the same columns
+/// as `xml_to_polars`, returning the number of rows written. Files larger than
+/// `max_bytes` are not read and get a row of nulls, like unreadable ones; I/O
+/// errors are retried as in `batch_xml_to_ndjson`.
+#[pyfunction(signature = (xml_paths, output_path, compression=None, lenient_xml=false, strip_xref=false, normalize_whitespace=false, max_bytes=None, retries=0, retry_delay_ms=100))]
+#[allow(clippy::too_many_arguments)]
+pub fn batch_xml_to_parquet(
+    py: Python,
+    xml_paths: Vec<String>,
+    output_path: &str,
+    compression: Option<&str>,
+    lenient_xml: bool,
+    strip_xref: bool,
+    normalize_whitespace: bool,
+    max_bytes: Option<u64>,
+    retries: u32,
+    retry_delay_ms: u64,
+) -> PyResult<usize> {
+    let compression = parquet_compression(compression)?;
+    let options = ExtractOptions {
+        lenient_xml,
+        strip_xref,
+        normalize_whitespace,
+        max_bytes,
+        retries,
+        retry_delay_ms,
+        ..Default::default()
+    };
+
+    let mut df = py
+        .allow_threads(|| articles_to_dataframe_with_options(&xml_paths, &options))
+        .map_err(|e| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Failed to create DataFrame: {e}"
+            ))
+        })?;
+
+    let output_file = File::create(output_path).map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to create output file: {e}"))
+    })?;
+
+    py.allow_threads(|| {
+        ParquetWriter::new(output_file)
+            .with_compression(compression)
+            .finish(&mut df)
+    })
+    .map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to write Parquet file: {e}"))
+    })?;
+
+    Ok(df.height())
+}
+
+fn ipc_compression(compression: Option<&str>) -> PyResult<Option<IpcCompression>> {
+    Ok(match compression.map(|c| c.to_lowercase()).as_deref() {
+        None | Some("uncompressed") | Some("none") => None,
+        Some("lz4") => Some(IpcCompression::LZ4),
+        Some("zstd") => Some(IpcCompression::ZSTD),
+        Some(other) => {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Unknown IPC compression '{other}' (expected lz4, zstd or uncompressed)"
+            )))
+        }
+    })
+}
+
+/// Convert multiple XML files to an Arrow IPC (Feather v2) file with the same
+/// columns as `xml_to_polars`, returning the number of rows written. Files
+/// larger than `max_bytes` get a row of nulls and I/O errors are retried, as
+/// in `batch_xml_to_parquet`.
+#[pyfunction(signature = (xml_paths, output_path, compression=None, lenient_xml=false, strip_xref=false, normalize_whitespace=false, max_bytes=None, retries=0, retry_delay_ms=100))]
+#[allow(clippy::too_many_arguments)]
+pub fn batch_xml_to_ipc(
+    py: Python,
+    xml_paths: Vec<String>,
+    output_path: &str,
+    compression: Option<&str>,
+    lenient_xml: bool,
+    strip_xref: bool,
+    normalize_whitespace: bool,
+    max_bytes: Option<u64>,
+    retries: u32,
+    retry_delay_ms: u64,
+) -> PyResult<usize> {
+    let compression = ipc_compression(compression)?;
+    let options = ExtractOptions {
+        lenient_xml,
+        strip_xref,
+        normalize_whitespace,
+        max_bytes,
+        retries,
+        retry_delay_ms,
+        ..Default::default()
+    };
+
+    let mut df = py
+        .allow_threads(|| articles_to_dataframe_with_options(&xml_paths, &options))
+        .map_err(|e| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Failed to create DataFrame: {e}"
+            ))
+        })?;
+
+    let output_file = File::create(output_path).map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to create output file: {e}"))
+    })?;
+
+    py.allow_threads(|| {
+        IpcWriter::new(output_file)
+            .with_compression(compression)
+            .finish(&mut df)
+    })
+    .map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to write IPC file: {e}"))
+    })?;
+
+    Ok(df.height())
+}
+
+/// Convert XML files to a directory of Parquet part files
+/// (`part-00000.parquet`, ...) holding `rows_per_file` articles each, so only
+/// one chunk is in memory at a time. Returns the paths of the written files.
+#[pyfunction(signature = (xml_paths, output_dir, rows_per_file=10000, compression=None, lenient_xml=false, strip_xref=false, normalize_whitespace=false))]
+#[allow(clippy::too_many_arguments)]
+pub fn xml_to_parquet_dataset(
+    py: Python,
+    xml_paths: Vec<String>,
+    output_dir: &str,
+    rows_per_file: usize,
+    compression: Option<&str>,
+    lenient_xml: bool,
+    strip_xref: bool,
+    normalize_whitespace: bool,
+) -> PyResult<Vec<String>> {
+    let options = ExtractOptions {
+        lenient_xml,
+        strip_xref,
+        normalize_whitespace,
+        ..Default::default()
+    };
+    if rows_per_file == 0 {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "rows_per_file must be at least 1",
+        ));
+    }
+    let compression = parquet_compression(compression)?;
+
+    std::fs::create_dir_all(output_dir).map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyIOError, _>(format!(
+            "Failed to create output directory: {e}"
+        ))
+    })?;
+
+    py.allow_threads(|| {
+        let mut written = Vec::new();
+
+        for (part, chunk) in xml_paths.chunks(rows_per_file).enumerate() {
+            let mut df = articles_to_dataframe_with_options(chunk, &options).map_err(|e| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "Failed to create DataFrame: {e}"
+                ))
+            })?;
+
+            let part_path =
+                std::path::Path::new(output_dir).join(format!("part-{part:05}.parquet"));
+            let output_file = File::create(&part_path).map_err(|e| {
+                PyErr::new::<pyo3::exceptions::PyIOError, _>(format!(
+                    "Failed to create output file: {e}"
+                ))
+            })?;
+            ParquetWriter::new(output_file)
+                .with_compression(compression)
+                .finish(&mut df)
+                .map_err(|e| {
+                    PyErr::new::<pyo3::exceptions::PyIOError, _>(format!(
+                        "Failed to write Parquet file: {e}"
+                    ))
+                })?;
+
+            written.push(part_path.to_string_lossy().into_owned());
+        }
+
+        Ok(written)
+    })
+}
+
+/// Read XML files into a Polars DataFrame with one row per body paragraph
+#[pyfunction(signature = (xml_paths, lenient_xml=false, strip_xref=false))]
+pub fn xml_to_paragraphs(
+    py: Python,
+    xml_paths: Vec<String>,
+    lenient_xml: bool,
+    strip_xref: bool,
+) -> PyResult<PyDataFrame> {
+    let options = ExtractOptions {
+        lenient_xml,
+        strip_xref,
+        ..Default::default()
+    };
+    let result = py.allow_threads(|| {
+        let mut file_paths = Vec::new();
+        let mut pmids = Vec::new();
+        let mut paragraph_indices = Vec::new();
+        let mut paragraph_texts = Vec::new();
+
+        for xml_path in &xml_paths {
+            match read_and_extract(xml_path, &options) {
+                Ok(metadata) => {
+                    for (index, paragraph) in metadata.body_paragraphs.into_iter().enumerate() {
+                        file_paths.push(xml_path.clone());
+                        pmids.push(metadata.pmid.clone());
+                        paragraph_indices.push(index as u32);
+                        paragraph_texts.push(paragraph);
+                    }
+                }
+                Err(e) => e.report(xml_path),
+            }
+        }
+
+        df! {
+            "file_path" => &file_paths,
+            "pmid" => &pmids,
+            "paragraph_index" => &paragraph_indices,
+            "paragraph_text" => &paragraph_texts,
+        }
+    });
+
+    let df = result.map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Failed to create paragraphs DataFrame: {e}"
+        ))
+    })?;
+
+    Ok(PyDataFrame(df))
+}
+
+/// Read XML files into a Polars DataFrame with one row per sentence of the
+/// abstract or the full text (`source`), split with [`split_sentences`]
+#[pyfunction(signature = (xml_paths, source="abstract", lenient_xml=false, strip_xref=false))]
+pub fn xml_to_sentences(
+    py: Python,
+    xml_paths: Vec<String>,
+    source: &str,
+    lenient_xml: bool,
+    strip_xref: bool,
+) -> PyResult<PyDataFrame> {
+    if !matches!(source, "abstract" | "full_text") {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Unknown sentence source '{source}' (expected abstract or full_text)"
+        )));
+    }
+
+    let options = ExtractOptions {
+        lenient_xml,
+        strip_xref,
+        ..Default::default()
+    };
+    let result = py.allow_threads(|| {
+        let mut file_paths = Vec::new();
+        let mut pmids = Vec::new();
+        let mut sentence_indices = Vec::new();
+        let mut sentence_texts = Vec::new();
+
+        for xml_path in &xml_paths {
+            match read_and_extract(xml_path, &options) {
+                Ok(metadata) => {
+                    let text = article_field_text(&metadata, source).unwrap_or_default();
+                    for (index, sentence) in split_sentences(&text).into_iter().enumerate() {
+                        file_paths.push(xml_path.clone());
+                        pmids.push(metadata.pmid.clone());
+                        sentence_indices.push(index as u32);
+                        sentence_texts.push(sentence);
+                    }
+                }
+                Err(e) => e.report(xml_path),
+            }
+        }
+
+        df! {
+            "file_path" => &file_paths,
+            "pmid" => &pmids,
+            "sentence_index" => &sentence_indices,
+            "sentence_text" => &sentence_texts,
+        }
+    });
+
+    let df = result.map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Failed to create sentences DataFrame: {e}"
+        ))
+    })?;
+
+    Ok(PyDataFrame(df))
+}
+
+/// Read XML files into a Polars DataFrame with one row per body `<sec>`
+#[pyfunction(signature = (xml_paths, lenient_xml=false, strip_xref=false))]
+pub fn xml_to_sections(
+    py: Python,
+    xml_paths: Vec<String>,
+    lenient_xml: bool,
+    strip_xref: bool,
+) -> PyResult<PyDataFrame> {
+    let options = ExtractOptions {
+        lenient_xml,
+        strip_xref,
+        ..Default::default()
+    };
+    let result = py.allow_threads(|| {
+        let mut file_paths = Vec::new();
+        let mut section_types = Vec::new();
+        let mut section_titles = Vec::new();
+        let mut section_texts = Vec::new();
+
+        for xml_path in &xml_paths {
+            match read_and_extract(xml_path, &options) {
+                Ok(metadata) => {
+                    for (sec_type, title, text) in metadata.full_text_sections {
+                        file_paths.push(xml_path.clone());
+                        section_types.push((!sec_type.is_empty()).then_some(sec_type));
+                        section_titles.push((!title.is_empty()).then_some(title));
+                        section_texts.push(text);
+                    }
+                }
+                Err(e) => e.report(xml_path),
+            }
+        }
+
+        df! {
+            "file_path" => &file_paths,
+            "section_type" => &section_types,
+            "section_title" => &section_titles,
+            "section_text" => &section_texts,
+        }
+    });
+
+    let df = result.map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Failed to create sections DataFrame: {e}"
+        ))
+    })?;
+
+    Ok(PyDataFrame(df))
+}
+
+/// Read the funding groups of XML files into one Polars DataFrame, one row per
+/// (funder, award id) pair
+#[pyfunction(signature = (xml_paths, lenient_xml=false))]
+pub fn xml_to_funding(
+    py: Python,
+    xml_paths: Vec<String>,
+    lenient_xml: bool,
+) -> PyResult<PyDataFrame> {
+    let options = ExtractOptions {
+        lenient_xml,
+        ..Default::default()
+    };
+    let result = py.allow_threads(|| {
+        let mut file_paths = Vec::new();
+        let mut pmids = Vec::new();
+        let mut funders = Vec::new();
+        let mut funder_ids = Vec::new();
+        let mut award_ids = Vec::new();
+
+        for xml_path in &xml_paths {
+            match read_and_extract(xml_path, &options) {
+                Ok(metadata) => {
+                    for award in metadata.funding {
+                        file_paths.push(xml_path.clone());
+                        pmids.push(metadata.pmid.clone());
+                        funders.push(award.funder);
+                        funder_ids.push(award.funder_id);
+                        award_ids.push(award.award_id);
+                    }
+                }
+                Err(e) => e.report(xml_path),
+            }
+        }
+
+        df! {
+            "file_path" => &file_paths,
+            "pmid" => &pmids,
+            "funder" => &funders,
+            "funder_id" => &funder_ids,
+            "award_id" => &award_ids,
+        }
+    });
+
+    let df = result.map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Failed to create funding DataFrame: {e}"
+        ))
+    })?;
+
+    Ok(PyDataFrame(df))
+}
+
+/// (citing file path, citing pmid, reference) for every reference in the
+/// reference lists of `xml_paths`; unreadable files are reported and skipped
+fn cited_references(
+    xml_paths: &[String],
+    lenient_xml: bool,
+) -> Vec<(String, Option<String>, Reference)> {
+    let mut rows = Vec::new();
+
+    for xml_path in xml_paths {
+        let xml_content = match load_xml(xml_path, lenient_xml) {
+            Ok(xml_content) => xml_content,
+            Err(e) => {
+                log::warn!("Failed to read {xml_path}: {e}");
+                continue;
+            }
+        };
+
+        let parsed = extract_article_metadata(&xml_content, xml_path)
+            .and_then(|metadata| Ok((metadata, extract_references(&xml_content)?)));
+        match parsed {
+            Ok((metadata, references)) => {
+                for reference in references {
+                    rows.push((xml_path.clone(), metadata.pmid.clone(), reference));
+                }
+            }
+            Err(e) => log::warn!("Failed to extract references from {xml_path}: {e}"),
+        }
+    }
+
+    rows
+}
+
+/// Read the figures of XML files into one Polars DataFrame, one row per `<fig>`
+#[pyfunction(signature = (xml_paths, lenient_xml=false))]
+pub fn xml_to_figures(
+    py: Python,
+    xml_paths: Vec<String>,
+    lenient_xml: bool,
+) -> PyResult<PyDataFrame> {
+    let result = py.allow_threads(|| {
+        let mut file_paths = Vec::new();
+        let mut fig_ids = Vec::new();
+        let mut labels = Vec::new();
+        let mut caption_texts = Vec::new();
+        let mut graphic_hrefs = Vec::new();
+
+        for xml_path in &xml_paths {
+            let figures = load_xml(xml_path, lenient_xml)
+                .and_then(|xml_content| extract_figures(&xml_content));
+            match figures {
+                Ok(figures) => {
+                    for figure in figures {
+                        file_paths.push(xml_path.clone());
+                        fig_ids.push(figure.id);
+                        labels.push(figure.label);
+                        caption_texts.push(figure.caption);
+                        graphic_hrefs.push(figure.graphic_href);
+                    }
+                }
+                Err(e) => log::warn!("Failed to extract figures from {xml_path}: {e}"),
+            }
+        }
+
+        df! {
+            "file_path" => &file_paths,
+            "fig_id" => &fig_ids,
+            "label" => &labels,
+            "caption_text" => &caption_texts,
+            "graphic_href" => &graphic_hrefs,
+        }
+    });
+
+    let df = result.map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Failed to create figures DataFrame: {e}"
+        ))
+    })?;
+
+    Ok(PyDataFrame(df))
+}
+
+/// Read the supplementary files of XML files into one Polars DataFrame, one
+/// row per `<media>` of a `<supplementary-material>` (or per block linking its
+/// file directly)
+#[pyfunction(signature = (xml_paths, lenient_xml=false))]
+pub fn xml_to_supplementary(
+    py: Python,
+    xml_paths: Vec<String>,
+    lenient_xml: bool,
+) -> PyResult<PyDataFrame> {
+    let result = py.allow_threads(|| {
+        let mut file_paths = Vec::new();
+        let mut pmids = Vec::new();
+        let mut labels = Vec::new();
+        let mut hrefs = Vec::new();
+        let mut mimetypes = Vec::new();
+
+        for xml_path in &xml_paths {
+            let files = load_xml(xml_path, lenient_xml).and_then(|xml_content| {
+                let metadata = extract_article_metadata(&xml_content, xml_path)?;
+                Ok((metadata.pmid, extract_supplementary(&xml_content)?))
+            });
+            match files {
+                Ok((pmid, files)) => {
+                    for file in files {
+                        file_paths.push(xml_path.clone());
+                        pmids.push(pmid.clone());
+                        labels.push(file.label);
+                        hrefs.push(file.href);
+                        mimetypes.push(file.mimetype);
+                    }
+                }
+                Err(e) => log::warn!("Failed to extract supplementary files from {xml_path}: {e}"),
+            }
+        }
+
+        df! {
+            "file_path" => &file_paths,
+            "pmid" => &pmids,
+            "supp_label" => &labels,
+            "supp_href" => &hrefs,
+            "supp_mimetype" => &mimetypes,
+        }
+    });
+
+    let df = result.map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Failed to create supplementary DataFrame: {e}"
+        ))
+    })?;
+
+    Ok(PyDataFrame(df))
+}
+
+/// Read the publisher-tagged entities (`<named-content>`) of XML files into
+/// one Polars DataFrame, one row per distinct type and text in each file
+#[pyfunction(signature = (xml_paths, lenient_xml=false))]
+pub fn xml_to_entities(
+    py: Python,
+    xml_paths: Vec<String>,
+    lenient_xml: bool,
+) -> PyResult<PyDataFrame> {
+    let result = py.allow_threads(|| {
+        let mut file_paths = Vec::new();
+        let mut entity_types = Vec::new();
+        let mut entity_texts = Vec::new();
+        let mut counts = Vec::new();
+
+        for xml_path in &xml_paths {
+            match load_xml(xml_path, lenient_xml)
+                .and_then(|xml_content| extract_named_entities(&xml_content))
+            {
+                Ok(entities) => {
+                    for entity in entities {
+                        file_paths.push(xml_path.clone());
+                        entity_types.push(entity.entity_type);
+                        entity_texts.push(entity.text);
+                        counts.push(entity.count);
+                    }
+                }
+                Err(e) => log::warn!("Failed to extract entities from {xml_path}: {e}"),
+            }
+        }
+
+        df! {
+            "file_path" => &file_paths,
+            "entity_type" => &entity_types,
+            "entity_text" => &entity_texts,
+            "count" => &counts,
+        }
+    });
+
+    let df = result.map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Failed to create entities DataFrame: {e}"
+        ))
+    })?;
+
+    Ok(PyDataFrame(df))
+}
+
+/// Read the inline cross-references of XML files into one Polars DataFrame,
+/// one row per `<xref>` target in the body
+#[pyfunction(signature = (xml_paths, lenient_xml=false))]
+pub fn xml_to_xrefs(
+    py: Python,
+    xml_paths: Vec<String>,
+    lenient_xml: bool,
+) -> PyResult<PyDataFrame> {
+    let result = py.allow_threads(|| {
+        let mut file_paths = Vec::new();
+        let mut source_contexts = Vec::new();
+        let mut ref_types = Vec::new();
+        let mut rids = Vec::new();
+
+        for xml_path in &xml_paths {
+            match load_xml(xml_path, lenient_xml)
+                .and_then(|xml_content| extract_cross_references(&xml_content))
+            {
+                Ok(xrefs) => {
+                    for xref in xrefs {
+                        file_paths.push(xml_path.clone());
+                        source_contexts.push(xref.context);
+                        ref_types.push(xref.ref_type);
+                        rids.push(xref.rid);
+                    }
+                }
+                Err(e) => log::warn!("Failed to extract cross-references from {xml_path}: {e}"),
+            }
+        }
+
+        df! {
+            "file_path" => &file_paths,
+            "source_context" => &source_contexts,
+            "ref_type" => &ref_types,
+            "rid" => &rids,
+        }
+    });
+
+    let df = result.map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Failed to create cross-references DataFrame: {e}"
+        ))
+    })?;
+
+    Ok(PyDataFrame(df))
+}
+
+/// Read the tables of XML files into one Polars DataFrame, one row per
+/// `<table-wrap>`, with the cell matrix serialized as JSON
+#[pyfunction(signature = (xml_paths, lenient_xml=false))]
+pub fn xml_to_tables(
+    py: Python,
+    xml_paths: Vec<String>,
+    lenient_xml: bool,
+) -> PyResult<PyDataFrame> {
+    let result = py.allow_threads(|| {
+        let mut file_paths = Vec::new();
+        let mut table_ids = Vec::new();
+        let mut labels = Vec::new();
+        let mut captions = Vec::new();
+        let mut table_jsons = Vec::new();
+
+        for xml_path in &xml_paths {
+            let tables = load_xml(xml_path, lenient_xml)
+                .and_then(|xml_content| extract_tables(&xml_content));
+            match tables {
+                Ok(tables) => {
+                    for table in tables {
+                        let table_json = serde_json::to_string(&table.rows)
+                            .map_err(|e| PolarsError::ComputeError(e.to_string().into()))?;
+                        file_paths.push(xml_path.clone());
+                        table_ids.push(table.id);
+                        labels.push(table.label);
+                        captions.push(table.caption);
+                        table_jsons.push(table_json);
+                    }
+                }
+                Err(e) => log::warn!("Failed to extract tables from {xml_path}: {e}"),
+            }
+        }
+
+        df! {
+            "file_path" => &file_paths,
+            "table_id" => &table_ids,
+            "label" => &labels,
+            "caption_text" => &captions,
+            "table_json" => &table_jsons,
+        }
+    });
+
+    let df = result.map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Failed to create tables DataFrame: {e}"
+        ))
+    })?;
+
+    Ok(PyDataFrame(df))
+}
+
+/// Read the reference lists of XML files into one Polars DataFrame, one row per
+/// cited reference, for building citation edge lists
+#[pyfunction(signature = (xml_paths, lenient_xml=false))]
+pub fn references_to_polars(
+    py: Python,
+    xml_paths: Vec<String>,
+    lenient_xml: bool,
+) -> PyResult<PyDataFrame> {
+    let result = py.allow_threads(|| {
+        let mut citing_file_paths = Vec::new();
+        let mut citing_pmids = Vec::new();
+        let mut ref_labels = Vec::new();
+        let mut ref_texts = Vec::new();
+        let mut ref_dois = Vec::new();
+        let mut ref_pmids = Vec::new();
+
+        for (xml_path, citing_pmid, reference) in cited_references(&xml_paths, lenient_xml) {
+            citing_file_paths.push(xml_path);
+            citing_pmids.push(citing_pmid);
+            ref_labels.push(reference.label);
+            ref_texts.push(reference.text);
+            ref_dois.push(reference.doi);
+            ref_pmids.push(reference.pmid);
+        }
+
+        df! {
+            "citing_file_path" => &citing_file_paths,
+            "citing_pmid" => &citing_pmids,
+            "ref_label" => &ref_labels,
+            "ref_text" => &ref_texts,
+            "ref_doi" => &ref_dois,
+            "ref_pmid" => &ref_pmids,
+        }
+    });
+
+    let df = result.map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Failed to create references DataFrame: {e}"
+        ))
+    })?;
+
+    Ok(PyDataFrame(df))
+}
+
+/// Read the bibliographies of XML files into one Polars DataFrame, one row per
+/// `<ref>`, with the identifiers and title/year of each cited work
+#[pyfunction(signature = (xml_paths, lenient_xml=false))]
+pub fn xml_to_references(
+    py: Python,
+    xml_paths: Vec<String>,
+    lenient_xml: bool,
+) -> PyResult<PyDataFrame> {
+    let result = py.allow_threads(|| {
+        let mut file_paths = Vec::new();
+        let mut citing_pmids = Vec::new();
+        let mut ref_ids = Vec::new();
+        let mut ref_pmids = Vec::new();
+        let mut ref_dois = Vec::new();
+        let mut ref_titles = Vec::new();
+        let mut ref_years = Vec::new();
+
+        for (xml_path, citing_pmid, reference) in cited_references(&xml_paths, lenient_xml) {
+            file_paths.push(xml_path);
+            citing_pmids.push(citing_pmid);
+            ref_ids.push(reference.id);
+            ref_pmids.push(reference.pmid);
+            ref_dois.push(reference.doi);
+            ref_titles.push(reference.title);
+            ref_years.push(reference.year);
+        }
+
+        df! {
+            "file_path" => &file_paths,
+            "citing_pmid" => &citing_pmids,
+            "ref_id" => &ref_ids,
+            "ref_pmid" => &ref_pmids,
+            "ref_doi" => &ref_dois,
+            "ref_title" => &ref_titles,
+            "ref_year" => &ref_years,
+        }
+    });
+
+    let df = result.map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Failed to create references DataFrame: {e}"
+        ))
+    })?;
+
+    Ok(PyDataFrame(df))
+}
+
+/// Compile a search pattern, case-insensitively unless `case_sensitive`
+fn compile_pattern(pattern: &str, case_sensitive: bool, whole_word: bool) -> PyResult<Regex> {
+    // The group keeps alternations ("gene|genome") inside the boundaries
+    let wrapped = if whole_word {
+        format!(r"\b(?:{pattern})\b")
+    } else {
+        pattern.to_string()
+    };
+    if case_sensitive {
+        Regex::new(&wrapped)
+    } else {
+        Regex::new(&format!("(?i){wrapped}"))
+    }
+    .map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Invalid regex pattern '{pattern}': {e}"
+        ))
+    })
+}
+
+/// Compile search patterns, case-insensitively unless `case_sensitive`
+fn compile_patterns(
+    patterns: &[String],
+    case_sensitive: bool,
+    whole_word: bool,
+) -> PyResult<Vec<Regex>> {
+    patterns
+        .iter()
+        .map(|pattern| compile_pattern(pattern, case_sensitive, whole_word))
+        .collect()
+}
+
+/// Article fields `search_xml_content` can be restricted to
+const SEARCHABLE_FIELDS: &[&str] = &[
+    "title",
+    "abstract",
+    "full_text",
+    "keywords",
+    "mesh_terms",
+    "data_availability",
+];
+
+/// Extracted text of one of `SEARCHABLE_FIELDS`; list fields are joined with "; "
+fn article_field_text(metadata: &ArticleMetadata, field: &str) -> Option<String> {
+    match field {
+        "title" => metadata.title.clone(),
+        "abstract" => metadata.abstract_text.clone(),
+        "full_text" => metadata.full_text.clone(),
+        "keywords" => Some(metadata.keywords.join("; ")),
+        "mesh_terms" => Some(metadata.mesh_terms.join("; ")),
+        "data_availability" => metadata.data_availability.clone(),
+        _ => None,
+    }
+}
+
+/// `text[start..end]` widened by up to `context_chars` characters on each side,
+/// always cut on char boundaries
+fn match_context(text: &str, start: usize, end: usize, context_chars: usize) -> &str {
+    let (context_start, context_end) = context_bounds(text, start, end, context_chars);
+    &text[context_start..context_end]
+}
+
+/// Stand-ins for the highlight markers while a context is cleaned, so the
+/// markers (often tags themselves) survive and land on the cleaned text
+const HIGHLIGHT_OPEN: &str = "\u{e000}";
+const HIGHLIGHT_CLOSE: &str = "\u{e001}";
+
+/// `text[context_start..context_end]` with every one of `ranges` that lies
+/// inside it wrapped in the `highlight` markers, optionally cleaned
+fn highlighted_context(
+    text: &str,
+    (context_start, context_end): (usize, usize),
+    ranges: &[(usize, usize)],
+    (open, close): (&str, &str),
+    clean: bool,
+) -> String {
+    let inside: Vec<(usize, usize)> = ranges
+        .iter()
+        .filter(|&&(start, end)| start >= context_start && end <= context_end)
+        .map(|&(start, end)| (start - context_start, end - context_start))
+        .collect();
+    let context = &text[context_start..context_end];
+    if clean {
+        let marked = highlight_ranges(context, &inside, HIGHLIGHT_OPEN, HIGHLIGHT_CLOSE);
+        clean_context(&marked)
+            .replace(HIGHLIGHT_OPEN, open)
+            .replace(HIGHLIGHT_CLOSE, close)
+    } else {
+        highlight_ranges(context, &inside, open, close)
+    }
+}
+
+/// `context` with its XML tags removed, including a tag cut in half at either
+/// end of the window; each tag counts as a word break and whitespace runs are
+/// collapsed
+fn clean_context(context: &str) -> String {
+    // A '>' before any '<' closes a tag that started before the window
+    let context = match (context.find('>'), context.find('<')) {
+        (Some(close), Some(open)) if close < open => &context[close + 1..],
+        (Some(close), None) => &context[close + 1..],
+        _ => context,
+    };
+
+    let mut cleaned = String::with_capacity(context.len());
+    let mut in_tag = false;
+    for c in context.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => {
+                in_tag = false;
+                cleaned.push(' ');
+            }
+            _ if !in_tag => cleaned.push(c),
+            _ => {}
+        }
+    }
+    normalize_whitespace(&cleaned)
+}
+
+/// One regex match found by `search_xml_content`
+struct SearchMatch {
+    file_index: usize,
+    /// Position of the field in the requested `fields` (0 for raw XML)
+    field_index: usize,
+    pattern_index: usize,
+    field: Option<String>,
+    start: usize,
+    end: usize,
+    matched_text: String,
+    context: String,
+    /// (group name, captured text) for each named group of the pattern
+    captures: Vec<(String, Option<String>)>,
+    /// Edits between the pattern and `matched_text` (fuzzy search only)
+    distance: Option<usize>,
+}
+
+/// How `search_xml_content` finds its patterns
+enum SearchMatcher {
+    /// The compiled patterns
+    Regex(Vec<Regex>),
+    /// The patterns as literals with the case sensitivity of each, matched
+    /// within `max_distance` edits (see [`fuzzy_matches`])
+    Fuzzy {
+        patterns: Vec<(String, bool)>,
+        max_distance: usize,
+    },
+}
+
+impl SearchMatcher {
+    fn len(&self) -> usize {
+        match self {
+            Self::Regex(regexes) => regexes.len(),
+            Self::Fuzzy { patterns, .. } => patterns.len(),
+        }
+    }
+
+    /// (start, end, distance, named captures) of every match of pattern
+    /// `pattern_index` in `text`
+    #[allow(clippy::type_complexity)]
+    fn find(
+        &self,
+        pattern_index: usize,
+        text: &str,
+    ) -> Vec<(usize, usize, Option<usize>, Vec<(String, Option<String>)>)> {
+        match self {
+            Self::Regex(regexes) => {
+                let regex = &regexes[pattern_index];
+                let group_names: Vec<&str> = regex.capture_names().flatten().collect();
+                regex
+                    .captures_iter(text)
+                    .map(|captures| {
+                        let mat = captures.get(0).expect("group 0 is always the whole match");
+                        let named = group_names
+                            .iter()
+                            .map(|name| {
+                                let value = captures.name(name).map(|m| m.as_str().to_string());
+                                (name.to_string(), value)
+                            })
+                            .collect();
+                        (mat.start(), mat.end(), None, named)
+                    })
+                    .collect()
+            }
+            Self::Fuzzy {
+                patterns,
+                max_distance,
+            } => {
+                let (pattern, case_sensitive) = &patterns[pattern_index];
+                fuzzy_matches(text, pattern, *max_distance, *case_sensitive)
+                    .into_iter()
+                    .map(|(start, end, distance)| (start, end, Some(distance), Vec::new()))
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Every match of `matcher` in one file, searching the raw XML or, with
+/// `fields`, the extracted text of those fields. Unreadable files yield nothing.
+fn search_file(
+    file_index: usize,
+    xml_path: &str,
+    matcher: &SearchMatcher,
+    fields: Option<&[String]>,
+    context_chars: usize,
+    clean: bool,
+    highlight: Option<(&str, &str)>,
+) -> Vec<SearchMatch> {
+    // (field, text) pairs to search; the field is None for raw XML
+    let targets: Vec<(Option<&str>, String)> = match fields {
+        None => match read_xml_text(xml_path) {
+            Ok(xml_content) => vec![(None, xml_content)],
+            Err(_) => return Vec::new(),
+        },
+        Some(fields) => match read_and_extract(xml_path, &ExtractOptions::default()) {
+            Ok(metadata) => fields
+                .iter()
+                .filter_map(|field| {
+                    article_field_text(&metadata, field).map(|text| (Some(field.as_str()), text))
+                })
+                .collect(),
+            Err(_) => return Vec::new(),
+        },
+    };
+
+    let mut matches = Vec::new();
+    for (field_index, (field, text)) in targets.iter().enumerate() {
+        for pattern_index in 0..matcher.len() {
+            let found = matcher.find(pattern_index, text);
+            // Every match of the pattern, to highlight all of those in a context
+            let ranges: Vec<(usize, usize)> =
+                found.iter().map(|&(start, end, ..)| (start, end)).collect();
+            for (start, end, distance, captures) in found {
+                let bounds = context_bounds(text, start, end, context_chars);
+                let context = &text[bounds.0..bounds.1];
+                matches.push(SearchMatch {
+                    file_index,
+                    field_index,
+                    pattern_index,
+                    field: field.map(str::to_string),
+                    start,
+                    end,
+                    matched_text: text[start..end].to_string(),
+                    context: match highlight {
+                        Some(markers) => highlighted_context(text, bounds, &ranges, markers, clean),
+                        None if clean => clean_context(context),
+                        None => context.to_string(),
+                    },
+                    captures,
+                    distance,
+                });
+            }
+        }
+    }
+    matches
+}
+
+/// Columns of the `search_xml_content` result, which named capture groups
+/// must not shadow
+const SEARCH_COLUMNS: &[&str] = &[
+    "file_path",
+    "matched_pattern",
+    "field",
+    "match_start",
+    "match_end",
+    "matched_text",
+    "match_context",
+];
+
+/// Search for patterns in XML content and return matching articles. With
+/// `fields`, the search runs over those extracted fields instead of raw XML.
+/// Files are searched in parallel; rows come out in input file order.
+/// Every named capture group across the patterns adds a column of its own.
+/// `clean_context` strips XML tags from the returned context.
+/// `case_sensitive_flags`, aligned with `patterns`, overrides `case_sensitive`
+/// pattern by pattern. `highlight` wraps each match of the pattern inside the
+/// context in its (open, close) markers. `fuzzy` takes the patterns as
+/// literals matched within that many edits instead of as regexes, and adds a
+/// `distance` column.
+#[pyfunction(signature = (xml_paths, patterns, case_sensitive=None, fields=None, context_chars=100, num_threads=None, whole_word=false, clean_context=false, case_sensitive_flags=None, highlight=None, fuzzy=None))]
+#[allow(clippy::too_many_arguments)]
+pub fn search_xml_content(
+    py: Python,
+    xml_paths: Vec<String>,
+    patterns: Vec<String>,
+    case_sensitive: Option<bool>,
+    fields: Option<Vec<String>>,
+    context_chars: usize,
+    num_threads: Option<usize>,
+    whole_word: bool,
+    clean_context: bool,
+    case_sensitive_flags: Option<Vec<bool>>,
+    highlight: Option<(String, String)>,
+    fuzzy: Option<u8>,
+) -> PyResult<PyDataFrame> {
+    let case_sensitive = case_sensitive.unwrap_or(false);
+
+    if let Some(fields) = &fields {
+        if let Some(unknown) = fields
+            .iter()
+            .find(|field| !SEARCHABLE_FIELDS.contains(&field.as_str()))
+        {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Unknown search field '{unknown}' (expected one of {})",
+                SEARCHABLE_FIELDS.join(", ")
+            )));
+        }
+    }
+
+    let matcher = match (&case_sensitive_flags, fuzzy) {
+        (Some(flags), _) if flags.len() != patterns.len() => {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "case_sensitive_flags has {} entries for {} patterns",
+                flags.len(),
+                patterns.len()
+            )));
+        }
+        (flags, Some(max_distance)) => SearchMatcher::Fuzzy {
+            patterns: patterns
+                .iter()
+                .enumerate()
+                .map(|(i, pattern)| {
+                    let flag = flags.as_ref().map_or(case_sensitive, |flags| flags[i]);
+                    (pattern.clone(), flag)
+                })
+                .collect(),
+            max_distance: max_distance.into(),
+        },
+        (Some(flags), None) => SearchMatcher::Regex(
+            patterns
+                .iter()
+                .zip(flags)
+                .map(|(pattern, &flag)| compile_pattern(pattern, flag, whole_word))
+                .collect::<PyResult<Vec<_>>>()?,
+        ),
+        (None, None) => {
+            SearchMatcher::Regex(compile_patterns(&patterns, case_sensitive, whole_word)?)
+        }
+    };
+    let regexes: &[Regex] = match &matcher {
+        SearchMatcher::Regex(regexes) => regexes,
+        SearchMatcher::Fuzzy { .. } => &[],
+    };
+
+    // One column per distinct group name, in order of first appearance
+    let mut group_columns: Vec<String> = Vec::new();
+    for name in regexes
+        .iter()
+        .flat_map(|regex| regex.capture_names().flatten())
+    {
+        if SEARCH_COLUMNS.contains(&name) {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Capture group name '{name}' clashes with a result column"
+            )));
+        }
+        if !group_columns.iter().any(|column| column == name) {
+            group_columns.push(name.to_string());
+        }
+    }
+
+    let mut matches = py.allow_threads(|| {
+        run_in_pool(num_threads, || {
+            xml_paths
+                .par_iter()
+                .enumerate()
+                .flat_map_iter(|(file_index, xml_path)| {
+                    search_file(
+                        file_index,
+                        xml_path,
+                        &matcher,
+                        fields.as_deref(),
+                        context_chars,
+                        clean_context,
+                        highlight
+                            .as_ref()
+                            .map(|(open, close)| (open.as_str(), close.as_str())),
+                    )
+                })
+                .collect::<Vec<_>>()
+        })
+    })?;
+    matches.sort_by_key(|m| (m.file_index, m.field_index, m.pattern_index, m.start));
+
+    let mut matched_file_paths = Vec::with_capacity(matches.len());
+    let mut matched_patterns = Vec::with_capacity(matches.len());
+    let mut matched_fields = Vec::with_capacity(matches.len());
+    let mut match_starts = Vec::with_capacity(matches.len());
+    let mut match_ends = Vec::with_capacity(matches.len());
+    let mut matched_texts = Vec::with_capacity(matches.len());
+    let mut match_contexts = Vec::with_capacity(matches.len());
+    let mut distances = Vec::with_capacity(matches.len());
+    let mut group_values: Vec<Vec<Option<String>>> =
+        vec![Vec::with_capacity(matches.len()); group_columns.len()];
+
+    for m in matches {
+        for (column, values) in group_columns.iter().zip(group_values.iter_mut()) {
+            let value = m
+                .captures
+                .iter()
+                .find(|(name, _)| name == column)
+                .and_then(|(_, value)| value.clone());
+            values.push(value);
+        }
+        matched_file_paths.push(Some(xml_paths[m.file_index].clone()));
+        matched_patterns.push(Some(patterns[m.pattern_index].clone()));
+        matched_fields.push(m.field);
+        match_starts.push(m.start as u64);
+        match_ends.push(m.end as u64);
+        matched_texts.push(m.matched_text);
+        match_contexts.push(Some(m.context));
+        distances.push(m.distance.map(|distance| distance as u32));
+    }
+
+    let df = df! {
+        "file_path" => &matched_file_paths,
+        "matched_pattern" => &matched_patterns,
+        "field" => &matched_fields,
+        "match_start" => &match_starts,
+        "match_end" => &match_ends,
+        "matched_text" => &matched_texts,
+        "match_context" => &match_contexts,
+    }
+    .and_then(|mut df| {
+        if fuzzy.is_some() {
+            df.with_column(Series::new("distance".into(), distances))?;
+        }
+        for (column, values) in group_columns.iter().zip(group_values) {
+            df.with_column(Series::new(column.as_str().into(), values))?;
+        }
+        Ok(df)
+    })
+    .map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Failed to create search results DataFrame: {e}"
+        ))
+    })?;
+
+    Ok(PyDataFrame(df))
+}
+
+/// Search the text `columns` of a DataFrame already built from the articles
+/// (e.g. by `xml_to_polars` or `ndjson_to_polars`), so a corpus can be
+/// extracted once and queried many times. One row per match, ordered by row,
+/// column, pattern and position; null cells are skipped.
+#[pyfunction(signature = (df, columns, patterns, case_sensitive=None, context_chars=100))]
+pub fn search_dataframe(
+    py: Python,
+    df: PyDataFrame,
+    columns: Vec<String>,
+    patterns: Vec<String>,
+    case_sensitive: Option<bool>,
+    context_chars: usize,
+) -> PyResult<PyDataFrame> {
+    let df = df.0;
+    let regex_patterns = compile_patterns(&patterns, case_sensitive.unwrap_or(false), false)?;
+
+    let mut text_columns = Vec::with_capacity(columns.len());
+    for name in &columns {
+        let column = df.column(name).map_err(|_| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "DataFrame has no column '{name}'"
+            ))
+        })?;
+        let text = column.str().map_err(|_| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Column '{name}' is not a string column"
+            ))
+        })?;
+        text_columns.push(text.clone());
+    }
+
+    let mut row_indices = Vec::new();
+    let mut matched_columns = Vec::new();
+    let mut matched_patterns = Vec::new();
+    let mut match_starts = Vec::new();
+    let mut match_ends = Vec::new();
+    let mut matched_texts = Vec::new();
+    let mut match_contexts = Vec::new();
+
+    py.allow_threads(|| {
+        for row_index in 0..df.height() {
+            for (name, text_column) in columns.iter().zip(&text_columns) {
+                let Some(text) = text_column.get(row_index) else {
+                    continue;
+                };
+                for (pattern, regex) in patterns.iter().zip(&regex_patterns) {
+                    for mat in regex.find_iter(text) {
+                        row_indices.push(row_index as u64);
+                        matched_columns.push(name.as_str());
+                        matched_patterns.push(pattern.as_str());
+                        match_starts.push(mat.start() as u64);
+                        match_ends.push(mat.end() as u64);
+                        matched_texts.push(mat.as_str());
+                        match_contexts.push(match_context(
+                            text,
+                            mat.start(),
+                            mat.end(),
+                            context_chars,
+                        ));
+                    }
+                }
+            }
+        }
+    });
+
+    let df = df! {
+        "row_index" => &row_indices,
+        "column" => &matched_columns,
+        "matched_pattern" => &matched_patterns,
+        "match_start" => &match_starts,
+        "match_end" => &match_ends,
+        "matched_text" => &matched_texts,
+        "match_context" => &match_contexts,
+    }
+    .map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Failed to create search results DataFrame: {e}"
+        ))
+    })?;
+
+    Ok(PyDataFrame(df))
+}
+
+/// Count pattern matches in XML files: one row per file with a `file_path`
+/// column and one match-count column per pattern (named after the pattern).
+/// Files that can't be read get null counts.
+#[pyfunction(signature = (xml_paths, patterns, case_sensitive=None, num_threads=None))]
+pub fn count_xml_matches(
+    py: Python,
+    xml_paths: Vec<String>,
+    patterns: Vec<String>,
+    case_sensitive: Option<bool>,
+    num_threads: Option<usize>,
+) -> PyResult<PyDataFrame> {
+    let regex_patterns = compile_patterns(&patterns, case_sensitive.unwrap_or(false), false)?;
+
+    // Each pattern names a column, so they must be unique
+    for (i, pattern) in patterns.iter().enumerate() {
+        if pattern == "file_path" || patterns[..i].contains(pattern) {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Pattern '{pattern}' is repeated or clashes with the file_path column"
+            )));
+        }
+    }
+
+    // Per file, the count of each pattern; None for unreadable files
+    let counts: Vec<Option<Vec<u32>>> = py.allow_threads(|| {
+        run_in_pool(num_threads, || {
+            xml_paths
+                .par_iter()
+                .map(|xml_path| match read_xml_text(xml_path) {
+                    Ok(xml_content) => Some(
+                        regex_patterns
+                            .iter()
+                            .map(|regex| regex.find_iter(&xml_content).count() as u32)
+                            .collect(),
+                    ),
+                    Err(e) => {
+                        log::warn!("Failed to read {xml_path}: {e}");
+                        None
+                    }
+                })
+                .collect()
+        })
+    })?;
+
+    let mut columns = vec![Column::new("file_path".into(), &xml_paths)];
+    for (pattern_idx, pattern) in patterns.iter().enumerate() {
+        let pattern_counts: Vec<Option<u32>> = counts
+            .iter()
+            .map(|file_counts| file_counts.as_ref().map(|c| c[pattern_idx]))
+            .collect();
+        columns.push(Column::new(pattern.as_str().into(), pattern_counts));
+    }
+
+    let df = DataFrame::new(columns).map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Failed to create match count DataFrame: {e}"
+        ))
+    })?;
+
+    Ok(PyDataFrame(df))
+}
+
+/// Word frequencies of one text field (one of `SEARCHABLE_FIELDS`, e.g.
+/// "title", "abstract" or "full_text") over all `xml_paths`, as `word`/`count`
+/// rows, most frequent first, limited to `top_n`. Words are split on Unicode
+/// word boundaries and lowercased; those shorter than `min_length` characters
+/// are left out. Files that can't be read or parsed are logged and skipped.
+#[pyfunction(signature = (xml_paths, field="abstract", top_n=Some(100), min_length=3, num_threads=None))]
+pub fn word_frequencies(
+    py: Python,
+    xml_paths: Vec<String>,
+    field: &str,
+    top_n: Option<usize>,
+    min_length: usize,
+    num_threads: Option<usize>,
+) -> PyResult<PyDataFrame> {
+    if !SEARCHABLE_FIELDS.contains(&field) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Unknown field '{field}' (expected one of {})",
+            SEARCHABLE_FIELDS.join(", ")
+        )));
+    }
+
+    let words = py.allow_threads(|| {
+        run_in_pool(num_threads, || {
+            let counts = xml_paths
+                .par_iter()
+                .fold(HashMap::new, |mut counts, xml_path| {
+                    match read_and_extract(xml_path, &ExtractOptions::default()) {
+                        Ok(metadata) => {
+                            if let Some(text) = article_field_text(&metadata, field) {
+                                count_words(&text, min_length, &mut counts);
+                            }
+                        }
+                        Err(e) => e.report(xml_path),
+                    }
+                    counts
+                })
+                .reduce(HashMap::new, |mut total, counts| {
+                    for (word, count) in counts {
+                        *total.entry(word).or_default() += count;
+                    }
+                    total
+                });
+            top_words(counts, top_n)
+        })
+    })?;
+
+    let (word_column, count_column): (Vec<String>, Vec<u64>) = words.into_iter().unzip();
+    let df = df! {
+        "word" => word_column,
+        "count" => count_column,
+    }
+    .map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Failed to create word frequency DataFrame: {e}"
+        ))
+    })?;
+
+    Ok(PyDataFrame(df))
+}
+
+/// Tag the articles with a controlled vocabulary: `term_dict` maps a category
+/// (e.g. "disease") to its terms, matched as whole words, ignoring case, in
+/// one text field (one of `SEARCHABLE_FIELDS`). One `file_path`/`category`/
+/// `term`/`count` row per term found in a file, in input file order, then by
+/// category name and term order. Files that can't be read or parsed are logged
+/// and skipped.
+#[pyfunction(signature = (xml_paths, term_dict, field="abstract", num_threads=None))]
+pub fn tag_terms(
+    py: Python,
+    xml_paths: Vec<String>,
+    term_dict: HashMap<String, Vec<String>>,
+    field: &str,
+    num_threads: Option<usize>,
+) -> PyResult<PyDataFrame> {
+    if !SEARCHABLE_FIELDS.contains(&field) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Unknown field '{field}' (expected one of {})",
+            SEARCHABLE_FIELDS.join(", ")
+        )));
+    }
+
+    let mut vocabulary: Vec<(String, Vec<String>)> = term_dict.into_iter().collect();
+    vocabulary.sort_by(|(a, _), (b, _)| a.cmp(b));
+    let tagger = TermTagger::new(&vocabulary).map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Failed to compile term_dict: {e}"))
+    })?;
+    let options = ExtractOptions {
+        skip_body_text: field != "full_text",
+        ..Default::default()
+    };
+
+    let tags_per_file = py.allow_threads(|| {
+        run_in_pool(num_threads, || {
+            xml_paths
+                .par_iter()
+                .map(|xml_path| match read_and_extract(xml_path, &options) {
+                    Ok(metadata) => article_field_text(&metadata, field)
+                        .map(|text| {
+                            tagger
+                                .tag(&text)
+                                .into_iter()
+                                .map(|(category, term, count)| {
+                                    (category.to_string(), term.to_string(), count)
+                                })
+                                .collect()
+                        })
+                        .unwrap_or_default(),
+                    Err(e) => {
+                        e.report(xml_path);
+                        Vec::new()
+                    }
+                })
+                .collect::<Vec<Vec<_>>>()
+        })
+    })?;
+
+    let mut file_paths = Vec::new();
+    let mut categories = Vec::new();
+    let mut terms = Vec::new();
+    let mut counts = Vec::new();
+    for (xml_path, tags) in xml_paths.iter().zip(tags_per_file) {
+        for (category, term, count) in tags {
+            file_paths.push(xml_path.clone());
+            categories.push(category);
+            terms.push(term);
+            counts.push(count);
+        }
+    }
+
+    let df = df! {
+        "file_path" => file_paths,
+        "category" => categories,
+        "term" => terms,
+        "count" => counts,
+    }
+    .map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Failed to create term tags DataFrame: {e}"
+        ))
+    })?;
+
+    Ok(PyDataFrame(df))
+}
+
+/// Built-in (accession type, regex) pairs used by `extract_accessions`
+const ACCESSION_PATTERNS: &[(&str, &str)] = &[
+    // Nucleotide/protein accessions: 1 letter + 5 digits or 2 letters + 6
+    // digits, with an optional version
+    ("genbank", r"\b(?:[A-Z]\d{5}|[A-Z]{2}\d{6})(?:\.\d+)?\b"),
+    ("geo", r"\bG(?:SE|SM|PL|DS)\d+\b"),
+    ("sra", r"\b[SED]R[APRSXZ]\d{6,}\b"),
+    ("pdb", r"\b[1-9][A-Za-z0-9]{3}\b"),
+];
+
+/// Whether a built-in PDB match looks like an ID rather than a number, year
+/// or unit ("2019", "10th", "20mg"): it needs an uppercase letter and no
+/// lowercase ones
+fn is_plausible_pdb_id(id: &str) -> bool {
+    id.chars().any(|c| c.is_ascii_uppercase()) && !id.chars().any(|c| c.is_ascii_lowercase())
+}
+
+/// Find database accession numbers (GenBank, GEO, SRA, PDB) in the extracted
+/// full text of XML files, one row per occurrence in document order. Entries
+/// of `patterns` (accession type -> regex) replace the built-in regex of that
+/// type or add a new type.
+#[pyfunction(signature = (xml_paths, patterns=None, context_chars=100))]
+pub fn extract_accessions(
+    py: Python,
+    xml_paths: Vec<String>,
+    patterns: Option<HashMap<String, String>>,
+    context_chars: usize,
+) -> PyResult<PyDataFrame> {
+    let patterns = patterns.unwrap_or_default();
+    let compile = |accession_type: &str, pattern: &str| {
+        Regex::new(pattern).map_err(|e| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Invalid regex pattern '{pattern}' for '{accession_type}': {e}"
+            ))
+        })
+    };
+
+    // (type, regex, whether matches must pass the PDB check)
+    let mut accession_regexes: Vec<(String, Regex, bool)> = Vec::new();
+    for (accession_type, pattern) in ACCESSION_PATTERNS {
+        if !patterns.contains_key(*accession_type) {
+            let regex = compile(accession_type, pattern)?;
+            accession_regexes.push((accession_type.to_string(), regex, *accession_type == "pdb"));
+        }
+    }
+    for (accession_type, pattern) in &patterns {
+        let regex = compile(accession_type, pattern)?;
+        accession_regexes.push((accession_type.clone(), regex, false));
+    }
+
+    let result = py.allow_threads(|| {
+        let mut file_paths = Vec::new();
+        let mut accession_types = Vec::new();
+        let mut accession_ids = Vec::new();
+        let mut contexts = Vec::new();
+
+        for xml_path in &xml_paths {
+            let full_text = match read_and_extract(xml_path, &ExtractOptions::default()) {
+                Ok(metadata) => metadata.full_text.unwrap_or_default(),
+                Err(e) => {
+                    e.report(xml_path);
+                    continue;
+                }
+            };
+
+            // (start, type, id, context), sorted so rows follow the text
+            let mut found: Vec<(usize, &str, &str, &str)> = Vec::new();
+            for (accession_type, regex, is_builtin_pdb) in &accession_regexes {
+                for mat in regex.find_iter(&full_text) {
+                    if *is_builtin_pdb && !is_plausible_pdb_id(mat.as_str()) {
+                        continue;
+                    }
+                    found.push((
+                        mat.start(),
+                        accession_type,
+                        mat.as_str(),
+                        match_context(&full_text, mat.start(), mat.end(), context_chars),
+                    ));
+                }
+            }
+            found.sort_by_key(|&(start, accession_type, _, _)| (start, accession_type));
+
+            for (_, accession_type, accession_id, context) in found {
+                file_paths.push(xml_path.clone());
+                accession_types.push(accession_type.to_string());
+                accession_ids.push(accession_id.to_string());
+                contexts.push(context.to_string());
+            }
+        }
+
+        df! {
+            "file_path" => &file_paths,
+            "accession_type" => &accession_types,
+            "accession_id" => &accession_ids,
+            "context" => &contexts,
+        }
+    });
+
+    let df = result.map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Failed to create accessions DataFrame: {e}"
         ))
     })?;
 
@@ -0,0 +1,50 @@
+//! Forwards `log` records to Python's `logging` module, so warnings about
+//! skipped files can be filtered and routed like any other Python logger.
+//! Records from `xml_processor::nxml` go to the `xml_processor.nxml` logger.
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use pyo3::prelude::*;
+
+struct PythonLogger;
+
+impl Log for PythonLogger {
+    fn enabled(&self, _metadata: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        // Python's numeric levels
+        let level = match record.level() {
+            Level::Error => 40,
+            Level::Warn => 30,
+            Level::Info => 20,
+            Level::Debug => 10,
+            Level::Trace => 5,
+        };
+        let name = record.target().replace("::", ".");
+        let message = record.args().to_string();
+
+        // Called from rayon workers too; the parallel work always runs with
+        // the GIL released, so taking it here can't deadlock
+        Python::with_gil(|py| {
+            let logged = py
+                .import("logging")
+                .and_then(|logging| logging.call_method1("getLogger", (name,)))
+                .and_then(|logger| logger.call_method1("log", (level, message)));
+            if let Err(e) = logged {
+                e.print(py);
+            }
+        });
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: PythonLogger = PythonLogger;
+
+/// Install the bridge as the `log` logger; a no-op if a logger is already set
+pub fn init() {
+    if log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(LevelFilter::Info);
+    }
+}
@@ -0,0 +1,1575 @@
+//! The extraction API used as a plain Rust library, without Python
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use polars::prelude::{DataType, Field};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
+use xml_processor::core::{
+    build_article_index, count_words, extract_cross_references, extract_named_entities,
+    extract_references, extract_supplementary, extract_tables, for_each_archive_article,
+    for_each_article, for_each_article_buffered, fuzzy_matches, highlight_ranges, normalize_doi,
+    normalize_pmid, open_ndjson_output, read_and_extract, read_article_at, split_sentences,
+    top_words, validate_articles, write_json_array, write_ndjson_with_summary, write_split_ndjson,
+    ProcessingSummary, TermTagger,
+};
+use xml_processor::{
+    articles_to_dataframe, articles_to_dataframe_with_columns, articles_to_dataframe_with_options,
+    extract_article_metadata, extract_article_metadata_with_options, ExtractOptions,
+    XmlProcessorError, SCHEMA_VERSION,
+};
+
+const ARTICLE: &str = r#"<article article-type="research-article">
+<front><article-meta>
+<article-id pub-id-type="pmid">12345</article-id>
+<title-group><article-title>Native extraction</article-title></title-group>
+<contrib-group><contrib contrib-type="author"><name><surname>Doe</surname><given-names>Jane</given-names></name></contrib></contrib-group>
+<abstract><p>An abstract.</p></abstract>
+</article-meta></front>
+<body><p>Body text.</p></body>
+</article>"#;
+
+fn temp_file(name: &str, contents: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("xml_processor_{}_{name}", std::process::id()));
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+/// A ustar member: header block followed by the data padded to 512 bytes
+fn tar_member(name: &str, data: &[u8]) -> Vec<u8> {
+    let mut header = [0u8; 512];
+    header[..name.len()].copy_from_slice(name.as_bytes());
+    header[100..107].copy_from_slice(b"0000644");
+    header[124..135].copy_from_slice(format!("{:011o}", data.len()).as_bytes());
+    header[156] = b'0';
+    header[257..263].copy_from_slice(b"ustar\0");
+    header[148..156].fill(b' ');
+    let checksum: u32 = header.iter().map(|&b| u32::from(b)).sum();
+    header[148..155].copy_from_slice(format!("{checksum:06o}\0").as_bytes());
+
+    let mut member = header.to_vec();
+    member.extend_from_slice(data);
+    member.resize(512 + data.len().div_ceil(512) * 512, 0);
+    member
+}
+
+#[test]
+fn extracts_metadata_from_a_string() {
+    let metadata = extract_article_metadata(ARTICLE, "article.xml").unwrap();
+    assert_eq!(metadata.pmid.as_deref(), Some("12345"));
+    assert_eq!(metadata.title.as_deref(), Some("Native extraction"));
+    assert_eq!(metadata.authors, vec!["Doe, Jane"]);
+    assert_eq!(metadata.full_text.as_deref(), Some("Body text."));
+}
+
+#[test]
+fn normalizes_prefixed_identifiers() {
+    let xml = ARTICLE.replace(
+        "<article-id pub-id-type=\"pmid\">12345</article-id>",
+        "<article-id pub-id-type=\"pmid\">PMID: 12345 </article-id><article-id pub-id-type=\"pmc\">pmc678</article-id>",
+    );
+
+    let metadata = extract_article_metadata(&xml, "prefixed.xml").unwrap();
+
+    assert_eq!(metadata.pmid.as_deref(), Some("12345"));
+    assert_eq!(metadata.pmc_id.as_deref(), Some("PMC678"));
+    assert_eq!(normalize_pmid("12345a"), None);
+    assert_eq!(
+        normalize_doi("https://doi.org/10.1000/ABC").as_deref(),
+        Some("10.1000/abc")
+    );
+}
+
+#[test]
+fn reads_volume_issue_and_pages() {
+    let article = ARTICLE.replace(
+        "<abstract>",
+        "<volume>12</volume><issue>3</issue><fpage>45</fpage><lpage>67</lpage>\n<abstract>",
+    );
+
+    let metadata = extract_article_metadata(&article, "citation.xml").unwrap();
+
+    assert_eq!(metadata.volume.as_deref(), Some("12"));
+    assert_eq!(metadata.issue.as_deref(), Some("3"));
+    assert_eq!(metadata.first_page.as_deref(), Some("45"));
+    assert_eq!(metadata.last_page.as_deref(), Some("67"));
+}
+
+#[test]
+fn takes_the_doi_from_the_elocation_id_without_an_article_id() {
+    let article = ARTICLE.replace(
+        "<title-group>",
+        "<elocation-id>10.1371/journal.pone.0123456</elocation-id>\n<title-group>",
+    );
+
+    let metadata = extract_article_metadata(&article, "elocation.xml").unwrap();
+
+    assert_eq!(
+        metadata.doi.as_deref(),
+        Some("10.1371/journal.pone.0123456")
+    );
+    assert_eq!(metadata.doi_source.as_deref(), Some("elocation-id"));
+}
+
+#[test]
+fn reads_received_and_accepted_dates_from_the_history() {
+    let article = ARTICLE.replace(
+        "<abstract>",
+        r#"<history>
+<date date-type="received"><day>5</day><month>1</month><year>2022</year></date>
+<date date-type="accepted"><day>20</day><month>Mar</month><year>2022</year></date>
+</history>
+<abstract>"#,
+    );
+
+    let metadata = extract_article_metadata(&article, "history.xml").unwrap();
+
+    assert_eq!(metadata.date_received.as_deref(), Some("2022-01-05"));
+    assert_eq!(metadata.date_accepted.as_deref(), Some("2022-03-20"));
+}
+
+#[test]
+fn serializes_the_schema_version_first() {
+    let metadata = extract_article_metadata(ARTICLE, "article.xml").unwrap();
+    let json = serde_json::to_string(&metadata).unwrap();
+    assert!(json.starts_with(&format!(r#"{{"schema_version":{SCHEMA_VERSION},"#)));
+}
+
+#[test]
+fn keeps_only_the_requested_sections_in_the_full_text() {
+    let xml = r#"<article><body>
+<sec sec-type="intro"><title>Introduction</title><p>Why we did it.</p></sec>
+<sec><title>Materials and Methods</title><p>How we did it.</p>
+<sec><title>Statistics</title><p>Tests used.</p></sec></sec>
+<sec sec-type="results"><title>Results</title><p>What we found.</p></sec>
+</body></article>"#;
+    let options = ExtractOptions {
+        sections_filter: Some(vec!["methods".into(), "materials-and-methods".into()]),
+        ..Default::default()
+    };
+
+    let filtered = extract_article_metadata_with_options(xml, "article.xml", &options).unwrap();
+    let unfiltered = extract_article_metadata(xml, "article.xml").unwrap();
+
+    assert_eq!(
+        filtered.full_text.as_deref(),
+        Some("Materials and Methods How we did it. Statistics Tests used.")
+    );
+    assert!(unfiltered.full_text.unwrap().contains("What we found."));
+}
+
+#[test]
+fn leaves_xref_markers_out_of_the_body_on_request() {
+    let article = ARTICLE.replace(
+        "<p>Body text.</p>",
+        r#"<p>As shown before <xref ref-type="bibr" rid="B1">[1]</xref>, see <xref ref-type="fig" rid="F2">Fig. 2</xref>.</p>"#,
+    );
+
+    let kept = extract_article_metadata(&article, "xref.xml").unwrap();
+    let stripped = extract_article_metadata_with_options(
+        &article,
+        "xref.xml",
+        &ExtractOptions {
+            strip_xref: true,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    let kept = kept.full_text.unwrap();
+    let stripped = stripped.full_text.unwrap();
+    assert!(kept.contains("[1]") && kept.contains("Fig. 2"));
+    assert!(!stripped.contains("[1]") && !stripped.contains("Fig. 2"));
+    assert!(stripped.contains("As shown before"));
+}
+
+#[test]
+fn collapses_newlines_and_tabs_on_request() {
+    let article = ARTICLE.replace("Native extraction", "Native\n\textraction  of\r\n   text");
+
+    let metadata = extract_article_metadata_with_options(
+        &article,
+        "whitespace.xml",
+        &ExtractOptions {
+            normalize_whitespace: true,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    assert_eq!(metadata.title.as_deref(), Some("Native extraction of text"));
+}
+
+#[test]
+fn lowercases_text_fields_but_not_identifiers() {
+    let xml = ARTICLE.replace(
+        "<article-id pub-id-type=\"pmid\">12345</article-id>",
+        "<article-id pub-id-type=\"pmid\">12345</article-id><article-id pub-id-type=\"pmc\">PMC777</article-id>",
+    );
+    let options = ExtractOptions {
+        lowercase: true,
+        ..Default::default()
+    };
+
+    let metadata = extract_article_metadata_with_options(&xml, "mixed.xml", &options).unwrap();
+
+    assert_eq!(metadata.title.as_deref(), Some("native extraction"));
+    assert_eq!(metadata.abstract_text.as_deref(), Some("an abstract."));
+    assert_eq!(metadata.pmc_id.as_deref(), Some("PMC777"));
+}
+
+#[test]
+fn writes_subscript_digits_as_unicode_on_request() {
+    let xml = ARTICLE.replace(
+        "Native extraction",
+        "Rising CO<sub>2</sub> and m<sup>2</sup> of H<sub>n</sub>",
+    );
+    let options = ExtractOptions {
+        unicode_superscripts: true,
+        ..Default::default()
+    };
+
+    let plain = extract_article_metadata(&xml, "co2.xml").unwrap();
+    let unicode = extract_article_metadata_with_options(&xml, "co2.xml", &options).unwrap();
+
+    assert!(plain.title.unwrap().contains("CO2"));
+    let title = unicode.title.unwrap();
+    assert!(title.contains("CO\u{2082}"));
+    assert!(title.contains("m\u{b2}"));
+    assert!(title.contains("Hn"));
+}
+
+#[test]
+fn decodes_entity_and_character_references() {
+    let article = ARTICLE
+        .replace(
+            "Native extraction",
+            "&#x3b2;-catenin and IL-6 &amp; &alpha;-cells",
+        )
+        .replace("Body text.", "Doses &ge; 5 &mu;M.");
+    let metadata = extract_article_metadata(&article, "article.xml").unwrap();
+    assert_eq!(
+        metadata.title.as_deref(),
+        Some("β-catenin and IL-6 & α-cells")
+    );
+    assert_eq!(metadata.full_text.as_deref(), Some("Doses ≥ 5 μM."));
+}
+
+#[test]
+fn keeps_the_raw_abstract_markup_on_request() {
+    let options = ExtractOptions {
+        keep_raw_abstract: true,
+        ..Default::default()
+    };
+    let metadata = extract_article_metadata_with_options(ARTICLE, "article.xml", &options).unwrap();
+    assert_eq!(
+        metadata.abstract_xml.as_deref(),
+        Some("<p>An abstract.</p>")
+    );
+    assert_eq!(metadata.abstract_text.as_deref(), Some("An abstract."));
+
+    let metadata = extract_article_metadata(ARTICLE, "article.xml").unwrap();
+    assert_eq!(metadata.abstract_xml, None);
+}
+
+#[test]
+fn reads_the_subtitle_and_translated_titles() {
+    let article = ARTICLE.replace(
+        "<title-group><article-title>Native extraction</article-title></title-group>",
+        r#"<title-group><article-title>Native extraction</article-title>
+<subtitle>A worked example</subtitle>
+<trans-title-group xml:lang="fr"><trans-title>Extraction native</trans-title></trans-title-group>
+</title-group>"#,
+    );
+
+    let metadata = extract_article_metadata(&article, "titles.xml").unwrap();
+
+    assert_eq!(metadata.title.as_deref(), Some("Native extraction"));
+    assert_eq!(metadata.subtitle.as_deref(), Some("A worked example"));
+    assert_eq!(
+        metadata.trans_titles,
+        vec![("fr".to_string(), "Extraction native".to_string())]
+    );
+}
+
+#[test]
+fn finds_an_abstract_outside_the_front_matter() {
+    let xml = r#"<article><front><article-meta>
+<title-group><article-title>A converted preprint</article-title></title-group>
+</article-meta></front>
+<body><abstract><p>An abstract in the body.</p></abstract><p>Body text.</p></body></article>"#;
+    let with_front_abstract = xml.replace(
+        "</article-meta>",
+        "<abstract><p>The front abstract.</p></abstract></article-meta>",
+    );
+
+    let metadata = extract_article_metadata(xml, "preprint.xml").unwrap();
+    let both = extract_article_metadata(&with_front_abstract, "preprint.xml").unwrap();
+
+    assert_eq!(
+        metadata.abstract_text.as_deref(),
+        Some("An abstract in the body.")
+    );
+    assert_eq!(both.abstract_text.as_deref(), Some("The front abstract."));
+}
+
+#[test]
+fn keeps_a_graphical_abstract_apart_from_the_abstract() {
+    let article = ARTICLE.replace(
+        "<abstract><p>An abstract.</p></abstract>",
+        r#"<abstract><p>An abstract.</p></abstract>
+<abstract abstract-type="graphical"><title>Graphical abstract</title>
+<fig><caption><p>Pathway overview.</p></caption><graphic xlink:href="ga1.jpg"/></fig></abstract>"#,
+    );
+    let metadata = extract_article_metadata(&article, "article.xml").unwrap();
+    assert_eq!(metadata.abstract_text.as_deref(), Some("An abstract."));
+    assert_eq!(metadata.graphical_abstract_href.as_deref(), Some("ga1.jpg"));
+    assert_eq!(
+        metadata.graphical_abstract_caption.as_deref(),
+        Some("Pathway overview.")
+    );
+}
+
+#[test]
+fn extracts_credit_roles_per_author() {
+    let xml = r#"<article><front><article-meta>
+<contrib-group>
+<contrib contrib-type="author"><name><surname>Doe</surname><given-names>Jane</given-names></name>
+<role vocab="credit" vocab-term="Conceptualization">Conceptualization</role>
+<role vocab="credit" vocab-term="Writing &#x2013; original draft">Writing &#x2013; original draft</role>
+</contrib>
+<contrib contrib-type="author"><name><surname>Roe</surname><given-names>Rick</given-names></name></contrib>
+</contrib-group>
+<author-notes><fn fn-type="con"><p>JD designed the study.</p></fn></author-notes>
+</article-meta></front></article>"#;
+
+    let metadata = extract_article_metadata(xml, "article.xml").unwrap();
+
+    assert_eq!(
+        metadata.author_roles,
+        vec![
+            vec![
+                "Conceptualization".to_string(),
+                "Writing \u{2013} original draft".to_string(),
+            ],
+            vec![],
+        ]
+    );
+    assert_eq!(
+        metadata.contributions_text.as_deref(),
+        Some("JD designed the study.")
+    );
+}
+
+#[test]
+fn aligns_orcids_with_authors_that_lack_one() {
+    let xml = r#"<article><front><article-meta><contrib-group>
+<contrib contrib-type="author"><contrib-id contrib-id-type="orcid">https://orcid.org/0000-0002-1825-0097</contrib-id><name><surname>Doe</surname><given-names>Jane</given-names></name></contrib>
+<contrib contrib-type="author"><name><surname>Roe</surname><given-names>Rick</given-names></name></contrib>
+<contrib contrib-type="author"><contrib-id contrib-id-type="orcid">0000-0001-5109-351x</contrib-id><name><surname>Poe</surname><given-names>Pat</given-names></name></contrib>
+</contrib-group></article-meta></front></article>"#;
+
+    let metadata = extract_article_metadata(xml, "orcids.xml").unwrap();
+
+    assert_eq!(metadata.authors, vec!["Doe, Jane", "Roe, Rick", "Poe, Pat"]);
+    assert_eq!(
+        metadata.orcids,
+        vec![
+            Some("0000-0002-1825-0097".to_string()),
+            None,
+            Some("0000-0001-5109-351X".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn lists_each_award_of_a_funding_group() {
+    let xml = r#"<article><front><article-meta><funding-group>
+<award-group id="award1">
+<funding-source><institution-wrap><institution-id institution-id-type="doi">10.13039/100000002</institution-id><institution>National Institutes of Health</institution></institution-wrap></funding-source>
+<award-id>R01 GM123456</award-id>
+</award-group>
+<award-group id="award2">
+<funding-source>Wellcome Trust</funding-source>
+<award-id>208349/Z/17/Z</award-id>
+</award-group>
+</funding-group></article-meta></front></article>"#;
+
+    let metadata = extract_article_metadata(xml, "funding.xml").unwrap();
+
+    let awards: Vec<_> = metadata
+        .funding
+        .iter()
+        .map(|award| {
+            (
+                award.funder.as_str(),
+                award.funder_id.as_deref(),
+                award.award_id.as_deref(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        awards,
+        vec![
+            (
+                "National Institutes of Health",
+                Some("10.13039/100000002"),
+                Some("R01 GM123456")
+            ),
+            ("Wellcome Trust", None, Some("208349/Z/17/Z")),
+        ]
+    );
+}
+
+#[test]
+fn lists_references_with_their_dois() {
+    let xml = r#"<article><back><ref-list>
+<ref id="B1"><label>1</label><element-citation publication-type="journal">
+<person-group person-group-type="author"><name><surname>Smith</surname><given-names>A</given-names></name></person-group>
+<article-title>First cited work</article-title><year>2019</year>
+<pub-id pub-id-type="doi">10.1000/first</pub-id>
+</element-citation></ref>
+<ref id="B2"><label>2</label><mixed-citation publication-type="journal">Jones B. Second cited work. 2020.
+<pub-id pub-id-type="doi">10.1000/second</pub-id><pub-id pub-id-type="pmid">31234567</pub-id>
+</mixed-citation></ref>
+</ref-list></back></article>"#;
+
+    let references = extract_references(xml).unwrap();
+
+    assert_eq!(references.len(), 2);
+    assert_eq!(references[0].id.as_deref(), Some("B1"));
+    assert_eq!(references[0].doi.as_deref(), Some("10.1000/first"));
+    assert_eq!(references[0].title.as_deref(), Some("First cited work"));
+    assert_eq!(references[0].year.as_deref(), Some("2019"));
+    assert_eq!(references[1].id.as_deref(), Some("B2"));
+    assert_eq!(references[1].doi.as_deref(), Some("10.1000/second"));
+    assert_eq!(references[1].pmid.as_deref(), Some("31234567"));
+}
+
+#[test]
+fn reads_the_cells_of_a_table() {
+    let xml = r#"<article><body><table-wrap id="T1"><label>Table 1</label>
+<caption><p>Sample counts.</p></caption>
+<table><thead><tr><th>Group</th><th>n</th><th>Age</th></tr></thead>
+<tbody><tr><td>Cases</td><td>12</td><td><italic>54</italic></td></tr></tbody></table>
+</table-wrap></body></article>"#;
+
+    let tables = extract_tables(xml).unwrap();
+
+    assert_eq!(tables.len(), 1);
+    assert_eq!(tables[0].id.as_deref(), Some("T1"));
+    assert_eq!(tables[0].label.as_deref(), Some("Table 1"));
+    assert_eq!(tables[0].caption, "Sample counts.");
+    assert_eq!(
+        tables[0].rows,
+        vec![vec!["Group", "n", "Age"], vec!["Cases", "12", "54"]]
+    );
+}
+
+#[test]
+fn reads_issns_and_publisher_from_journal_meta() {
+    let xml = r#"<article><front><journal-meta>
+<journal-id journal-id-type="nlm-ta">PLoS One</journal-id>
+<journal-title-group><journal-title>PLoS ONE</journal-title>
+<abbrev-journal-title abbrev-type="publisher">PLoS ONE</abbrev-journal-title></journal-title-group>
+<issn pub-type="ppub">1234-5678</issn>
+<issn pub-type="epub">1932-6203</issn>
+<publisher><publisher-name>Public Library of Science</publisher-name></publisher>
+</journal-meta></front></article>"#;
+
+    let metadata = extract_article_metadata(xml, "article.xml").unwrap();
+
+    assert_eq!(metadata.journal.as_deref(), Some("PLoS ONE"));
+    assert_eq!(metadata.issn_print.as_deref(), Some("1234-5678"));
+    assert_eq!(metadata.issn_electronic.as_deref(), Some("1932-6203"));
+    assert_eq!(metadata.journal_abbrev.as_deref(), Some("PLoS One"));
+    assert_eq!(
+        metadata.publisher_name.as_deref(),
+        Some("Public Library of Science")
+    );
+}
+
+#[test]
+fn lists_a_collab_as_a_group_author() {
+    let xml = r#"<article><front><article-meta><contrib-group>
+<contrib contrib-type="author"><name><surname>Doe</surname><given-names>Jane</given-names></name></contrib>
+<contrib contrib-type="author"><collab>Human Genome <italic>Sequencing</italic> Consortium</collab></contrib>
+</contrib-group></article-meta></front></article>"#;
+
+    let metadata = extract_article_metadata(xml, "collab.xml").unwrap();
+
+    assert_eq!(
+        metadata.authors,
+        vec!["Doe, Jane", "Human Genome Sequencing Consortium"]
+    );
+    assert_eq!(metadata.author_is_collab, vec![false, true]);
+    assert_eq!(
+        metadata.author_surnames,
+        vec![Some("Doe".to_string()), None]
+    );
+}
+
+#[test]
+fn lists_a_pubmed_collective_name_after_an_affiliated_author() {
+    let xml = r#"<PubmedArticle><MedlineCitation><PMID>123</PMID><Article>
+<AuthorList>
+<Author><LastName>Doe</LastName><ForeName>Jane</ForeName><Initials>J</Initials>
+<AffiliationInfo><Affiliation>Dept of Genetics, Example University</Affiliation></AffiliationInfo>
+</Author>
+<Author><CollectiveName>Human Genome Sequencing Consortium</CollectiveName></Author>
+</AuthorList>
+</Article></MedlineCitation></PubmedArticle>"#;
+
+    let metadata = extract_article_metadata(xml, "pubmed_collab.xml").unwrap();
+
+    assert_eq!(
+        metadata.authors,
+        vec!["Doe, Jane", "Human Genome Sequencing Consortium"]
+    );
+    assert_eq!(metadata.author_is_collab, vec![false, true]);
+    assert_eq!(
+        metadata.affiliations,
+        vec![
+            vec!["Dept of Genetics, Example University".to_string()],
+            vec![]
+        ]
+    );
+}
+
+#[test]
+fn reads_the_same_fields_from_pubmed_as_from_jats() {
+    let pubmed = r#"<PubmedArticle><MedlineCitation><PMID>12345</PMID><Article>
+<Journal><JournalIssue><Volume>7</Volume><Issue>2</Issue><PubDate><Year>2021</Year><Month>Mar</Month><Day>4</Day></PubDate></JournalIssue>
+<Title>Journal of Examples</Title></Journal>
+<ArticleTitle>Native extraction</ArticleTitle>
+<Abstract><AbstractText>An abstract.</AbstractText></Abstract>
+<AuthorList><Author><LastName>Doe</LastName><ForeName>Jane</ForeName><Initials>J</Initials></Author></AuthorList>
+</Article></MedlineCitation>
+<PubmedData><ArticleIdList><ArticleId IdType="doi">10.1000/example.1</ArticleId></ArticleIdList></PubmedData>
+</PubmedArticle>"#;
+    let jats = r#"<article><front>
+<journal-meta><journal-title-group><journal-title>Journal of Examples</journal-title></journal-title-group></journal-meta>
+<article-meta>
+<article-id pub-id-type="pmid">12345</article-id>
+<article-id pub-id-type="doi">10.1000/example.1</article-id>
+<title-group><article-title>Native extraction</article-title></title-group>
+<contrib-group><contrib contrib-type="author"><name><surname>Doe</surname><given-names>Jane</given-names></name></contrib></contrib-group>
+<pub-date pub-type="epub"><day>4</day><month>3</month><year>2021</year></pub-date>
+<volume>7</volume><issue>2</issue>
+<abstract><p>An abstract.</p></abstract>
+</article-meta></front></article>"#;
+
+    let from_pubmed = extract_article_metadata(pubmed, "pubmed.xml").unwrap();
+    let from_jats = extract_article_metadata(jats, "jats.xml").unwrap();
+
+    assert_eq!(from_pubmed.pmid, from_jats.pmid);
+    assert_eq!(from_pubmed.doi, from_jats.doi);
+    assert_eq!(from_pubmed.title, from_jats.title);
+    assert_eq!(from_pubmed.abstract_text, from_jats.abstract_text);
+    assert_eq!(from_pubmed.authors, from_jats.authors);
+    assert_eq!(from_pubmed.author_surnames, from_jats.author_surnames);
+    assert_eq!(from_pubmed.journal, from_jats.journal);
+    assert_eq!(from_pubmed.publication_date, from_jats.publication_date);
+    assert_eq!(from_pubmed.volume, from_jats.volume);
+    assert_eq!(from_pubmed.issue, from_jats.issue);
+    assert_eq!(from_pubmed.publication_date.as_deref(), Some("2021-03-04"));
+}
+
+#[test]
+fn ignores_article_ids_of_pubmed_references() {
+    let xml = r#"<PubmedArticle><MedlineCitation><PMID>12345</PMID><Article>
+<ArticleTitle>Native extraction</ArticleTitle>
+</Article></MedlineCitation>
+<PubmedData>
+<ArticleIdList>
+<ArticleId IdType="pubmed">12345</ArticleId>
+<ArticleId IdType="doi">10.1000/example.1</ArticleId>
+<ArticleId IdType="pmc">PMC1111111</ArticleId>
+</ArticleIdList>
+<ReferenceList>
+<Reference><Citation>First cited work.</Citation><ArticleIdList>
+<ArticleId IdType="doi">10.1000/cited.1</ArticleId>
+<ArticleId IdType="pmc">PMC2222222</ArticleId>
+</ArticleIdList></Reference>
+<Reference><Citation>Second cited work.</Citation><ArticleIdList>
+<ArticleId IdType="doi">10.1000/cited.2</ArticleId>
+<ArticleId IdType="pmc">PMC3333333</ArticleId>
+</ArticleIdList></Reference>
+</ReferenceList>
+</PubmedData>
+</PubmedArticle>"#;
+
+    let metadata = extract_article_metadata(xml, "pubmed.xml").unwrap();
+
+    assert_eq!(metadata.pmid.as_deref(), Some("12345"));
+    assert_eq!(metadata.doi.as_deref(), Some("10.1000/example.1"));
+    assert_eq!(metadata.doi_source.as_deref(), Some("article-id"));
+    assert_eq!(metadata.pmc_id.as_deref(), Some("PMC1111111"));
+}
+
+#[test]
+fn skips_empty_pubmed_journal_identifiers() {
+    let xml = r#"<PubmedArticle><MedlineCitation><PMID>12345</PMID><Article>
+<Journal><ISSN IssnType="Print"></ISSN><ISSN IssnType="Electronic">1234-5678</ISSN>
+<Title>Journal of Examples</Title><ISOAbbreviation>J Ex</ISOAbbreviation></Journal>
+<ArticleTitle>Native extraction</ArticleTitle>
+</Article>
+<MedlineJournalInfo><MedlineTA></MedlineTA></MedlineJournalInfo>
+</MedlineCitation></PubmedArticle>"#;
+
+    let metadata = extract_article_metadata(xml, "pubmed.xml").unwrap();
+
+    assert_eq!(metadata.issn_print, None);
+    assert_eq!(metadata.issn_electronic.as_deref(), Some("1234-5678"));
+    assert_eq!(metadata.journal_abbrev.as_deref(), Some("J Ex"));
+}
+
+#[test]
+fn keeps_editors_apart_from_authors() {
+    let xml = r#"<article><front><article-meta>
+<contrib-group>
+<contrib contrib-type="author"><name><surname>Doe</surname><given-names>Jane</given-names></name></contrib>
+<contrib contrib-type="editor"><name><surname>Poe</surname><given-names>Ed</given-names></name></contrib>
+<contrib contrib-type="author"><name><surname>Roe</surname><given-names>Rick</given-names></name></contrib>
+</contrib-group>
+</article-meta></front></article>"#;
+
+    let metadata = extract_article_metadata(xml, "article.xml").unwrap();
+
+    assert_eq!(metadata.authors, vec!["Doe, Jane", "Roe, Rick"]);
+    assert_eq!(metadata.editors, vec!["Poe, Ed"]);
+    assert_eq!(metadata.orcids.len(), 2);
+}
+
+#[test]
+fn extracts_acknowledgements_and_competing_interests() {
+    let article = ARTICLE.replace(
+        "</body>",
+        r#"</body>
+<back><ack><title>Acknowledgements</title><p>We thank the sequencing core.</p></ack>
+<fn-group><fn fn-type="COI-statement"><p>The authors declare no competing interests.</p></fn></fn-group></back>"#,
+    );
+    let metadata = extract_article_metadata(&article, "article.xml").unwrap();
+    assert_eq!(
+        metadata.acknowledgements.as_deref(),
+        Some("We thank the sequencing core.")
+    );
+    assert_eq!(
+        metadata.conflict_of_interest.as_deref(),
+        Some("The authors declare no competing interests.")
+    );
+    assert_eq!(metadata.has_competing_interests, Some(false));
+}
+
+#[test]
+fn reads_the_article_type_from_the_root() {
+    let metadata = extract_article_metadata(ARTICLE, "article.xml").unwrap();
+
+    assert_eq!(metadata.article_type.as_deref(), Some("research-article"));
+}
+
+#[test]
+fn takes_the_language_from_the_root_element() {
+    let article = ARTICLE.replace("<article ", r#"<article xml:lang="es" "#);
+    let metadata = extract_article_metadata(&article, "article.xml").unwrap();
+    assert_eq!(metadata.language.as_deref(), Some("es"));
+}
+
+#[cfg(feature = "langdetect")]
+#[test]
+fn guesses_the_language_without_xml_lang() {
+    let article = ARTICLE.replace(
+        "An abstract.",
+        "We measured the expression of these genes in tissue from patients with the disease.",
+    );
+    let metadata = extract_article_metadata(&article, "article.xml").unwrap();
+    assert_eq!(metadata.language.as_deref(), Some("en"));
+}
+
+#[test]
+fn reads_the_data_availability_statement() {
+    let article = ARTICLE.replace(
+        "</body>",
+        "</body>\n<back><sec sec-type=\"data-availability\"><title>Data Availability</title>\
+         <p>Reads are available from GEO under GSE12345.</p></sec></back>",
+    );
+
+    let metadata = extract_article_metadata(&article, "data.xml").unwrap();
+
+    assert_eq!(
+        metadata.data_availability.as_deref(),
+        Some("Reads are available from GEO under GSE12345.")
+    );
+}
+
+#[test]
+fn finds_trial_numbers_mentioned_in_the_abstract() {
+    let article = ARTICLE.replace(
+        "An abstract.",
+        "Registered as NCT01234567 (see NCT01234567).",
+    );
+    let metadata = extract_article_metadata(&article, "article.xml").unwrap();
+    assert_eq!(metadata.trial_numbers, vec!["NCT01234567"]);
+}
+
+#[test]
+fn parses_a_bare_ampersand_only_with_lenient_xml() {
+    let article = ARTICLE.replace("Native extraction", "Funded by AT&T &amp; friends");
+    let path = temp_file("bare_ampersand.xml", &article);
+    let xml_path = path.to_string_lossy();
+
+    let lenient = read_and_extract(
+        &xml_path,
+        &ExtractOptions {
+            lenient_xml: true,
+            ..Default::default()
+        },
+    );
+    let strict = read_and_extract(&xml_path, &ExtractOptions::default());
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(
+        lenient.unwrap().title.as_deref(),
+        Some("Funded by AT&T & friends")
+    );
+    assert!(strict.is_err());
+}
+
+#[test]
+fn skips_a_file_just_over_max_bytes() {
+    let path = temp_file("oversized.xml", ARTICLE);
+    let xml_path = path.to_string_lossy();
+    let options_with_limit = |limit| ExtractOptions {
+        max_bytes: Some(limit),
+        ..Default::default()
+    };
+
+    let at_limit = read_and_extract(&xml_path, &options_with_limit(ARTICLE.len() as u64));
+    let over_limit = read_and_extract(&xml_path, &options_with_limit(ARTICLE.len() as u64 - 1));
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(at_limit.is_ok());
+    match over_limit {
+        Err(e @ XmlProcessorError::TooLarge { .. }) => assert_eq!(e.status(), "too_large"),
+        _ => panic!("expected the file to be refused as too large"),
+    }
+}
+
+#[test]
+fn retries_a_file_that_appears_while_reading() {
+    let path = std::env::temp_dir().join(format!(
+        "xml_processor_{}_late_article.xml",
+        std::process::id()
+    ));
+    let writer = {
+        let path = path.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            // Renamed into place so a read never sees it half-written
+            let partial = path.with_extension("partial");
+            std::fs::write(&partial, ARTICLE).unwrap();
+            std::fs::rename(&partial, &path).unwrap();
+        })
+    };
+    let options = ExtractOptions {
+        retries: 20,
+        retry_delay_ms: 25,
+        ..Default::default()
+    };
+
+    let metadata = read_and_extract(&path.to_string_lossy(), &options);
+    writer.join().unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(metadata.unwrap().pmid.as_deref(), Some("12345"));
+}
+
+#[test]
+fn appends_records_after_an_unterminated_last_line() {
+    let path = temp_file("append.ndjson", r#"{"pmid":"1"}"#);
+    let metadata = extract_article_metadata(ARTICLE, "article.xml").unwrap();
+
+    let mut output = open_ndjson_output(&path.to_string_lossy(), true).unwrap();
+    writeln!(output, "{}", serde_json::to_string(&metadata).unwrap()).unwrap();
+    drop(output);
+    let contents = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let lines: Vec<&str> = contents.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0], r#"{"pmid":"1"}"#);
+    assert!(lines[1].contains(r#""pmid":"12345""#));
+}
+
+#[test]
+fn tallies_the_outcome_of_each_file() {
+    let good = temp_file("summary_good.xml", ARTICLE);
+    let malformed = temp_file("summary_malformed.xml", "<article><front></article>");
+    let empty = temp_file("summary_empty.xml", "<?xml version=\"1.0\"?>\n");
+    let paths = vec![
+        good.to_string_lossy().into_owned(),
+        malformed.to_string_lossy().into_owned(),
+        empty.to_string_lossy().into_owned(),
+        "/nonexistent/article.xml".to_string(),
+    ];
+
+    let mut output = Vec::new();
+    let summary =
+        write_ndjson_with_summary(&paths, &mut output, &ExtractOptions::default()).unwrap();
+    for path in [&good, &malformed, &empty] {
+        std::fs::remove_file(path).unwrap();
+    }
+
+    let expected_bytes = (ARTICLE.len() + "<article><front></article>".len() + 22) as u64;
+    assert_eq!(
+        summary,
+        ProcessingSummary {
+            total_files: 4,
+            succeeded: 1,
+            read_failures: 1,
+            parse_failures: 1,
+            empty_documents: 1,
+            bytes_read: expected_bytes,
+        }
+    );
+    assert_eq!(output.iter().filter(|&&b| b == b'\n').count(), 1);
+}
+
+#[test]
+fn keeps_every_abstract_with_its_language() {
+    let xml = r#"<article xml:lang="fr"><front><article-meta>
+<title-group><article-title>Titre</article-title></title-group>
+<abstract xml:lang="fr"><p>Un résumé.</p></abstract>
+<abstract xml:lang="en"><p>An abstract.</p></abstract>
+<abstract><p>Plain summary.</p></abstract>
+</article-meta></front></article>"#;
+
+    let metadata = extract_article_metadata(xml, "bilingual.xml").unwrap();
+    let path = temp_file("bilingual.xml", xml);
+    let df = articles_to_dataframe(&[path.to_string_lossy().into_owned()]).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(
+        metadata.abstracts_by_language,
+        [
+            ("fr".to_string(), "Un résumé.".to_string()),
+            ("en".to_string(), "An abstract.".to_string()),
+            (String::new(), "Plain summary.".to_string()),
+        ]
+    );
+    assert_eq!(metadata.abstract_text.as_deref(), Some("An abstract."));
+    assert_eq!(
+        df.column("abstracts_by_language").unwrap().dtype(),
+        &DataType::List(Box::new(DataType::Struct(vec![
+            Field::new("lang".into(), DataType::String),
+            Field::new("text".into(), DataType::String),
+        ])))
+    );
+}
+
+#[test]
+fn reads_a_large_path_list_under_a_low_open_file_limit() {
+    let files: Vec<PathBuf> = (0..8)
+        .map(|i| temp_file(&format!("bounded_{i}.xml"), ARTICLE))
+        .collect();
+    let paths: Vec<String> = (0..2000)
+        .map(|i| files[i % files.len()].to_string_lossy().into_owned())
+        .collect();
+    let options = ExtractOptions {
+        max_open_files: Some(2),
+        ..Default::default()
+    };
+
+    let parsed: usize = std::thread::scope(|scope| {
+        let workers: Vec<_> = paths
+            .chunks(250)
+            .map(|chunk| {
+                scope.spawn(|| {
+                    chunk
+                        .iter()
+                        .filter(|path| read_and_extract(path, &options).is_ok())
+                        .count()
+                })
+            })
+            .collect();
+        workers.into_iter().map(|w| w.join().unwrap()).sum()
+    });
+    let df = articles_to_dataframe_with_options(&paths[..100], &options).unwrap();
+    for file in &files {
+        std::fs::remove_file(file).unwrap();
+    }
+
+    assert_eq!(parsed, paths.len());
+    assert_eq!(df.column("pmid").unwrap().null_count(), 0);
+}
+
+#[test]
+fn tags_vocabulary_terms_as_whole_words() {
+    let vocabulary = vec![(
+        "disease".to_string(),
+        vec!["asthma".to_string(), "COPD".to_string()],
+    )];
+    let tagger = TermTagger::new(&vocabulary).unwrap();
+    let abstracts = [
+        "Asthma and COPD overlap; asthma is more common in children.",
+        "Patients with copd but not asthmatic bronchitis.",
+    ];
+
+    let tags: Vec<_> = abstracts.iter().map(|text| tagger.tag(text)).collect();
+
+    assert_eq!(tags[0], [("disease", "asthma", 2), ("disease", "COPD", 1)]);
+    assert_eq!(tags[1], [("disease", "COPD", 1)]);
+}
+
+#[test]
+fn captures_the_article_version_and_source_mtime() {
+    let preprint = r#"<article article-type="preprint"><front><article-meta>
+<article-id pub-id-type="pmcid-ver">PMC98765.3</article-id>
+<article-version article-version-type="preprint-version">2.1</article-version>
+<title-group><article-title>A revised preprint</article-title></title-group>
+</article-meta></front></article>"#;
+    let path = temp_file("versioned.xml", preprint);
+
+    let metadata = read_and_extract(&path.to_string_lossy(), &ExtractOptions::default()).unwrap();
+    let without_version = preprint
+        .replace("<article-version", "<x")
+        .replace("</article-version>", "</x>");
+    let pmc_versioned = extract_article_metadata(&without_version, "v.xml").unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(metadata.article_version.as_deref(), Some("2.1"));
+    assert_eq!(metadata.pmc_id.as_deref(), Some("PMC98765"));
+    assert!(metadata.source_mtime.is_some_and(|mtime| mtime > 0));
+    assert_eq!(pmc_versioned.article_version.as_deref(), Some("3"));
+    assert_eq!(pmc_versioned.source_mtime, None);
+}
+
+#[test]
+fn finds_spelling_variants_within_the_edit_distance() {
+    let text = "Tumour growth; the tumor was small; humor aside.";
+
+    let matches = fuzzy_matches(text, "tumor", 1, false);
+
+    let found: Vec<_> = matches
+        .iter()
+        .map(|&(start, end, distance)| (&text[start..end], distance))
+        .collect();
+    assert_eq!(found, [("Tumour", 1), ("tumor", 0), ("humor", 1)]);
+    assert_eq!(fuzzy_matches(text, "tumor", 0, true).len(), 1);
+    assert_eq!(
+        fuzzy_matches("the tumour growth rate", "tumor growth", 1, false),
+        [(4, 17, 1)]
+    );
+}
+
+#[test]
+fn maps_inline_xrefs_to_their_targets() {
+    let xml = r#"<article><body><sec><title>Results</title>
+<p>Growth was slower (<xref ref-type="fig" rid="fig1">Figure 1</xref>) as reported <xref ref-type="bibr" rid="b1 b2">1,2</xref>.</p>
+</sec></body></article>"#;
+
+    let xrefs = extract_cross_references(xml).unwrap();
+
+    let targets: Vec<_> = xrefs
+        .iter()
+        .map(|x| (x.ref_type.as_deref(), x.rid.as_str()))
+        .collect();
+    assert_eq!(
+        targets,
+        [
+            (Some("fig"), "fig1"),
+            (Some("bibr"), "b1"),
+            (Some("bibr"), "b2")
+        ]
+    );
+    assert!(xrefs[0].context.contains("Growth was slower (Figure 1)"));
+}
+
+#[test]
+fn matches_namespace_prefixed_tags_by_local_name() {
+    let xml = r#"<jats:article xmlns:jats="http://jats.nlm.nih.gov">
+<jats:front><jats:article-meta>
+<jats:title-group><jats:article-title>Prefixed title</jats:article-title></jats:title-group>
+<jats:abstract><jats:p>An abstract.</jats:p></jats:abstract>
+</jats:article-meta></jats:front>
+</jats:article>"#;
+
+    let metadata = extract_article_metadata(xml, "ns.xml").unwrap();
+
+    assert_eq!(metadata.title.as_deref(), Some("Prefixed title"));
+    assert_eq!(metadata.abstract_text.as_deref(), Some("An abstract."));
+}
+
+#[test]
+fn reads_a_cc_by_license_from_the_permissions_block() {
+    let xml = r#"<article xmlns:xlink="http://www.w3.org/1999/xlink"><front><article-meta>
+<permissions>
+<copyright-statement>© 2024 Doe et al.</copyright-statement>
+<license license-type="open-access" xlink:href="https://creativecommons.org/licenses/by/4.0/">
+<license-p>This is an open access article distributed under the terms of the Creative Commons Attribution License.</license-p>
+</license>
+</permissions>
+</article-meta></front></article>"#;
+
+    let metadata = extract_article_metadata(xml, "cc_by.xml").unwrap();
+
+    assert_eq!(
+        metadata.license_url.as_deref(),
+        Some("https://creativecommons.org/licenses/by/4.0/")
+    );
+    assert_eq!(metadata.license_type.as_deref(), Some("open-access"));
+    assert_eq!(metadata.is_open_access, Some(true));
+    assert!(metadata
+        .license_text
+        .as_deref()
+        .is_some_and(|text| text.contains("Creative Commons Attribution")));
+}
+
+#[test]
+fn reads_the_license_url_from_an_ali_license_ref() {
+    let xml = r#"<article xmlns:ali="http://www.niso.org/schemas/ali/1.0/"><front><article-meta>
+<permissions><license>
+<ali:license_ref>https://creativecommons.org/licenses/by/4.0/</ali:license_ref>
+<license-p>This article is distributed under a CC BY license.</license-p>
+</license></permissions>
+</article-meta></front></article>"#;
+
+    let metadata = extract_article_metadata(xml, "ali.xml").unwrap();
+
+    assert_eq!(
+        metadata.license_url.as_deref(),
+        Some("https://creativecommons.org/licenses/by/4.0/")
+    );
+    assert_eq!(metadata.is_open_access, Some(true));
+}
+
+#[test]
+fn reports_contentless_articles_as_empty_rather_than_failed() {
+    let stub = temp_file("summary_stub.xml", "<article></article>");
+    let paths = vec![stub.to_string_lossy().into_owned()];
+
+    let metadata = read_and_extract(&paths[0], &ExtractOptions::default()).unwrap();
+    let mut output = Vec::new();
+    let summary =
+        write_ndjson_with_summary(&paths, &mut output, &ExtractOptions::default()).unwrap();
+    std::fs::remove_file(&stub).unwrap();
+
+    assert!(metadata.is_empty);
+    assert_eq!(summary.empty_documents, 1);
+    assert_eq!(summary.succeeded, 0);
+    assert_eq!(summary.parse_failures, 0);
+    assert!(!extract_article_metadata(ARTICLE, "a.xml").unwrap().is_empty);
+}
+
+#[test]
+fn leaves_abstract_only_articles_out_of_the_full_text_stream() {
+    let with_body = temp_file("split_body.xml", ARTICLE);
+    let abstract_only = temp_file(
+        "split_abstract.xml",
+        &ARTICLE
+            .replace("12345", "67890")
+            .replace("<body><p>Body text.</p></body>", ""),
+    );
+    let paths = vec![
+        with_body.to_string_lossy().into_owned(),
+        abstract_only.to_string_lossy().into_owned(),
+    ];
+
+    let (mut abstracts, mut full_texts) = (Vec::new(), Vec::new());
+    let counts = write_split_ndjson(
+        &paths,
+        &mut abstracts,
+        &mut full_texts,
+        &ExtractOptions::default(),
+    )
+    .unwrap();
+    std::fs::remove_file(&with_body).unwrap();
+    std::fs::remove_file(&abstract_only).unwrap();
+
+    assert_eq!(counts, (2, 1));
+    let abstracts = String::from_utf8(abstracts).unwrap();
+    let full_texts = String::from_utf8(full_texts).unwrap();
+    assert!(abstracts.lines().nth(1).unwrap().contains("\"67890\""));
+    assert!(!abstracts.contains("Body text."));
+    assert!(full_texts.contains("\"12345\""));
+    assert!(!full_texts.contains("67890"));
+}
+
+#[test]
+fn writes_a_valid_json_array() {
+    let first = temp_file("array1.xml", ARTICLE);
+    let second = temp_file("array2.xml", &ARTICLE.replace("12345", "67890"));
+    let paths = vec![
+        first.to_string_lossy().into_owned(),
+        "/nonexistent/article.xml".to_string(),
+        second.to_string_lossy().into_owned(),
+    ];
+
+    let mut output = Vec::new();
+    let written = write_json_array(&paths, &mut output, &ExtractOptions::default()).unwrap();
+    std::fs::remove_file(&first).unwrap();
+    std::fs::remove_file(&second).unwrap();
+
+    let articles: Vec<serde_json::Value> = serde_json::from_slice(&output).unwrap();
+    assert_eq!(written, 2);
+    assert_eq!(articles.len(), 2);
+    assert_eq!(articles[1]["pmid"], "67890");
+
+    let mut empty = Vec::new();
+    assert_eq!(
+        write_json_array(&[], &mut empty, &ExtractOptions::default()).unwrap(),
+        0
+    );
+    assert_eq!(
+        serde_json::from_slice::<Vec<serde_json::Value>>(&empty)
+            .unwrap()
+            .len(),
+        0
+    );
+}
+
+#[test]
+fn memory_mapped_and_buffered_reads_extract_the_same_articles() {
+    let second = ARTICLE
+        .replace("12345", "67890")
+        .replace("Native", "Mapped &amp; streamed");
+    let path = temp_file(
+        "articleset.xml",
+        &format!("<pmc-articleset>\n{ARTICLE}\n{second}\n</pmc-articleset>"),
+    );
+    let xml_path = path.to_string_lossy();
+    let options = ExtractOptions::default();
+
+    let mut mapped = Vec::new();
+    for_each_article(&xml_path, &options, |metadata| {
+        mapped.push(serde_json::to_string(&metadata.unwrap()).unwrap())
+    })
+    .unwrap();
+    let mut buffered = Vec::new();
+    for_each_article_buffered(&xml_path, &options, |metadata| {
+        buffered.push(serde_json::to_string(&metadata.unwrap()).unwrap())
+    })
+    .unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(mapped.len(), 2);
+    assert_eq!(mapped, buffered);
+    assert!(mapped[1].contains("Mapped & streamed extraction"));
+}
+
+#[test]
+fn calls_back_once_per_article_of_a_set_file() {
+    let articles = ["111", "222", "333"].map(|pmid| ARTICLE.replace("12345", pmid));
+    let path = temp_file(
+        "three_articles.xml",
+        &format!(
+            "<pmc-articleset>\n{}\n</pmc-articleset>",
+            articles.join("\n")
+        ),
+    );
+
+    let mut pmids = Vec::new();
+    for_each_article(
+        &path.to_string_lossy(),
+        &ExtractOptions::default(),
+        |metadata| pmids.push(metadata.unwrap().pmid),
+    )
+    .unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(
+        pmids,
+        vec![
+            Some("111".to_string()),
+            Some("222".to_string()),
+            Some("333".to_string())
+        ]
+    );
+}
+
+#[test]
+fn reads_the_middle_article_back_by_its_indexed_offset() {
+    let articles = ["111", "222", "333"].map(|pmid| {
+        format!(
+            "<PubmedArticle><MedlineCitation><PMID>{pmid}</PMID><Article>\
+             <ArticleTitle>Article {pmid}</ArticleTitle></Article></MedlineCitation>\
+             </PubmedArticle>"
+        )
+    });
+    let path = temp_file(
+        "indexed.xml",
+        &format!(
+            "<PubmedArticleSet>\n{}\n</PubmedArticleSet>",
+            articles.join("\n")
+        ),
+    );
+    let index_path = temp_file("indexed.tsv", "");
+    let xml_path = path.to_string_lossy();
+
+    let count = build_article_index(&xml_path, &index_path.to_string_lossy()).unwrap();
+    let index = std::fs::read_to_string(&index_path).unwrap();
+    let middle = index.lines().nth(2).unwrap();
+    let (offset, pmid) = middle.split_once('\t').unwrap();
+    let article = read_article_at(
+        &xml_path,
+        offset.parse().unwrap(),
+        &ExtractOptions::default(),
+    )
+    .unwrap();
+    std::fs::remove_file(&path).unwrap();
+    std::fs::remove_file(&index_path).unwrap();
+
+    assert_eq!(count, 3);
+    assert_eq!(pmid, "222");
+    assert_eq!(article.pmid.as_deref(), Some("222"));
+    assert_eq!(article.title.as_deref(), Some("Article 222"));
+}
+
+#[test]
+fn splits_sentences_but_not_abbreviations_or_decimals() {
+    assert_eq!(
+        split_sentences("We used e.g. PCR. Results followed."),
+        vec!["We used e.g. PCR.", "Results followed."]
+    );
+    assert_eq!(
+        split_sentences("As in Fig. 2 of Smith et al. (2019), p was 0.05. Next one!"),
+        vec![
+            "As in Fig. 2 of Smith et al. (2019), p was 0.05.",
+            "Next one!"
+        ]
+    );
+}
+
+#[test]
+fn counts_named_content_entities_by_type() {
+    let xml = r#"<article><body><p>Loss of <named-content content-type="gene"><italic>TP53</italic></named-content>
+and <named-content content-type="gene">BRCA1</named-content> in
+<named-content content-type="species"><italic>Mus musculus</italic></named-content>;
+<named-content content-type="gene"><italic>TP53</italic></named-content> again.</p></body></article>"#;
+
+    let entities = extract_named_entities(xml).unwrap();
+
+    let genes: Vec<_> = entities
+        .iter()
+        .filter(|entity| entity.entity_type == "gene")
+        .map(|entity| (entity.text.as_str(), entity.count))
+        .collect();
+    assert_eq!(genes, vec![("TP53", 2), ("BRCA1", 1)]);
+    assert_eq!(entities.len(), 3);
+}
+
+#[test]
+fn lists_each_media_of_a_supplementary_block() {
+    let xml = r#"<article><body><sec><title>Data</title>
+<supplementary-material id="S1"><label>Additional file 1</label>
+<caption><label>ignored</label><p>Raw counts</p></caption>
+<media xlink:href="counts.xlsx" mimetype="application" mime-subtype="vnd.ms-excel"/>
+<media xlink:href="counts.csv" mimetype="text" mime-subtype="csv"><caption><p>CSV</p></caption></media>
+</supplementary-material>
+</sec></body></article>"#;
+
+    let files = extract_supplementary(xml).unwrap();
+
+    let hrefs: Vec<_> = files.iter().map(|file| file.href.as_deref()).collect();
+    assert_eq!(hrefs, vec![Some("counts.xlsx"), Some("counts.csv")]);
+    assert!(files
+        .iter()
+        .all(|file| file.label.as_deref() == Some("Additional file 1")));
+    assert_eq!(files[1].mimetype.as_deref(), Some("text/csv"));
+}
+
+#[test]
+fn wraps_every_highlighted_range_in_markers() {
+    let context = "IL-6 and TNF; IL-6 again";
+
+    let highlighted = highlight_ranges(context, &[(0, 4), (14, 18)], "<b>", "</b>");
+
+    assert_eq!(highlighted, "<b>IL-6</b> and TNF; <b>IL-6</b> again");
+    assert_eq!(highlight_ranges(context, &[], "<b>", "</b>"), context);
+}
+
+#[test]
+fn counts_words_across_abstracts() {
+    let abstracts = [
+        "Gut microbiome diversity shapes the immune response.",
+        "The microbiome of infants: microbiome maturation and diet.",
+    ];
+
+    let mut counts = HashMap::new();
+    for text in abstracts {
+        count_words(text, 3, &mut counts);
+    }
+    let top = top_words(counts, Some(2));
+
+    assert_eq!(
+        top,
+        vec![("microbiome".to_string(), 3), ("the".to_string(), 2)]
+    );
+}
+
+#[test]
+fn reports_a_missing_abstract_as_incomplete() {
+    let complete = temp_file("complete.xml", ARTICLE);
+    let no_abstract = temp_file(
+        "no_abstract.xml",
+        &ARTICLE.replace("<abstract><p>An abstract.</p></abstract>", ""),
+    );
+    let paths = vec![
+        complete.to_string_lossy().into_owned(),
+        no_abstract.to_string_lossy().into_owned(),
+    ];
+    let fields = vec!["pmid".to_string(), "abstract".to_string()];
+
+    let df = validate_articles(&paths, &fields, &ExtractOptions::default()).unwrap();
+    std::fs::remove_file(&complete).unwrap();
+    std::fs::remove_file(&no_abstract).unwrap();
+
+    let has_pmid = df.column("has_pmid").unwrap().bool().unwrap();
+    let has_abstract = df.column("has_abstract").unwrap().bool().unwrap();
+    let is_complete = df.column("is_complete").unwrap().bool().unwrap();
+    assert_eq!(has_pmid.get(1), Some(true));
+    assert_eq!(has_abstract.get(0), Some(true));
+    assert_eq!(has_abstract.get(1), Some(false));
+    assert_eq!(is_complete.get(0), Some(true));
+    assert_eq!(is_complete.get(1), Some(false));
+}
+
+#[test]
+fn builds_a_dataframe_with_a_row_per_path() {
+    let path = temp_file("article.xml", ARTICLE);
+    let paths = vec![
+        path.to_string_lossy().into_owned(),
+        "/nonexistent/article.xml".to_string(),
+    ];
+
+    let df = articles_to_dataframe(&paths).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(df.height(), 2);
+    let pmids = df.column("pmid").unwrap().str().unwrap();
+    assert_eq!(pmids.get(0), Some("12345"));
+    assert_eq!(pmids.get(1), None);
+}
+
+#[test]
+fn builds_only_the_requested_columns_without_the_body() {
+    let path = temp_file("columns.xml", ARTICLE);
+    let paths = vec![path.to_string_lossy().into_owned()];
+    let columns = vec!["pmid".to_string(), "title".to_string()];
+    let options = ExtractOptions {
+        skip_body_text: true,
+        ..Default::default()
+    };
+
+    let df =
+        articles_to_dataframe_with_columns(&paths, &ExtractOptions::default(), &columns).unwrap();
+    let metadata = read_and_extract(&paths[0], &options).unwrap();
+    let unknown = articles_to_dataframe_with_columns(
+        &paths,
+        &ExtractOptions::default(),
+        &["body".to_string()],
+    );
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(df.get_column_names_str(), vec!["pmid", "title"]);
+    assert_eq!(
+        df.column("pmid").unwrap().str().unwrap().get(0),
+        Some("12345")
+    );
+    assert_eq!(metadata.full_text, None);
+    assert!(metadata.body_paragraphs.is_empty());
+    assert!(unknown.is_err());
+}
+
+#[test]
+fn assembles_a_struct_per_author() {
+    let xml = r#"<article><front><article-meta>
+<contrib-group>
+<contrib contrib-type="author" corresp="yes"><name><surname>Doe</surname><given-names>Jane</given-names></name>
+<xref ref-type="aff" rid="aff1"/><xref ref-type="aff" rid="aff2"/><email>jane@example.org</email></contrib>
+<contrib contrib-type="author"><name><surname>Roe</surname><given-names>Rick</given-names></name>
+<xref ref-type="aff" rid="aff2"/></contrib>
+</contrib-group>
+<aff id="aff1">Institute One</aff>
+<aff id="aff2">Institute Two</aff>
+</article-meta></front></article>"#;
+    let path = temp_file("detailed.xml", xml);
+
+    let df = articles_to_dataframe(&[path.to_string_lossy().into_owned()]).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let authors = df
+        .column("authors_detailed")
+        .unwrap()
+        .list()
+        .unwrap()
+        .get_as_series(0)
+        .unwrap();
+    let authors = authors.struct_().unwrap();
+    let affiliations = authors.field_by_name("affiliations").unwrap();
+    let affiliations = affiliations.list().unwrap();
+    assert_eq!(affiliations.get_as_series(0).unwrap().len(), 2);
+    assert_eq!(affiliations.get_as_series(1).unwrap().len(), 1);
+    let emails = authors.field_by_name("email").unwrap();
+    assert_eq!(emails.str().unwrap().get(0), Some("jane@example.org"));
+    assert_eq!(emails.str().unwrap().get(1), None);
+}
+
+#[test]
+fn falls_back_to_the_first_author_notes_email() {
+    let article = ARTICLE.replace(
+        "<abstract>",
+        "<author-notes><fn><p>Contact: <email>lab@example.org</email></p></fn></author-notes>\n<abstract>",
+    );
+
+    let metadata = extract_article_metadata(&article, "author_notes.xml").unwrap();
+
+    assert_eq!(metadata.corresponding_author, None);
+    assert_eq!(
+        metadata.corresponding_email.as_deref(),
+        Some("lab@example.org")
+    );
+}
+
+#[test]
+fn gives_the_corresponding_email_to_that_author_and_not_a_namesake() {
+    let xml = r#"<article><front><article-meta>
+<contrib-group>
+<contrib contrib-type="author"><name><surname>Wang</surname><given-names>Li</given-names></name></contrib>
+<contrib contrib-type="author"><name><surname>Wang</surname><given-names>Li</given-names></name>
+<xref ref-type="corresp" rid="cor1">*</xref></contrib>
+</contrib-group>
+<author-notes><corresp id="cor1">* Corresponding author. E-mail: <email>li.wang@example.org</email></corresp></author-notes>
+</article-meta></front></article>"#;
+    let path = temp_file("namesakes.xml", xml);
+
+    let metadata = extract_article_metadata(xml, "namesakes.xml").unwrap();
+    let df = articles_to_dataframe(&[path.to_string_lossy().into_owned()]).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(metadata.corresponding_author.as_deref(), Some("Wang, Li"));
+    assert_eq!(metadata.corresponding_author_index, Some(1));
+    assert_eq!(
+        metadata.corresponding_email.as_deref(),
+        Some("li.wang@example.org")
+    );
+    let authors = df
+        .column("authors_detailed")
+        .unwrap()
+        .list()
+        .unwrap()
+        .get_as_series(0)
+        .unwrap();
+    let emails = authors.struct_().unwrap().field_by_name("email").unwrap();
+    assert_eq!(emails.str().unwrap().get(0), None);
+    assert_eq!(emails.str().unwrap().get(1), Some("li.wang@example.org"));
+}
+
+#[test]
+fn counts_the_words_of_the_abstract_and_body() {
+    let article = ARTICLE
+        .replace("An abstract.", "Gene expression  varies\nacross tissues.")
+        .replace("Body text.", "Body text in four words.");
+    let path = temp_file("word_counts.xml", &article);
+
+    let df = articles_to_dataframe(&[path.to_string_lossy().into_owned()]).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let count = |column: &str| df.column(column).unwrap().u32().unwrap().get(0);
+    assert_eq!(count("abstract_word_count"), Some(5));
+    assert_eq!(count("full_text_word_count"), Some(5));
+}
+
+#[test]
+fn derives_first_and_last_author_columns() {
+    let contribs = ["Doe", "Roe", "Poe"]
+        .map(|surname| {
+            format!(
+                r#"<contrib contrib-type="author"><name><surname>{surname}</surname><given-names>J</given-names></name></contrib>"#
+            )
+        })
+        .concat();
+    let article = ARTICLE.replace(
+        r#"<contrib contrib-type="author"><name><surname>Doe</surname><given-names>Jane</given-names></name></contrib>"#,
+        &contribs,
+    );
+    let path = temp_file("authors.xml", &article);
+
+    let df = articles_to_dataframe(&[path.to_string_lossy().into_owned()]).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let first = df.column("first_author").unwrap().str().unwrap();
+    let last = df.column("last_author").unwrap().str().unwrap();
+    let count = df.column("author_count").unwrap().u32().unwrap();
+    assert_eq!(first.get(0), Some("Doe, J"));
+    assert_eq!(last.get(0), Some("Poe, J"));
+    assert_eq!(count.get(0), Some(3));
+}
+
+#[test]
+fn streams_xml_members_of_a_tar_gz() {
+    let mut tar = Vec::new();
+    tar.extend(tar_member("PMC1/article.nxml", ARTICLE.as_bytes()));
+    tar.extend(tar_member("PMC1/figure.jpg", b"not xml"));
+    tar.extend(tar_member(
+        "PMC2/other.xml",
+        ARTICLE.replace("12345", "67890").as_bytes(),
+    ));
+    tar.extend([0u8; 1024]);
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&tar).unwrap();
+    let archive = temp_file("bundle.tar.gz", "");
+    std::fs::write(&archive, encoder.finish().unwrap()).unwrap();
+
+    let mut articles = Vec::new();
+    for_each_archive_article(
+        &archive.to_string_lossy(),
+        &ExtractOptions::default(),
+        |member_path, metadata| articles.push((member_path.to_string(), metadata.unwrap())),
+    )
+    .unwrap();
+    std::fs::remove_file(&archive).unwrap();
+
+    assert_eq!(articles.len(), 2);
+    assert_eq!(articles[0].0, "PMC1/article.nxml");
+    assert_eq!(articles[0].1.file_path, "PMC1/article.nxml");
+    assert_eq!(articles[1].1.pmid.as_deref(), Some("67890"));
+}
+
+#[test]
+fn skips_archive_members_over_max_bytes_unread() {
+    let large = ARTICLE.replace("Body text.", &"Body text. ".repeat(100));
+    let mut tar = Vec::new();
+    tar.extend(tar_member("PMC1/large.nxml", large.as_bytes()));
+    tar.extend(tar_member("PMC2/small.nxml", ARTICLE.as_bytes()));
+    tar.extend([0u8; 1024]);
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&tar).unwrap();
+    let archive = temp_file("limited.tar.gz", "");
+    std::fs::write(&archive, encoder.finish().unwrap()).unwrap();
+
+    let mut results = Vec::new();
+    for_each_archive_article(
+        &archive.to_string_lossy(),
+        &ExtractOptions {
+            max_bytes: Some(ARTICLE.len() as u64),
+            ..Default::default()
+        },
+        |member_path, metadata| results.push((member_path.to_string(), metadata)),
+    )
+    .unwrap();
+    std::fs::remove_file(&archive).unwrap();
+
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].0, "PMC1/large.nxml");
+    assert!(matches!(
+        results[0].1,
+        Err(XmlProcessorError::TooLarge { size, limit })
+            if size == large.len() as u64 && limit == ARTICLE.len() as u64
+    ));
+    assert_eq!(
+        results[1].1.as_ref().unwrap().pmid.as_deref(),
+        Some("12345")
+    );
+}